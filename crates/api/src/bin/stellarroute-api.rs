@@ -1,8 +1,9 @@
 //! StellarRoute API Server Binary
 
 use sqlx::postgres::PgPoolOptions;
-use std::time::Duration;
-use stellarroute_api::{telemetry, Server, ServerConfig};
+use std::{sync::Arc, time::Duration};
+use stellarroute_api::{signing::QuoteSigner, telemetry, Server, ServerConfig};
+use stellarroute_routing::{export::ContractAsset, graph::AssetId, simulate::RouterState};
 use tracing::{error, info};
 
 #[tokio::main]
@@ -74,6 +75,16 @@ async fn main() {
         enable_cors: true,
         enable_compression: true,
         redis_url: std::env::var("REDIS_URL").ok(),
+        quote_signer: std::env::var("QUOTE_SIGNING_KEY").ok().map(|seed| {
+            match QuoteSigner::from_secret_seed(&seed) {
+                Ok(signer) => Arc::new(signer),
+                Err(e) => {
+                    error!("❌ Invalid QUOTE_SIGNING_KEY: {}", e);
+                    std::process::exit(1);
+                }
+            }
+        }),
+        router: router_state_from_env(),
     };
 
     // Create and start server
@@ -84,3 +95,45 @@ async fn main() {
         std::process::exit(1);
     }
 }
+
+/// Router contract state for swap simulation, from the `ROUTER_*` variables
+fn router_state_from_env() -> RouterState {
+    let allowed_tokens = router_var("ROUTER_ALLOWED_TOKENS")
+        .map(|list| {
+            list.split(',')
+                .map(|asset| {
+                    ContractAsset::from_asset_id(&AssetId::new(asset.trim())).unwrap_or_else(|e| {
+                        error!("❌ Invalid asset in ROUTER_ALLOWED_TOKENS: {}", e);
+                        std::process::exit(1);
+                    })
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    RouterState {
+        paused: parse_router_var("ROUTER_PAUSED").unwrap_or(false),
+        protocol_fee_bps: parse_router_var("ROUTER_FEE_BPS").unwrap_or(0),
+        sdex_adapter: router_var("ROUTER_SDEX_ADAPTER"),
+        allowed_tokens,
+        commit_threshold: parse_router_var("ROUTER_COMMIT_THRESHOLD"),
+    }
+}
+
+fn router_var(name: &str) -> Option<String> {
+    std::env::var(name)
+        .ok()
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+}
+
+/// A set `ROUTER_*` variable must parse; a wrong router state would make
+/// every simulation misleading
+fn parse_router_var<T: std::str::FromStr>(name: &str) -> Option<T> {
+    router_var(name).map(|v| {
+        v.parse().unwrap_or_else(|_| {
+            error!("❌ Invalid {}: {}", name, v);
+            std::process::exit(1);
+        })
+    })
+}
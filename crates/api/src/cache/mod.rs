@@ -92,9 +92,28 @@ pub mod keys {
         format!("orderbook:{}:{}", base, quote)
     }
 
+    /// Cache key for 24h pair statistics
+    pub fn ticker(base: &str, quote: &str) -> String {
+        format!("ticker:{}:{}", base, quote)
+    }
+
+    /// Storage key for an issued quote
+    pub fn firm_quote(id: &str) -> String {
+        format!("firm_quote:{}", id)
+    }
+
     /// Cache key for quote
-    pub fn quote(base: &str, quote: &str, amount: &str) -> String {
-        format!("quote:{}:{}:{}", base, quote, amount)
+    pub fn quote(
+        base: &str,
+        quote: &str,
+        amount: &str,
+        quote_type: &str,
+        slippage_bps: u32,
+    ) -> String {
+        format!(
+            "quote:{}:{}:{}:{}:{}",
+            base, quote, quote_type, amount, slippage_bps
+        )
     }
 }
 
@@ -106,6 +125,11 @@ mod tests {
     fn test_cache_keys() {
         assert_eq!(keys::pairs_list(), "pairs:list");
         assert_eq!(keys::orderbook("XLM", "USDC"), "orderbook:XLM:USDC");
-        assert_eq!(keys::quote("XLM", "USDC", "100"), "quote:XLM:USDC:100");
+        assert_eq!(keys::ticker("XLM", "USDC"), "ticker:XLM:USDC");
+        assert_eq!(
+            keys::quote("XLM", "USDC", "100", "sell", 50),
+            "quote:XLM:USDC:sell:100:50"
+        );
+        assert_eq!(keys::firm_quote("abc"), "firm_quote:abc");
     }
 }
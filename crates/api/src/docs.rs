@@ -3,8 +3,11 @@
 use utoipa::OpenApi;
 
 use crate::models::{
-    AssetInfo, ErrorResponse, HealthResponse, OrderbookLevel, OrderbookResponse, PairsResponse,
-    PathStep, QuoteResponse, TradingPair,
+    AssetInfo, BatchQuoteItem, BatchQuoteResponse, BatchQuoteResult, Candle, CandlesResponse,
+    ErrorResponse, HealthResponse, IssuedQuoteResponse, OrderbookHistoryResponse, OrderbookLevel,
+    OrderbookResponse, OrderbookSnapshot, PairsResponse, PathStep, PriceTicker, QuoteResponse,
+    QuoteSigningKeyResponse, QuoteType, SimulateRequest, SimulateResponse, SimulatedHop,
+    SimulationError, SnapshotLevel, TickerResponse, TradingPair,
 };
 
 /// OpenAPI documentation
@@ -14,7 +17,16 @@ use crate::models::{
         crate::routes::health::health_check,
         crate::routes::pairs::list_pairs,
         crate::routes::orderbook::get_orderbook,
+        crate::routes::orderbook::get_orderbook_history,
+        crate::routes::market::get_candles,
+        crate::routes::market::get_ticker,
         crate::routes::quote::get_quote,
+        crate::routes::quote::post_quotes,
+        crate::routes::quote::get_quote_by_id,
+        crate::routes::simulate::simulate_swap,
+        crate::routes::well_known::get_quote_signing_key,
+        crate::routes::ws::stream,
+        crate::routes::stream::stream_prices,
     ),
     components(schemas(
         HealthResponse,
@@ -23,8 +35,25 @@ use crate::models::{
         AssetInfo,
         OrderbookResponse,
         OrderbookLevel,
+        OrderbookHistoryResponse,
+        OrderbookSnapshot,
+        SnapshotLevel,
+        CandlesResponse,
+        Candle,
+        TickerResponse,
         QuoteResponse,
+        IssuedQuoteResponse,
         PathStep,
+        QuoteType,
+        QuoteSigningKeyResponse,
+        PriceTicker,
+        BatchQuoteItem,
+        BatchQuoteResponse,
+        BatchQuoteResult,
+        SimulateRequest,
+        SimulateResponse,
+        SimulatedHop,
+        SimulationError,
         ErrorResponse,
     )),
     tags(
@@ -5,6 +5,7 @@ use axum::{
     response::{IntoResponse, Response},
     Json,
 };
+use stellarroute_routing::error::RoutingError;
 use thiserror::Error;
 
 use crate::models::ErrorResponse;
@@ -41,8 +42,23 @@ pub enum ApiError {
 
 pub type Result<T> = std::result::Result<T, ApiError>;
 
-impl IntoResponse for ApiError {
-    fn into_response(self) -> Response {
+impl From<RoutingError> for ApiError {
+    fn from(err: RoutingError) -> Self {
+        match err {
+            RoutingError::NoRoute(..) | RoutingError::InsufficientLiquidity(_) => {
+                ApiError::NoRouteFound
+            }
+            RoutingError::InvalidAmount(msg) => ApiError::Validation(msg),
+            RoutingError::InvalidPair(msg) => ApiError::Validation(msg),
+            RoutingError::Overflow => ApiError::Validation("Amount too large".to_string()),
+            err => ApiError::Internal(anyhow::anyhow!(err)),
+        }
+    }
+}
+
+impl ApiError {
+    /// Status code and body this error is reported with
+    pub fn into_parts(self) -> (StatusCode, ErrorResponse) {
         let (status, error_type, message) = match self {
             ApiError::BadRequest(msg) => (StatusCode::BAD_REQUEST, "bad_request", msg),
             ApiError::NotFound(msg) => (StatusCode::NOT_FOUND, "not_found", msg),
@@ -66,7 +82,13 @@ impl IntoResponse for ApiError {
             ),
         };
 
-        let body = Json(ErrorResponse::new(error_type, message));
-        (status, body).into_response()
+        (status, ErrorResponse::new(error_type, message))
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let (status, body) = self.into_parts();
+        (status, Json(body)).into_response()
     }
 }
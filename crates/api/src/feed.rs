@@ -0,0 +1,419 @@
+//! Live liquidity feed
+//!
+//! Every update [`LiquiditySync`](crate::liquidity::LiquiditySync) applies
+//! to the routing engine is also published here, numbered with a sequence
+//! that increases by one per update. Streaming clients learn which orderbook
+//! price levels changed, and a gap in the sequence tells them they missed an
+//! update and should resync. The most recent events are kept so clients that
+//! reconnect can catch up on what they missed.
+
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap, VecDeque},
+    sync::{Arc, Mutex},
+};
+use stellarroute_routing::{
+    amount::{Amount, Price},
+    delta::{GraphDelta, SdexOffer},
+    graph::AssetId,
+};
+use tokio::sync::broadcast;
+use tracing::debug;
+
+/// Events buffered per subscriber before the slowest start missing some
+const FEED_CAPACITY: usize = 256;
+
+/// Past events kept for clients resuming a stream
+const FEED_HISTORY: usize = 1024;
+
+/// One side of a book, as `(selling, buying)`
+type Side = (AssetId, AssetId);
+
+/// A liquidity update as published on the feed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FeedEvent {
+    pub sequence: u64,
+    pub ledger: u64,
+    /// Price levels whose depth changed, in book-side then price order
+    pub levels: Vec<LevelChange>,
+}
+
+impl FeedEvent {
+    /// Whether any level of either side of the `base`/`quote` book changed
+    pub fn touches(&self, base: &AssetId, quote: &AssetId) -> bool {
+        self.levels.iter().any(|l| {
+            (&l.selling == base && &l.buying == quote) || (&l.selling == quote && &l.buying == base)
+        })
+    }
+}
+
+/// New depth at one price level of one side of a book
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LevelChange {
+    pub selling: AssetId,
+    pub buying: AssetId,
+    /// Units of `buying` per unit of `selling`
+    pub price: Price,
+    /// Total amount of `selling` offered at `price`; zero once the level is gone
+    pub amount: Amount,
+}
+
+/// State of a pair's book at one point of the feed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BookSnapshot {
+    pub sequence: u64,
+    pub ledger: u64,
+    /// Offers selling base for quote, by ascending price
+    pub asks: Vec<(Price, Amount)>,
+    /// Offers selling quote for base, by descending price
+    pub bids: Vec<(Price, Amount)>,
+}
+
+/// Best prices of a pair's book at one point of the feed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TopOfBook {
+    pub sequence: u64,
+    pub ledger: u64,
+    /// Most quote asset offered per unit of base asset
+    pub best_bid: Option<Price>,
+    /// Least quote asset asked per unit of base asset
+    pub best_ask: Option<Price>,
+}
+
+/// A subscription to the feed that may pick up after an earlier one
+pub struct FeedSubscription {
+    /// Sequence of the latest event before `events`
+    pub sequence: u64,
+    /// Events after the requested sequence, or `None` when they are no
+    /// longer (or were never) known
+    pub missed: Option<Vec<Arc<FeedEvent>>>,
+    pub events: broadcast::Receiver<Arc<FeedEvent>>,
+}
+
+#[derive(Default)]
+struct Books {
+    sequence: u64,
+    ledger: u64,
+    offers: HashMap<u64, SdexOffer>,
+    levels: HashMap<Side, BTreeMap<Price, Amount>>,
+    history: VecDeque<Arc<FeedEvent>>,
+}
+
+impl Books {
+    /// Apply `deltas`, returning the new depth of every level they touched
+    fn apply(&mut self, deltas: &[GraphDelta]) -> Vec<LevelChange> {
+        let mut touched: BTreeMap<Side, BTreeSet<Price>> = BTreeMap::new();
+        for delta in deltas {
+            let (removed, added) = match delta {
+                GraphDelta::RemoveOffer { id } => (self.offers.remove(id), None),
+                GraphDelta::UpsertOffer(offer) if offer.amount == 0 => {
+                    (self.offers.remove(&offer.id), None)
+                }
+                GraphDelta::UpsertOffer(offer) => (
+                    self.offers.insert(offer.id, offer.clone()),
+                    Some(offer.clone()),
+                ),
+                // Pools have no price levels
+                _ => continue,
+            };
+            for (offer, sign) in removed
+                .iter()
+                .map(|o| (o, -1))
+                .chain(added.iter().map(|o| (o, 1)))
+            {
+                let Ok(price) = offer.price() else {
+                    debug!("Skipping unpriceable offer {} on the feed", offer.id);
+                    continue;
+                };
+                let side = (offer.selling.clone(), offer.buying.clone());
+                let level = self
+                    .levels
+                    .entry(side.clone())
+                    .or_default()
+                    .entry(price)
+                    .or_default();
+                *level = Amount::from_stroops(level.stroops() + sign * offer.amount);
+                touched.entry(side).or_default().insert(price);
+            }
+        }
+
+        let mut changes = Vec::new();
+        for (side, prices) in touched {
+            let levels = self.levels.entry(side.clone()).or_default();
+            for price in prices {
+                let amount = levels.get(&price).copied().unwrap_or_default();
+                if !amount.is_positive() {
+                    levels.remove(&price);
+                }
+                changes.push(LevelChange {
+                    selling: side.0.clone(),
+                    buying: side.1.clone(),
+                    price,
+                    amount: amount.max(Amount::ZERO),
+                });
+            }
+            if levels.is_empty() {
+                self.levels.remove(&side);
+            }
+        }
+        changes
+    }
+
+    fn side(&self, selling: &AssetId, buying: &AssetId) -> Vec<(Price, Amount)> {
+        self.levels
+            .get(&(selling.clone(), buying.clone()))
+            .map(|levels| levels.iter().map(|(p, a)| (*p, *a)).collect())
+            .unwrap_or_default()
+    }
+}
+
+/// Publishes liquidity updates to streaming clients
+#[derive(Clone)]
+pub struct LiquidityFeed {
+    books: Arc<Mutex<Books>>,
+    events: broadcast::Sender<Arc<FeedEvent>>,
+}
+
+impl LiquidityFeed {
+    pub fn new() -> Self {
+        let (events, _) = broadcast::channel(FEED_CAPACITY);
+        Self {
+            books: Arc::default(),
+            events,
+        }
+    }
+
+    /// Publish an update the routing engine has applied
+    ///
+    /// Updates that change no offers are still published, so subscribers see
+    /// the ledger advance and can re-price.
+    pub fn publish(&self, ledger: u64, deltas: &[GraphDelta]) -> Arc<FeedEvent> {
+        let mut books = self.books.lock().expect("feed lock poisoned");
+        let levels = books.apply(deltas);
+        books.sequence += 1;
+        books.ledger = books.ledger.max(ledger);
+        let event = Arc::new(FeedEvent {
+            sequence: books.sequence,
+            ledger: books.ledger,
+            levels,
+        });
+        if books.history.len() == FEED_HISTORY {
+            books.history.pop_front();
+        }
+        books.history.push_back(event.clone());
+        // Sent under the lock, so events reach subscribers in sequence order;
+        // having no subscribers is not an error
+        let _ = self.events.send(event.clone());
+        event
+    }
+
+    /// Receive every event published from now on
+    pub fn subscribe(&self) -> broadcast::Receiver<Arc<FeedEvent>> {
+        self.events.subscribe()
+    }
+
+    /// Receive every event published from now on, along with those published
+    /// after sequence `after` if they are still kept
+    pub fn subscribe_after(&self, after: Option<u64>) -> FeedSubscription {
+        let books = self.books.lock().expect("feed lock poisoned");
+        let missed = after.and_then(|after| {
+            // Events after `after` are all kept when the history reaches back
+            // to it; a sequence from the future predates a restart
+            let oldest = books
+                .history
+                .front()
+                .map_or(books.sequence + 1, |e| e.sequence);
+            (after + 1 >= oldest && after <= books.sequence).then(|| {
+                books
+                    .history
+                    .iter()
+                    .filter(|e| e.sequence > after)
+                    .cloned()
+                    .collect()
+            })
+        });
+        FeedSubscription {
+            sequence: books.sequence,
+            missed,
+            events: self.events.subscribe(),
+        }
+    }
+
+    /// Best bid and ask for `base`/`quote` as of the latest event
+    pub fn top_of_book(&self, base: &AssetId, quote: &AssetId) -> TopOfBook {
+        let books = self.books.lock().expect("feed lock poisoned");
+        let best = |selling: &AssetId, buying: &AssetId| {
+            books
+                .levels
+                .get(&(selling.clone(), buying.clone()))
+                .and_then(|levels| levels.keys().next().copied())
+        };
+        TopOfBook {
+            sequence: books.sequence,
+            ledger: books.ledger,
+            // Bids sell quote for base, so the cheapest is the best bid
+            best_bid: best(quote, base).and_then(|price| price.invert().ok()),
+            best_ask: best(base, quote),
+        }
+    }
+
+    /// The book for `base`/`quote` as of the latest event
+    ///
+    /// A receiver taken before this call sees every event after the
+    /// snapshot's sequence.
+    pub fn book(&self, base: &AssetId, quote: &AssetId) -> BookSnapshot {
+        let books = self.books.lock().expect("feed lock poisoned");
+        let mut bids = books.side(quote, base);
+        bids.reverse();
+        BookSnapshot {
+            sequence: books.sequence,
+            ledger: books.ledger,
+            asks: books.side(base, quote),
+            bids,
+        }
+    }
+
+    /// Sequence of the latest event
+    pub fn sequence(&self) -> u64 {
+        self.books.lock().expect("feed lock poisoned").sequence
+    }
+}
+
+impl Default for LiquidityFeed {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn usdc() -> AssetId {
+        AssetId::credit("USDC", "GISSUER")
+    }
+
+    fn offer(id: u64, selling: AssetId, buying: AssetId, price_d: i64, amount: i128) -> SdexOffer {
+        SdexOffer {
+            id,
+            selling,
+            buying,
+            price_n: 1,
+            price_d,
+            amount,
+        }
+    }
+
+    fn ask(id: u64, price_d: i64, amount: i128) -> GraphDelta {
+        GraphDelta::UpsertOffer(offer(id, AssetId::native(), usdc(), price_d, amount))
+    }
+
+    fn price(d: i128) -> Price {
+        Price::new(1, d).unwrap()
+    }
+
+    #[test]
+    fn test_events_carry_consecutive_sequences() {
+        let feed = LiquidityFeed::new();
+        let mut events = feed.subscribe();
+        feed.publish(10, &[ask(1, 10, 100)]);
+        feed.publish(11, &[]);
+
+        let first = events.try_recv().unwrap();
+        let second = events.try_recv().unwrap();
+        assert_eq!((first.sequence, first.ledger), (1, 10));
+        assert_eq!((second.sequence, second.ledger), (2, 11));
+        assert!(second.levels.is_empty());
+        assert_eq!(feed.sequence(), 2);
+    }
+
+    #[test]
+    fn test_levels_report_aggregate_depth() {
+        let feed = LiquidityFeed::new();
+        feed.publish(1, &[ask(1, 10, 100), ask(2, 10, 50), ask(3, 20, 70)]);
+
+        // Shrinking one offer and removing another leaves one level changed
+        // and one emptied
+        let event = feed.publish(2, &[ask(1, 10, 40), GraphDelta::RemoveOffer { id: 3 }]);
+        let depth: Vec<_> = event.levels.iter().map(|l| (l.price, l.amount)).collect();
+        assert_eq!(
+            depth,
+            vec![
+                (price(20), Amount::ZERO),
+                (price(10), Amount::from_stroops(90)),
+            ]
+        );
+
+        let book = feed.book(&AssetId::native(), &usdc());
+        assert_eq!(book.sequence, 2);
+        assert_eq!(book.asks, vec![(price(10), Amount::from_stroops(90))]);
+        assert!(book.bids.is_empty());
+    }
+
+    #[test]
+    fn test_top_of_book_quotes_bids_in_quote_asset() {
+        let feed = LiquidityFeed::new();
+        feed.publish(
+            1,
+            &[
+                ask(1, 10, 100),
+                ask(2, 8, 100),
+                // 11 and 12 XLM per USDC: bids of 1/11 and 1/12 USDC per XLM
+                GraphDelta::UpsertOffer(SdexOffer {
+                    price_n: 11,
+                    ..offer(3, usdc(), AssetId::native(), 1, 100)
+                }),
+                GraphDelta::UpsertOffer(SdexOffer {
+                    price_n: 12,
+                    ..offer(4, usdc(), AssetId::native(), 1, 100)
+                }),
+            ],
+        );
+
+        let top = feed.top_of_book(&AssetId::native(), &usdc());
+        assert_eq!(top.best_ask, Some(price(10)));
+        assert_eq!(top.best_bid, Some(price(11)));
+        let reversed = feed.top_of_book(&usdc(), &AssetId::native());
+        assert_eq!(reversed.best_ask, Some(Price::new(11, 1).unwrap()));
+    }
+
+    #[test]
+    fn test_resuming_returns_kept_events_only() {
+        let feed = LiquidityFeed::new();
+        for ledger in 1..=3 {
+            feed.publish(ledger, &[]);
+        }
+
+        let resumed = feed.subscribe_after(Some(1));
+        assert_eq!(resumed.sequence, 3);
+        let missed: Vec<_> = resumed.missed.unwrap().iter().map(|e| e.sequence).collect();
+        assert_eq!(missed, vec![2, 3]);
+        assert!(feed.subscribe_after(Some(3)).missed.unwrap().is_empty());
+        assert!(feed.subscribe_after(Some(0)).missed.is_some());
+        // Sequences from before a restart cannot be resumed
+        assert!(feed.subscribe_after(Some(9)).missed.is_none());
+        assert!(feed.subscribe_after(None).missed.is_none());
+
+        for ledger in 4..=(FEED_HISTORY as u64 + 3) {
+            feed.publish(ledger, &[]);
+        }
+        assert!(feed.subscribe_after(Some(1)).missed.is_none());
+        assert!(feed.subscribe_after(Some(3)).missed.is_some());
+    }
+
+    #[test]
+    fn test_book_orders_bids_from_best() {
+        let feed = LiquidityFeed::new();
+        feed.publish(
+            1,
+            &[
+                GraphDelta::UpsertOffer(offer(1, usdc(), AssetId::native(), 2, 10)),
+                GraphDelta::UpsertOffer(offer(2, usdc(), AssetId::native(), 4, 10)),
+            ],
+        );
+
+        let book = feed.book(&AssetId::native(), &usdc());
+        let prices: Vec<_> = book.bids.iter().map(|(p, _)| *p).collect();
+        assert_eq!(prices, vec![price(2), price(4)]);
+        assert_eq!(feed.book(&usdc(), &AssetId::native()).asks.len(), 2);
+    }
+}
@@ -0,0 +1,145 @@
+//! Network ledger
+//!
+//! The indexer records the latest ledger the network has closed in
+//! `ingestion_state`. [`LedgerSync`] mirrors it into a [`LedgerClock`], which
+//! handlers read for the ledger a transaction submitted now would land in.
+//! The liquidity graph's own watermark is no substitute: it is the latest
+//! ledger any indexed offer changed in, which stands still on quiet books.
+
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use std::{
+    sync::{Arc, PoisonError, RwLock},
+    time::Duration,
+};
+use stellarroute_indexer::ledger::LATEST_LEDGER_KEY;
+use tokio::task::JoinHandle;
+use tracing::{debug, warn};
+
+use crate::error::Result;
+
+/// How often the latest ledger is read from the database
+pub const REFRESH_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Age past which the latest known ledger no longer tells where the network
+/// is, about six ledgers
+pub const MAX_LEDGER_AGE: Duration = Duration::from_secs(30);
+
+/// Latest closed ledger known to the API
+#[derive(Debug, Clone, Default)]
+pub struct LedgerClock {
+    latest: Arc<RwLock<Option<ClosedLedger>>>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct ClosedLedger {
+    sequence: u64,
+    closed_at: DateTime<Utc>,
+}
+
+impl LedgerClock {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record that ledger `sequence` closed at `closed_at`
+    pub fn observe(&self, sequence: u64, closed_at: DateTime<Utc>) {
+        *self.latest.write().unwrap_or_else(PoisonError::into_inner) = Some(ClosedLedger {
+            sequence,
+            closed_at,
+        });
+    }
+
+    /// Ledger a transaction submitted now would land in, the one after the
+    /// latest closed
+    ///
+    /// `None` when no ledger closed within [`MAX_LEDGER_AGE`] is known, as
+    /// when the indexer is down or behind.
+    pub fn current(&self) -> Option<u64> {
+        self.current_at(Utc::now())
+    }
+
+    fn current_at(&self, now: DateTime<Utc>) -> Option<u64> {
+        let latest = (*self.latest.read().unwrap_or_else(PoisonError::into_inner))?;
+        let age = (now - latest.closed_at).to_std().unwrap_or_default();
+        (age <= MAX_LEDGER_AGE).then_some(latest.sequence + 1)
+    }
+}
+
+/// Mirrors the latest ledger recorded by the indexer into a [`LedgerClock`]
+pub struct LedgerSync {
+    db: PgPool,
+    clock: LedgerClock,
+}
+
+impl LedgerSync {
+    pub fn new(db: PgPool, clock: LedgerClock) -> Self {
+        Self { db, clock }
+    }
+
+    /// Read the latest ledger; returns its sequence, or `None` when the
+    /// indexer has not recorded one
+    pub async fn refresh(&self) -> Result<Option<u64>> {
+        let row: Option<(String, DateTime<Utc>)> =
+            sqlx::query_as("select value, updated_at from ingestion_state where key = $1")
+                .bind(LATEST_LEDGER_KEY)
+                .fetch_optional(&self.db)
+                .await?;
+        let Some((value, closed_at)) = row else {
+            return Ok(None);
+        };
+        match value.parse() {
+            Ok(sequence) => {
+                self.clock.observe(sequence, closed_at);
+                Ok(Some(sequence))
+            }
+            Err(_) => {
+                warn!("Ignoring malformed latest ledger '{}'", value);
+                Ok(None)
+            }
+        }
+    }
+
+    /// Refresh every `every` in the background
+    pub fn spawn(self, every: Duration) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut ticks = tokio::time::interval(every);
+            ticks.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            loop {
+                ticks.tick().await;
+                match self.refresh().await {
+                    Ok(Some(sequence)) => debug!("Latest ledger is {}", sequence),
+                    Ok(None) => debug!("No latest ledger recorded yet"),
+                    Err(e) => warn!("Latest ledger refresh failed: {}", e),
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_current_ledger_follows_the_latest_closed() {
+        let clock = LedgerClock::new();
+        let now = Utc::now();
+        assert_eq!(clock.current_at(now), None);
+
+        clock.observe(1_000, now - chrono::Duration::seconds(4));
+        assert_eq!(clock.current_at(now), Some(1_001));
+    }
+
+    #[test]
+    fn test_stale_ledger_is_not_reported() {
+        let clock = LedgerClock::new();
+        let now = Utc::now();
+        clock.observe(1_000, now - chrono::Duration::seconds(31));
+        assert_eq!(clock.current_at(now), None);
+
+        // A close time ahead of the API's clock is as fresh as it gets
+        clock.observe(1_001, now + chrono::Duration::seconds(2));
+        assert_eq!(clock.current_at(now), Some(1_002));
+    }
+}
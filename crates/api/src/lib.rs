@@ -5,11 +5,16 @@
 pub mod cache;
 pub mod docs;
 pub mod error;
+pub mod feed;
 pub mod handlers;
+pub mod ledger;
+pub mod liquidity;
 pub mod middleware;
 pub mod models;
+pub mod quote_store;
 pub mod routes;
 pub mod server;
+pub mod signing;
 pub mod state;
 pub mod telemetry;
 
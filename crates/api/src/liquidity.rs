@@ -0,0 +1,327 @@
+//! Liquidity graph synchronisation
+//!
+//! Keeps the routing engine's graph in step with the offers the indexer has
+//! written to the database. Each refresh reads the open offers, compares them
+//! with what the engine was last given, and applies only the difference as a
+//! [`GraphUpdate`], so unchanged books keep their cached candidate paths.
+
+use sqlx::{PgPool, Row};
+use std::{collections::HashMap, sync::Arc, time::Duration};
+use stellarroute_routing::{
+    amount::{Amount, Price},
+    delta::{GraphDelta, GraphUpdate, SdexOffer},
+    graph::AssetId,
+    RoutingEngine,
+};
+use tokio::task::JoinHandle;
+use tracing::{debug, warn};
+
+use crate::{
+    error::{ApiError, Result},
+    feed::LiquidityFeed,
+};
+
+/// How often the graph is refreshed from the database
+pub const REFRESH_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Mirrors indexed SDEX offers into a [`RoutingEngine`]
+pub struct LiquiditySync {
+    db: PgPool,
+    engine: Arc<RoutingEngine>,
+    feed: LiquidityFeed,
+    /// Offers as last applied to the engine
+    offers: HashMap<u64, SdexOffer>,
+    ledger: u64,
+}
+
+impl LiquiditySync {
+    pub fn new(db: PgPool, engine: Arc<RoutingEngine>, feed: LiquidityFeed) -> Self {
+        Self {
+            db,
+            engine,
+            feed,
+            offers: HashMap::new(),
+            ledger: 0,
+        }
+    }
+
+    /// Bring the engine up to date with the database, publishing the update
+    /// to the feed
+    ///
+    /// Returns the number of offer changes applied. An update the engine
+    /// rejects is applied nowhere: the engine keeps its graph, nothing is
+    /// published, and the next refresh diffs against the same offers.
+    pub async fn refresh(&mut self) -> Result<usize> {
+        let (offers, ledger) = load_offers(&self.db).await?;
+        let deltas = diff_offers(&self.offers, &offers);
+        // Removals alone do not advance the latest ledger seen in the table
+        let ledger = ledger.max(self.ledger);
+
+        let applied = deltas.len();
+        if applied > 0 || ledger > self.ledger {
+            let update = GraphUpdate { ledger, deltas };
+            self.engine.apply_update(&update).map_err(|e| {
+                ApiError::Internal(anyhow::anyhow!("Failed to update liquidity graph: {}", e))
+            })?;
+            self.feed.publish(update.ledger, &update.deltas);
+        }
+        self.offers = offers;
+        self.ledger = ledger;
+        Ok(applied)
+    }
+
+    /// Refresh every `every` in the background
+    pub fn spawn(mut self, every: Duration) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut ticks = tokio::time::interval_at(tokio::time::Instant::now() + every, every);
+            ticks.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            loop {
+                ticks.tick().await;
+                match self.refresh().await {
+                    Ok(changes) => debug!(
+                        "Liquidity graph refreshed at ledger {} ({} offer changes)",
+                        self.ledger, changes
+                    ),
+                    Err(e) => warn!("Liquidity graph refresh failed: {}", e),
+                }
+            }
+        })
+    }
+}
+
+/// Every usable open offer, with the latest ledger any of them was modified at
+async fn load_offers(db: &PgPool) -> Result<(HashMap<u64, SdexOffer>, u64)> {
+    let rows = sqlx::query(
+        r#"
+        select
+          offer_id,
+          selling_asset_type, selling_asset_code, selling_asset_issuer,
+          buying_asset_type, buying_asset_code, buying_asset_issuer,
+          amount::text as amount,
+          price::text as price,
+          price_n,
+          price_d,
+          last_modified_ledger
+        from active_offers
+        "#,
+    )
+    .fetch_all(db)
+    .await?;
+
+    let mut offers = HashMap::with_capacity(rows.len());
+    let mut ledger = 0;
+    for row in rows {
+        let id: i64 = row.get("offer_id");
+        let selling = asset_id(
+            &row.get::<String, _>("selling_asset_type"),
+            row.get("selling_asset_code"),
+            row.get("selling_asset_issuer"),
+        );
+        let buying = asset_id(
+            &row.get::<String, _>("buying_asset_type"),
+            row.get("buying_asset_code"),
+            row.get("buying_asset_issuer"),
+        );
+        let offer = match (selling, buying) {
+            (Some(selling), Some(buying)) => to_offer(
+                id as u64,
+                selling,
+                buying,
+                &row.get::<String, _>("amount"),
+                &row.get::<String, _>("price"),
+                row.get("price_n"),
+                row.get("price_d"),
+            ),
+            _ => None,
+        };
+        match offer {
+            Some(offer) => {
+                ledger = ledger.max(row.get::<i64, _>("last_modified_ledger") as u64);
+                offers.insert(offer.id, offer);
+            }
+            None => debug!("Skipping unusable offer {}", id),
+        }
+    }
+    Ok((offers, ledger))
+}
+
+/// Routing asset for an `assets` row; credit assets need code and issuer
+fn asset_id(asset_type: &str, code: Option<String>, issuer: Option<String>) -> Option<AssetId> {
+    match (asset_type, code, issuer) {
+        ("native", _, _) => Some(AssetId::native()),
+        (_, Some(code), Some(issuer)) => Some(AssetId::credit(&code, &issuer)),
+        _ => None,
+    }
+}
+
+/// Offer in the routing engine's terms, or `None` when the stored values
+/// cannot be priced exactly
+fn to_offer(
+    id: u64,
+    selling: AssetId,
+    buying: AssetId,
+    amount: &str,
+    price: &str,
+    price_n: Option<i64>,
+    price_d: Option<i64>,
+) -> Option<SdexOffer> {
+    let amount: Amount = amount.parse().ok()?;
+    let (price_n, price_d) = match (price_n, price_d) {
+        (Some(n), Some(d)) => (n, d),
+        // Older rows only carry the decimal price
+        _ => {
+            let price: Price = price.parse().ok()?;
+            (
+                price.numerator().try_into().ok()?,
+                price.denominator().try_into().ok()?,
+            )
+        }
+    };
+    if selling == buying || price_n <= 0 || price_d <= 0 || amount.stroops() < 0 {
+        return None;
+    }
+    Some(SdexOffer {
+        id,
+        selling,
+        buying,
+        price_n,
+        price_d,
+        amount: amount.stroops(),
+    })
+}
+
+/// Changes taking the engine from `known` offers to `current`, by offer id
+fn diff_offers(
+    known: &HashMap<u64, SdexOffer>,
+    current: &HashMap<u64, SdexOffer>,
+) -> Vec<GraphDelta> {
+    let mut removed: Vec<u64> = known
+        .keys()
+        .filter(|id| !current.contains_key(id))
+        .copied()
+        .collect();
+    removed.sort_unstable();
+
+    let mut changed: Vec<&SdexOffer> = current
+        .values()
+        .filter(|offer| known.get(&offer.id) != Some(offer))
+        .collect();
+    changed.sort_unstable_by_key(|offer| offer.id);
+
+    removed
+        .into_iter()
+        .map(|id| GraphDelta::RemoveOffer { id })
+        .chain(
+            changed
+                .into_iter()
+                .map(|offer| GraphDelta::UpsertOffer(offer.clone())),
+        )
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn usdc() -> AssetId {
+        AssetId::credit("USDC", "GISSUER")
+    }
+
+    fn offer(id: u64, amount: i128) -> SdexOffer {
+        SdexOffer {
+            id,
+            selling: AssetId::native(),
+            buying: usdc(),
+            price_n: 1,
+            price_d: 10,
+            amount,
+        }
+    }
+
+    fn book(offers: &[SdexOffer]) -> HashMap<u64, SdexOffer> {
+        offers.iter().map(|o| (o.id, o.clone())).collect()
+    }
+
+    #[test]
+    fn test_asset_id_requires_issuer_for_credit_assets() {
+        assert_eq!(asset_id("native", None, None), Some(AssetId::native()));
+        assert_eq!(
+            asset_id(
+                "credit_alphanum4",
+                Some("USDC".to_string()),
+                Some("GISSUER".to_string())
+            ),
+            Some(usdc())
+        );
+        assert_eq!(
+            asset_id("credit_alphanum4", Some("USDC".to_string()), None),
+            None
+        );
+    }
+
+    #[test]
+    fn test_offer_prefers_exact_price_fraction() {
+        let exact = to_offer(
+            1,
+            AssetId::native(),
+            usdc(),
+            "12.50000000000000",
+            "0.33333333333333",
+            Some(1),
+            Some(3),
+        )
+        .unwrap();
+        assert_eq!((exact.price_n, exact.price_d), (1, 3));
+        assert_eq!(exact.amount, 125_000_000);
+
+        let decimal = to_offer(2, AssetId::native(), usdc(), "1", "0.25", None, None).unwrap();
+        assert_eq!(decimal.price().unwrap(), "0.25".parse().unwrap());
+    }
+
+    #[test]
+    fn test_unusable_offers_are_skipped() {
+        let xlm = AssetId::native;
+        // Same asset on both sides
+        assert!(to_offer(1, xlm(), xlm(), "1", "1", Some(1), Some(1)).is_none());
+        // More precision than a stroop
+        assert!(to_offer(1, xlm(), usdc(), "0.00000001", "1", Some(1), Some(1)).is_none());
+        // Non-positive price
+        assert!(to_offer(1, xlm(), usdc(), "1", "0", Some(0), Some(1)).is_none());
+    }
+
+    #[test]
+    fn test_diff_only_emits_changes() {
+        let known = book(&[offer(1, 100), offer(2, 200), offer(3, 300)]);
+        let current = book(&[offer(1, 100), offer(3, 250), offer(4, 400)]);
+
+        assert_eq!(
+            diff_offers(&known, &current),
+            vec![
+                GraphDelta::RemoveOffer { id: 2 },
+                GraphDelta::UpsertOffer(offer(3, 250)),
+                GraphDelta::UpsertOffer(offer(4, 400)),
+            ]
+        );
+        assert!(diff_offers(&current, &current).is_empty());
+    }
+
+    #[test]
+    fn test_diff_applies_cleanly_to_engine() {
+        let engine = RoutingEngine::new();
+        let known = book(&[offer(1, 100), offer(2, 200)]);
+        let current = book(&[offer(2, 150)]);
+        for deltas in [
+            diff_offers(&HashMap::new(), &known),
+            diff_offers(&known, &current),
+        ] {
+            engine
+                .apply_update(&GraphUpdate { ledger: 7, deltas })
+                .unwrap();
+        }
+
+        let graph = engine.snapshot();
+        let edge = graph.edges_from(&usdc()).next().unwrap();
+        assert_eq!(edge.depth(), 150);
+        assert_eq!(graph.watermark().ledger, 7);
+    }
+}
@@ -10,8 +10,15 @@
 //! | `/api/v1/pairs`       | 60 req / min  | 60 s   |
 //! | `/api/v1/orderbook/*` | 30 req / min  | 60 s   |
 //! | `/api/v1/quote/*`     | 100 req / min | 60 s   |
+//! | `/api/v1/quotes`      | shares quote  | 60 s   |
 //! | everything else       | 200 req / min | 60 s   |
 //!
+//! A batch request to `/api/v1/quotes` counts once against the quote limit,
+//! however many quotes it holds. A WebSocket connection to `/api/v1/ws`, or a
+//! price stream from `/api/v1/stream/prices`, counts once, when it is opened,
+//! against the default limit; clients that need live orderbooks should
+//! stream them there rather than poll.
+//!
 //! # Response headers
 //!
 //! Every response (allowed or denied) receives:
@@ -504,6 +511,7 @@ mod tests {
         assert_eq!(cfg.for_path("/api/v1/pairs").max_requests, 60);
         assert_eq!(cfg.for_path("/api/v1/orderbook/XLM/USDC").max_requests, 30);
         assert_eq!(cfg.for_path("/api/v1/quote/XLM/USDC").max_requests, 100);
+        assert_eq!(cfg.for_path("/api/v1/quotes").max_requests, 100);
         assert_eq!(cfg.for_path("/health").max_requests, 200);
         assert_eq!(cfg.for_path("/swagger-ui").max_requests, 200);
     }
@@ -1,6 +1,8 @@
 //! API request models
 
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+use stellarroute_routing::{export::ContractRoute, simulate::ContractSwapParams};
+use utoipa::ToSchema;
 
 /// Query parameters for quote endpoint
 #[derive(Debug, Deserialize)]
@@ -10,14 +12,183 @@ pub struct QuoteParams {
     /// Type of quote (buy or sell)
     #[serde(default = "default_quote_type")]
     pub quote_type: QuoteType,
+    /// Slippage tolerated below the quoted output, in basis points
+    #[serde(default = "default_slippage_bps")]
+    pub slippage_bps: u32,
+}
+
+/// Query parameters for the orderbook history endpoint
+#[derive(Debug, Default, Deserialize)]
+pub struct OrderbookHistoryParams {
+    /// Earliest snapshot time, as a Unix timestamp
+    pub from: Option<i64>,
+    /// Latest snapshot time, as a Unix timestamp
+    pub to: Option<i64>,
+    /// Earliest ledger
+    pub from_ledger: Option<u64>,
+    /// Latest ledger
+    pub to_ledger: Option<u64>,
+    /// Keep only the last snapshot of every `interval` seconds
+    pub interval: Option<u64>,
+    /// Most snapshots returned (default: 500)
+    pub limit: Option<usize>,
+}
+
+/// Query parameters for the candles endpoint
+#[derive(Debug, Default, Deserialize)]
+pub struct CandleParams {
+    /// Candle width (default: 1h)
+    #[serde(default)]
+    pub interval: CandleInterval,
+    /// Earliest candle time, as a Unix timestamp
+    pub from: Option<i64>,
+    /// End of the range (exclusive), as a Unix timestamp (default: now)
+    pub to: Option<i64>,
+    /// Most candles returned (default: 500)
+    pub limit: Option<usize>,
+}
+
+/// Width of one candle
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize, ToSchema)]
+pub enum CandleInterval {
+    #[serde(rename = "1m")]
+    OneMinute,
+    #[serde(rename = "5m")]
+    FiveMinutes,
+    #[default]
+    #[serde(rename = "1h")]
+    OneHour,
+    #[serde(rename = "1d")]
+    OneDay,
+}
+
+impl CandleInterval {
+    pub fn seconds(&self) -> i64 {
+        match self {
+            CandleInterval::OneMinute => 60,
+            CandleInterval::FiveMinutes => 5 * 60,
+            CandleInterval::OneHour => 60 * 60,
+            CandleInterval::OneDay => 24 * 60 * 60,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CandleInterval::OneMinute => "1m",
+            CandleInterval::FiveMinutes => "5m",
+            CandleInterval::OneHour => "1h",
+            CandleInterval::OneDay => "1d",
+        }
+    }
+}
+
+/// One quote in a `POST /api/v1/quotes` batch
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct BatchQuoteItem {
+    /// Base asset ("native", "CODE" or "CODE:ISSUER")
+    pub base: String,
+    /// Quote asset ("native", "CODE" or "CODE:ISSUER")
+    pub quote: String,
+    /// Amount to trade (default: 1)
+    pub amount: Option<String>,
+    #[serde(default = "default_quote_type")]
+    pub quote_type: QuoteType,
+    /// Slippage tolerated below the quoted output, in basis points
+    #[serde(default = "default_slippage_bps")]
+    pub slippage_bps: u32,
+}
+
+/// Body of `POST /api/v1/simulate`: the router contract's `SwapParams`
+///
+/// Amounts are in stroops and ledgers are sequence numbers. A `recipient`
+/// may be sent but is ignored, as no check reads it.
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct SimulateRequest {
+    /// Route in the router contract's format
+    #[schema(value_type = Object)]
+    pub route: ContractRoute,
+    pub amount_in: i128,
+    pub min_amount_out: i128,
+    /// Last ledger the swap may execute in
+    pub deadline: u64,
+    /// First ledger the swap may execute in
+    #[serde(default)]
+    pub not_before: u64,
+    /// Highest price impact accepted, in basis points; 0 disables the check
+    #[serde(default)]
+    pub max_price_impact_bps: u32,
+    /// Largest shortfall of the output against `route.estimated_output`, in
+    /// basis points; 0 disables the check
+    #[serde(default)]
+    pub max_execution_spread_bps: u32,
+}
+
+impl From<SimulateRequest> for ContractSwapParams {
+    fn from(request: SimulateRequest) -> Self {
+        Self {
+            route: request.route,
+            amount_in: request.amount_in,
+            min_amount_out: request.min_amount_out,
+            deadline: request.deadline,
+            not_before: request.not_before,
+            max_price_impact_bps: request.max_price_impact_bps,
+            max_execution_spread_bps: request.max_execution_spread_bps,
+        }
+    }
+}
+
+/// Query parameters for the price stream
+#[derive(Debug, Deserialize)]
+pub struct PriceStreamParams {
+    /// Comma-separated `base/quote` pairs, e.g. `native/USDC:G...,EURC:G.../USDC:G...`
+    pub pairs: String,
+}
+
+/// Message sent by a client over `/api/v1/ws`
+#[derive(Debug, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum StreamRequest {
+    Subscribe(StreamSubscription),
+    Unsubscribe { subscription: u64 },
+}
+
+/// Stream to open on a WebSocket connection
+#[derive(Debug, Deserialize)]
+pub struct StreamSubscription {
+    pub channel: StreamChannel,
+    /// Base asset ("native", "CODE" or "CODE:ISSUER")
+    pub base: String,
+    /// Quote asset ("native", "CODE" or "CODE:ISSUER")
+    pub quote: String,
+    /// Amount to quote (quote channel only, default: 1)
+    pub amount: Option<String>,
+    #[serde(default = "default_quote_type")]
+    pub quote_type: QuoteType,
+    /// Slippage tolerated below the quoted output, in basis points
+    #[serde(default = "default_slippage_bps")]
+    pub slippage_bps: u32,
+}
+
+/// Kinds of stream a WebSocket client can subscribe to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StreamChannel {
+    /// Orderbook level changes for a pair
+    Orderbook,
+    /// A quote for a pair and amount, re-priced on every update
+    Quote,
 }
 
 fn default_quote_type() -> QuoteType {
     QuoteType::Sell
 }
 
+fn default_slippage_bps() -> u32 {
+    50
+}
+
 /// Type of quote requested
-#[derive(Debug, Deserialize, Clone, Copy)]
+#[derive(Debug, Deserialize, Clone, Copy, ToSchema)]
 #[serde(rename_all = "lowercase")]
 pub enum QuoteType {
     /// Selling the base asset
@@ -26,6 +197,15 @@ pub enum QuoteType {
     Buy,
 }
 
+impl QuoteType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            QuoteType::Sell => "sell",
+            QuoteType::Buy => "buy",
+        }
+    }
+}
+
 /// Asset identifier in path parameters
 #[derive(Debug, Deserialize)]
 pub struct AssetPath {
@@ -1,8 +1,11 @@
 //! API response models
 
 use serde::{Deserialize, Serialize};
+use stellarroute_routing::export::ContractRoute;
 use utoipa::ToSchema;
 
+use super::request::StreamChannel;
+
 /// Per-component health status value
 pub type ComponentStatus = String;
 
@@ -38,6 +41,10 @@ pub struct TradingPair {
     pub offer_count: i64,
     /// RFC-3339 timestamp of the most recent offer update
     pub last_updated: Option<String>,
+    /// Price of the most recent trade, in counter asset
+    pub last_price: Option<String>,
+    /// Base asset traded over the last 24 hours
+    pub volume_24h: String,
 }
 
 /// Asset information
@@ -115,9 +122,106 @@ pub struct OrderbookLevel {
     pub total: String,
 }
 
-/// Price quote response
+/// Orderbook snapshots of a pair over time
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct OrderbookHistoryResponse {
+    pub base_asset: AssetInfo,
+    pub quote_asset: AssetInfo,
+    /// Snapshots by ascending ledger
+    pub snapshots: Vec<OrderbookSnapshot>,
+    /// Pass as `from_ledger` for the next page; absent on the last page
+    pub next_from_ledger: Option<u64>,
+}
+
+/// The orderbook of a pair as captured at one ledger
+///
+/// Prices and amounts are as stored, at full precision.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct OrderbookSnapshot {
+    /// Capture time, as a Unix timestamp
+    pub timestamp: i64,
+    pub ledger: u64,
+    pub bids: Vec<SnapshotLevel>,
+    pub asks: Vec<SnapshotLevel>,
+    /// Best ask minus best bid; absent when either side is empty
+    pub spread: Option<String>,
+    /// Average of best bid and best ask; absent when either side is empty
+    pub mid_price: Option<String>,
+    pub total_bid_volume: String,
+    pub total_ask_volume: String,
+}
+
+/// One offer in an orderbook snapshot
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct SnapshotLevel {
+    pub price: String,
+    pub amount: String,
+    pub offer_id: i64,
+}
+
+/// OHLCV candles of a pair
 #[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct CandlesResponse {
+    pub base_asset: AssetInfo,
+    pub quote_asset: AssetInfo,
+    /// Candle width ("1m", "5m", "1h" or "1d")
+    pub interval: String,
+    /// Candles by ascending time; intervals without trades are left out
+    pub candles: Vec<Candle>,
+}
+
+/// Trades of one interval, priced in quote asset per base asset
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct Candle {
+    /// Start of the interval, as a Unix timestamp
+    pub time: i64,
+    pub open: String,
+    pub high: String,
+    pub low: String,
+    pub close: String,
+    /// Base asset traded
+    pub volume: String,
+    /// Quote asset traded
+    pub quote_volume: String,
+    pub trades: i64,
+}
+
+/// 24 hour statistics of a pair
+///
+/// Prices are in quote asset per base asset and absent when nothing traded
+/// in the window.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct TickerResponse {
+    pub base_asset: AssetInfo,
+    pub quote_asset: AssetInfo,
+    pub open: Option<String>,
+    pub high: Option<String>,
+    pub low: Option<String>,
+    pub close: Option<String>,
+    /// Base asset traded
+    pub volume: String,
+    /// Quote asset traded
+    pub quote_volume: String,
+    pub trades: i64,
+    /// `close - open`
+    pub change: Option<String>,
+    /// Change relative to `open`, in percent
+    pub change_percent: Option<String>,
+    /// Mid price of the latest orderbook snapshot
+    pub mid_price: Option<String>,
+    /// Time of the last trade in the window, as a Unix timestamp
+    pub last_trade_at: Option<i64>,
+    /// Start of the window, as a Unix timestamp
+    pub from: i64,
+    /// End of the window, as a Unix timestamp
+    pub to: i64,
+}
+
+/// Price quote response
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct QuoteResponse {
+    /// Id to fetch this quote back by, or to execute it
+    pub quote_id: String,
     pub base_asset: AssetInfo,
     pub quote_asset: AssetInfo,
     pub amount: String,
@@ -125,11 +229,196 @@ pub struct QuoteResponse {
     pub total: String,
     pub quote_type: String,
     pub path: Vec<PathStep>,
+    /// Execution price against the spot price of every step, in percent
+    pub price_impact: String,
+    /// Swap fees paid along the path and the router's fee, in the asset
+    /// received
+    pub fee: String,
+    /// Least amount received with the requested slippage, after the
+    /// router's fee; a buy receives at least `amount`
+    pub min_received: String,
+    /// Most of the quote asset a buy sends with the requested slippage;
+    /// absent for a sell
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_sent: Option<String>,
     pub timestamp: i64,
+    /// Unix timestamp after which the quote can no longer be executed
+    pub valid_until: i64,
+    /// Base64 ed25519 signature over the quote, when the API signs quotes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature: Option<String>,
 }
 
-/// Step in a trading path
+/// An issued quote, with the route it was priced on
+///
+/// The quote's signature covers the route, so the two are always served
+/// together.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct IssuedQuoteResponse {
+    #[serde(flatten)]
+    pub quote: QuoteResponse,
+    /// Route in the router contract's format, to pass to `execute_swap` as
+    /// is; absent when the router cannot execute it, e.g. SDEX hops without
+    /// an adapter configured
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<Object>)]
+    pub route: Option<ContractRoute>,
+}
+
+/// Key that quote signatures can be verified with
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct QuoteSigningKeyResponse {
+    /// Always "ed25519"
+    pub algorithm: String,
+    /// Public key as a Stellar account id ("G...")
+    pub public_key: String,
+    /// Format of the signed message, e.g. "stellarroute-quote-v2"
+    pub message_version: String,
+}
+
+/// Quotes for a batch request, all priced on one snapshot of the liquidity
+/// graph
+#[derive(Debug, Serialize, ToSchema)]
+pub struct BatchQuoteResponse {
+    /// Ledger the liquidity snapshot reflects
+    pub ledger: u64,
+    /// One result per requested quote, in request order
+    pub results: Vec<BatchQuoteResult>,
+    pub timestamp: i64,
+}
+
+/// Outcome of one quote in a batch: either `quote` or `error` is set
+#[derive(Debug, Serialize, ToSchema)]
+pub struct BatchQuoteResult {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub quote: Option<IssuedQuoteResponse>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<ErrorResponse>,
+}
+
+/// Outcome of `POST /api/v1/simulate`
+///
+/// Amounts are in stroops, and filled in as far as the swap got before
+/// failing.
 #[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct SimulateResponse {
+    /// Whether the router would execute the swap
+    pub success: bool,
+    /// Error the router would return instead
+    pub error: Option<SimulationError>,
+    /// Hops run, in order
+    pub hops: Vec<SimulatedHop>,
+    /// Output paid to the recipient, after the router fee
+    pub amount_out: Option<i128>,
+    /// Router fee, in the output asset
+    pub fee_amount: Option<i128>,
+    /// Price impact as the router books it, in basis points
+    pub price_impact_bps: Option<u32>,
+    /// Shortfall of the output against the route's estimate, in basis points
+    pub spread_bps: Option<i128>,
+    /// Ledger the swap was simulated in; null when the network's current
+    /// ledger is not known, in which case the swap's ledger window was not
+    /// checked
+    pub ledger: Option<u64>,
+    pub timestamp: i64,
+}
+
+/// Router contract error a simulated swap fails with
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct SimulationError {
+    /// `ContractError` code
+    pub code: u32,
+    /// `ContractError` variant, e.g. "SlippageExceeded"
+    pub name: String,
+    pub message: String,
+}
+
+/// Amounts through one hop of a simulated swap
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct SimulatedHop {
+    /// Contract the router calls for the hop
+    pub pool: String,
+    pub amount_in: i128,
+    pub amount_out: i128,
+}
+
+/// Message sent to a client over `/api/v1/ws`
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum StreamMessage {
+    Subscribed {
+        subscription: u64,
+        channel: StreamChannel,
+    },
+    Unsubscribed {
+        subscription: u64,
+    },
+    Orderbook(OrderbookUpdate),
+    Quote(Box<QuoteUpdate>),
+    /// A request that could not be served
+    Error {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        subscription: Option<u64>,
+        error: ErrorResponse,
+    },
+}
+
+/// Orderbook levels of a subscribed pair at one feed sequence
+#[derive(Debug, Serialize)]
+pub struct OrderbookUpdate {
+    pub subscription: u64,
+    /// Feed sequence; consecutive updates differ by exactly one
+    pub sequence: u64,
+    pub ledger: u64,
+    /// Whether this is the full book rather than the levels that changed
+    pub snapshot: bool,
+    pub asks: Vec<BookLevel>,
+    pub bids: Vec<BookLevel>,
+}
+
+/// Depth at one orderbook price; an amount of zero removes the level
+#[derive(Debug, Serialize)]
+pub struct BookLevel {
+    pub price: String,
+    pub amount: String,
+}
+
+/// A subscribed quote, re-priced at one feed sequence: either `quote` or
+/// `error` is set
+#[derive(Debug, Serialize)]
+pub struct QuoteUpdate {
+    pub subscription: u64,
+    pub sequence: u64,
+    /// Ledger the liquidity the quote was priced on reflects
+    pub ledger: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub quote: Option<IssuedQuoteResponse>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<ErrorResponse>,
+}
+
+/// Top of book for a pair, as sent by the price stream
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct PriceTicker {
+    /// Base asset ("native" or "CODE:ISSUER")
+    pub base: String,
+    /// Quote asset ("native" or "CODE:ISSUER")
+    pub quote: String,
+    /// Highest price bid for the base asset, in quote asset
+    pub best_bid: Option<String>,
+    /// Lowest price asked for the base asset, in quote asset
+    pub best_ask: Option<String>,
+    pub mid_price: Option<String>,
+    /// `best_ask - best_bid`; negative while the book is crossed
+    pub spread: Option<String>,
+    /// Spread relative to the mid price, in basis points
+    pub spread_bps: Option<i64>,
+    /// Ledger the prices reflect
+    pub ledger: u64,
+}
+
+/// Step in a trading path
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct PathStep {
     pub from_asset: AssetInfo,
     pub to_asset: AssetInfo,
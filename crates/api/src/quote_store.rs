@@ -0,0 +1,221 @@
+//! Firm quote storage
+//!
+//! Every quote the API hands out is kept, with the route it was priced on in
+//! the router contract's format, until a while after it expires, so it can
+//! be fetched back by id and executed as quoted. Quotes live in Redis when a
+//! cache is configured and in process memory otherwise, or when Redis cannot
+//! take them.
+
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, sync::Arc, time::Duration};
+use stellarroute_routing::export::ContractRoute;
+use tokio::{sync::Mutex, time::Instant};
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::{
+    cache::{self, CacheManager},
+    models::{IssuedQuoteResponse, QuoteResponse},
+};
+
+/// How long a quote can be executed for after it is issued
+pub const QUOTE_TTL: Duration = Duration::from_secs(30);
+
+/// How long a quote can still be fetched after it has expired
+pub const QUOTE_RETENTION: Duration = Duration::from_secs(15 * 60);
+
+/// Ledgers that close within [`QUOTE_TTL`], at about five seconds each
+pub const QUOTE_TTL_LEDGERS: u64 = QUOTE_TTL.as_secs() / 5;
+
+/// A quote together with the route it was priced on
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FirmQuote {
+    pub quote: QuoteResponse,
+    /// `None` when the router contract cannot execute the route, e.g. SDEX
+    /// hops without an adapter configured, or when the network's current
+    /// ledger is unknown, so the route could not be given an expiry
+    pub route: Option<ContractRoute>,
+}
+
+impl FirmQuote {
+    pub fn id(&self) -> &str {
+        &self.quote.quote_id
+    }
+
+    /// The quote as served, with its route
+    pub fn into_response(self) -> IssuedQuoteResponse {
+        IssuedQuoteResponse {
+            quote: self.quote,
+            route: self.route,
+        }
+    }
+}
+
+enum Backend {
+    Redis(Mutex<CacheManager>),
+    InMemory,
+    /// A Redis that refuses every write
+    #[cfg(test)]
+    Unavailable,
+}
+
+/// Issued quotes by id
+#[derive(Clone)]
+pub struct QuoteStore {
+    backend: Arc<Backend>,
+    /// Quotes by id, with the instant each can be dropped: every quote
+    /// without Redis, and the ones Redis failed to take with it
+    memory: Arc<Mutex<HashMap<String, (FirmQuote, Instant)>>>,
+}
+
+impl QuoteStore {
+    fn new(backend: Backend) -> Self {
+        Self {
+            backend: Arc::new(backend),
+            memory: Arc::default(),
+        }
+    }
+
+    /// Store quotes in Redis
+    pub fn redis(cache: CacheManager) -> Self {
+        Self::new(Backend::Redis(Mutex::new(cache)))
+    }
+
+    /// Store quotes in process memory (single instance deployments and tests)
+    pub fn in_memory() -> Self {
+        Self::new(Backend::InMemory)
+    }
+
+    #[cfg(test)]
+    fn unavailable() -> Self {
+        Self::new(Backend::Unavailable)
+    }
+
+    /// Keep `quote` until [`QUOTE_RETENTION`] after it expires
+    ///
+    /// A quote Redis cannot take is kept in process memory instead, so an
+    /// outage costs other instances the quote rather than failing it.
+    pub async fn insert(&self, quote: &FirmQuote) {
+        let keep_for = QUOTE_TTL + QUOTE_RETENTION;
+        let shared = match &*self.backend {
+            Backend::Redis(cache) => Some(
+                cache
+                    .lock()
+                    .await
+                    .set(&cache::keys::firm_quote(quote.id()), quote, keep_for)
+                    .await
+                    .map_err(|e| e.to_string()),
+            ),
+            Backend::InMemory => None,
+            #[cfg(test)]
+            Backend::Unavailable => Some(Err("connection refused".to_string())),
+        };
+        match shared {
+            Some(Ok(())) => return,
+            Some(Err(e)) => warn!(
+                "Failed to store quote {} in Redis, keeping it in memory: {}",
+                quote.id(),
+                e
+            ),
+            None => {}
+        }
+
+        let now = Instant::now();
+        let mut quotes = self.memory.lock().await;
+        quotes.retain(|_, (_, until)| *until > now);
+        quotes.insert(quote.id().to_string(), (quote.clone(), now + keep_for));
+    }
+
+    /// A quote issued within the retention period, expired or not
+    pub async fn get(&self, id: &Uuid) -> Option<FirmQuote> {
+        let id = id.to_string();
+        if let Backend::Redis(cache) = &*self.backend {
+            if let Some(quote) = cache.lock().await.get(&cache::keys::firm_quote(&id)).await {
+                return Some(quote);
+            }
+        }
+        self.memory
+            .lock()
+            .await
+            .get(&id)
+            .filter(|(_, until)| *until > Instant::now())
+            .map(|(quote, _)| quote.clone())
+    }
+}
+
+impl Default for QuoteStore {
+    fn default() -> Self {
+        Self::in_memory()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::AssetInfo;
+
+    fn quote() -> FirmQuote {
+        FirmQuote {
+            quote: QuoteResponse {
+                quote_id: Uuid::new_v4().to_string(),
+                base_asset: AssetInfo::native(),
+                quote_asset: AssetInfo::credit("USDC".to_string(), Some("GISSUER".to_string())),
+                amount: "1.0000000".to_string(),
+                price: "0.1000000".to_string(),
+                total: "0.1000000".to_string(),
+                quote_type: "sell".to_string(),
+                path: vec![],
+                price_impact: "0.00".to_string(),
+                fee: "0.0000000".to_string(),
+                min_received: "0.0995000".to_string(),
+                max_sent: None,
+                timestamp: 0,
+                valid_until: 30,
+                signature: None,
+            },
+            route: Some(ContractRoute {
+                hops: vec![],
+                estimated_output: 1_000_000,
+                min_output: 995_000,
+                expires_at: 6,
+            }),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_in_memory_store_returns_inserted_quotes() {
+        let store = QuoteStore::in_memory();
+        let quote = quote();
+        store.insert(&quote).await;
+
+        let id = Uuid::parse_str(quote.id()).unwrap();
+        let found = store.get(&id).await.unwrap();
+        assert_eq!(found.quote.quote_id, quote.quote.quote_id);
+        assert_eq!(found.route, quote.route);
+        assert!(store.get(&Uuid::new_v4()).await.is_none());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_in_memory_store_drops_quotes_after_retention() {
+        let store = QuoteStore::in_memory();
+        let quote = quote();
+        let id = Uuid::parse_str(quote.id()).unwrap();
+        store.insert(&quote).await;
+
+        tokio::time::advance(QUOTE_TTL + QUOTE_RETENTION - Duration::from_secs(1)).await;
+        assert!(store.get(&id).await.is_some());
+        tokio::time::advance(Duration::from_secs(1)).await;
+        assert!(store.get(&id).await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_quotes_redis_refuses_are_kept_in_memory() {
+        let store = QuoteStore::unavailable();
+        let quote = quote();
+        store.insert(&quote).await;
+
+        let id = Uuid::parse_str(quote.id()).unwrap();
+        let found = store.get(&id).await.unwrap();
+        assert_eq!(found.quote.quote_id, quote.quote.quote_id);
+    }
+}
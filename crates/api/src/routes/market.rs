@@ -0,0 +1,393 @@
+//! Market data endpoints
+//!
+//! Candles and 24h statistics are computed from the trades the indexer
+//! stores in `sdex_trades`. Horizon reports each trade with its own
+//! base/counter orientation, so both orientations of a pair are read and
+//! priced in quote asset per base asset.
+
+use axum::{
+    extract::{Path, Query, State},
+    Json,
+};
+use chrono::{DateTime, Utc};
+use sqlx::Row;
+use std::{sync::Arc, time::Duration};
+use tracing::debug;
+
+use crate::{
+    cache,
+    error::{ApiError, Result},
+    models::{
+        request::{AssetPath, CandleInterval, CandleParams},
+        Candle, CandlesResponse, TickerResponse,
+    },
+    routes::orderbook::{asset_path_to_info, find_asset_id},
+    state::AppState,
+};
+
+/// Candles returned by a request unless it asks for fewer
+pub const DEFAULT_CANDLE_LIMIT: usize = 500;
+
+/// Most candles one request can return
+pub const MAX_CANDLE_LIMIT: usize = 1_000;
+
+/// Get OHLCV candles for a trading pair
+///
+/// Without `from`, returns the last `limit` intervals up to `to`.
+#[utoipa::path(
+    get,
+    path = "/api/v1/candles/{base}/{quote}",
+    tag = "trading",
+    params(
+        ("base" = String, Path, description = "Base asset (e.g., 'native', 'USDC', or 'USDC:ISSUER')"),
+        ("quote" = String, Path, description = "Quote asset (e.g., 'native', 'USDC', or 'USDC:ISSUER')"),
+        ("interval" = Option<String>, Query, description = "Candle width: 1m, 5m, 1h or 1d (default: 1h)"),
+        ("from" = Option<i64>, Query, description = "Earliest candle time (Unix timestamp)"),
+        ("to" = Option<i64>, Query, description = "End of the range, exclusive (Unix timestamp, default: now)"),
+        ("limit" = Option<usize>, Query, description = "Most candles returned (default: 500, max: 1000)"),
+    ),
+    responses(
+        (status = 200, description = "Candles", body = CandlesResponse),
+        (status = 400, description = "Invalid asset or parameters", body = ErrorResponse),
+        (status = 404, description = "Asset not found", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse),
+    )
+)]
+pub async fn get_candles(
+    State(state): State<Arc<AppState>>,
+    Path((base, quote)): Path<(String, String)>,
+    Query(params): Query<CandleParams>,
+) -> Result<Json<CandlesResponse>> {
+    debug!(
+        "Fetching candles for {}/{} with params: {:?}",
+        base, quote, params
+    );
+
+    let query = CandleQuery::parse(&params, Utc::now())?;
+    let base_asset = AssetPath::parse(&base)
+        .map_err(|e| ApiError::InvalidAsset(format!("Invalid base asset: {}", e)))?;
+    let quote_asset = AssetPath::parse(&quote)
+        .map_err(|e| ApiError::InvalidAsset(format!("Invalid quote asset: {}", e)))?;
+    let base_id = find_asset_id(&state, &base_asset).await?;
+    let quote_id = find_asset_id(&state, &quote_asset).await?;
+
+    let rows = sqlx::query(
+        r#"
+        select
+          floor(extract(epoch from ledger_close_time) / $5)::bigint * $5 as time,
+          round((array_agg(price order by operation_id, trade_index))[1], 14)::text as open,
+          round(max(price), 14)::text as high,
+          round(min(price), 14)::text as low,
+          round((array_agg(price order by operation_id desc, trade_index desc))[1], 14)::text as close,
+          sum(base_volume)::text as volume,
+          sum(counter_volume)::text as quote_volume,
+          count(*) as trades
+        from pair_trades($1, $2, $3, $4)
+        group by 1
+        order by 1
+        limit $6
+        "#,
+    )
+    .bind(base_id)
+    .bind(quote_id)
+    .bind(query.from)
+    .bind(query.to)
+    .bind(query.interval.seconds())
+    .bind(query.limit as i64)
+    .fetch_all(&state.db)
+    .await?;
+
+    let candles: Vec<Candle> = rows
+        .into_iter()
+        .map(|row| Candle {
+            time: row.get("time"),
+            open: row.get("open"),
+            high: row.get("high"),
+            low: row.get("low"),
+            close: row.get("close"),
+            volume: row.get("volume"),
+            quote_volume: row.get("quote_volume"),
+            trades: row.get("trades"),
+        })
+        .collect();
+
+    debug!(
+        "Candles for {}/{}: {} {} candles",
+        base,
+        quote,
+        candles.len(),
+        query.interval.as_str()
+    );
+
+    Ok(Json(CandlesResponse {
+        base_asset: asset_path_to_info(&base_asset),
+        quote_asset: asset_path_to_info(&quote_asset),
+        interval: query.interval.as_str().to_string(),
+        candles,
+    }))
+}
+
+/// Get 24 hour statistics for a trading pair
+///
+/// Open, high, low, close, volume and change over the trades of the last 24
+/// hours, with the mid price of the latest orderbook snapshot.
+#[utoipa::path(
+    get,
+    path = "/api/v1/ticker/{base}/{quote}",
+    tag = "trading",
+    params(
+        ("base" = String, Path, description = "Base asset (e.g., 'native', 'USDC', or 'USDC:ISSUER')"),
+        ("quote" = String, Path, description = "Quote asset (e.g., 'native', 'USDC', or 'USDC:ISSUER')"),
+    ),
+    responses(
+        (status = 200, description = "24h statistics", body = TickerResponse),
+        (status = 400, description = "Invalid asset", body = ErrorResponse),
+        (status = 404, description = "Asset not found", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse),
+    )
+)]
+pub async fn get_ticker(
+    State(state): State<Arc<AppState>>,
+    Path((base, quote)): Path<(String, String)>,
+) -> Result<Json<TickerResponse>> {
+    debug!("Fetching ticker for {}/{}", base, quote);
+
+    // Try to get from cache first
+    if let Some(cache) = &state.cache {
+        if let Ok(mut cache) = cache.try_lock() {
+            if let Some(cached) = cache
+                .get::<TickerResponse>(&cache::keys::ticker(&base, &quote))
+                .await
+            {
+                debug!("Returning cached ticker for {}/{}", base, quote);
+                return Ok(Json(cached));
+            }
+        }
+    }
+
+    let base_asset = AssetPath::parse(&base)
+        .map_err(|e| ApiError::InvalidAsset(format!("Invalid base asset: {}", e)))?;
+    let quote_asset = AssetPath::parse(&quote)
+        .map_err(|e| ApiError::InvalidAsset(format!("Invalid quote asset: {}", e)))?;
+    let base_id = find_asset_id(&state, &base_asset).await?;
+    let quote_id = find_asset_id(&state, &quote_asset).await?;
+
+    let to = Utc::now();
+    let from = to - chrono::Duration::hours(24);
+    let row = sqlx::query(
+        r#"
+        with stats as (
+          select
+            (array_agg(price order by operation_id, trade_index))[1] as open,
+            max(price) as high,
+            min(price) as low,
+            (array_agg(price order by operation_id desc, trade_index desc))[1] as close,
+            coalesce(sum(base_volume), 0::numeric(30, 14)) as volume,
+            coalesce(sum(counter_volume), 0::numeric(30, 14)) as quote_volume,
+            count(*) as trades,
+            max(ledger_close_time) as last_trade_at
+          from pair_trades($1, $2, $3, $4)
+        )
+        select
+          round(open, 14)::text as open,
+          round(high, 14)::text as high,
+          round(low, 14)::text as low,
+          round(close, 14)::text as close,
+          volume::text as volume,
+          quote_volume::text as quote_volume,
+          trades,
+          round(close - open, 14)::text as change,
+          round((close - open) * 100 / open, 2)::text as change_percent,
+          last_trade_at,
+          (
+            select round(
+              case when tp.base_asset_id = $1 then s.mid_price else 1 / s.mid_price end,
+              14
+            )::text
+            from orderbook_snapshots s
+            join trading_pairs tp on tp.id = s.trading_pair_id
+            where ((tp.base_asset_id = $1 and tp.counter_asset_id = $2)
+                or (tp.base_asset_id = $2 and tp.counter_asset_id = $1))
+              and s.mid_price > 0
+            order by s.snapshot_time desc
+            limit 1
+          ) as mid_price
+        from stats
+        "#,
+    )
+    .bind(base_id)
+    .bind(quote_id)
+    .bind(from)
+    .bind(to)
+    .fetch_one(&state.db)
+    .await?;
+
+    let response = TickerResponse {
+        base_asset: asset_path_to_info(&base_asset),
+        quote_asset: asset_path_to_info(&quote_asset),
+        open: row.get("open"),
+        high: row.get("high"),
+        low: row.get("low"),
+        close: row.get("close"),
+        volume: row.get("volume"),
+        quote_volume: row.get("quote_volume"),
+        trades: row.get("trades"),
+        change: row.get("change"),
+        change_percent: row.get("change_percent"),
+        mid_price: row.get("mid_price"),
+        last_trade_at: row
+            .get::<Option<DateTime<Utc>>, _>("last_trade_at")
+            .map(|t| t.timestamp()),
+        from: from.timestamp(),
+        to: to.timestamp(),
+    };
+
+    // Cache the response (TTL: 10 seconds; the window moves slowly)
+    if let Some(cache) = &state.cache {
+        if let Ok(mut cache) = cache.try_lock() {
+            let _ = cache
+                .set(
+                    &cache::keys::ticker(&base, &quote),
+                    &response,
+                    Duration::from_secs(10),
+                )
+                .await;
+        }
+    }
+
+    Ok(Json(response))
+}
+
+/// Validated candle request
+#[derive(Debug, PartialEq)]
+struct CandleQuery {
+    interval: CandleInterval,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+    limit: usize,
+}
+
+impl CandleQuery {
+    fn parse(params: &CandleParams, now: DateTime<Utc>) -> Result<Self> {
+        let limit = params.limit.unwrap_or(DEFAULT_CANDLE_LIMIT);
+        if limit == 0 || limit > MAX_CANDLE_LIMIT {
+            return Err(ApiError::Validation(format!(
+                "Limit must be between 1 and {}",
+                MAX_CANDLE_LIMIT
+            )));
+        }
+
+        let invalid = |name: &str| ApiError::Validation(format!("Invalid '{}' timestamp", name));
+        let time =
+            |secs: i64, name: &str| DateTime::from_timestamp(secs, 0).ok_or_else(|| invalid(name));
+
+        let width = params.interval.seconds();
+        let to = time(params.to.unwrap_or_else(|| now.timestamp()), "to")?;
+        let from = match params.from {
+            Some(from) => time(from, "from")?,
+            // By default, the interval holding the last second before `to`
+            // and the `limit - 1` before it
+            None => (to.timestamp() - 1)
+                .div_euclid(width)
+                .checked_mul(width)
+                .zip((limit as i64 - 1).checked_mul(width))
+                .and_then(|(last, span)| last.checked_sub(span))
+                .ok_or_else(|| invalid("to"))
+                .and_then(|from| time(from, "to"))?,
+        };
+
+        let query = Self {
+            interval: params.interval,
+            from,
+            to,
+            limit,
+        };
+        if query.from >= query.to {
+            return Err(ApiError::Validation(
+                "'from' must be before 'to'".to_string(),
+            ));
+        }
+        Ok(query)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(secs: i64) -> DateTime<Utc> {
+        DateTime::from_timestamp(secs, 0).unwrap()
+    }
+
+    #[test]
+    fn test_candle_intervals_parse_from_query_values() {
+        for (raw, interval, seconds) in [
+            ("1m", CandleInterval::OneMinute, 60),
+            ("5m", CandleInterval::FiveMinutes, 300),
+            ("1h", CandleInterval::OneHour, 3_600),
+            ("1d", CandleInterval::OneDay, 86_400),
+        ] {
+            let parsed: CandleInterval = serde_json::from_value(serde_json::json!(raw)).unwrap();
+            assert_eq!(parsed, interval);
+            assert_eq!(parsed.seconds(), seconds);
+            assert_eq!(parsed.as_str(), raw);
+        }
+        assert!(serde_json::from_value::<CandleInterval>(serde_json::json!("2h")).is_err());
+    }
+
+    #[test]
+    fn test_candle_query_defaults_to_last_intervals() {
+        let params = CandleParams {
+            interval: CandleInterval::FiveMinutes,
+            limit: Some(3),
+            ..Default::default()
+        };
+        // 10:07:30 falls in the 10:05 candle; 10:00 and 09:55 come before it
+        let now = at(36_000 + 450);
+        let query = CandleQuery::parse(&params, now).unwrap();
+        assert_eq!(query.from, at(36_000 - 300));
+        assert_eq!(query.to, now);
+
+        let query = CandleQuery::parse(&CandleParams::default(), now).unwrap();
+        assert_eq!(query.interval, CandleInterval::OneHour);
+        assert_eq!(query.limit, DEFAULT_CANDLE_LIMIT);
+        assert_eq!(query.from, at(36_000 - 499 * 3_600));
+    }
+
+    #[test]
+    fn test_candle_query_rejects_bad_ranges() {
+        let now = at(1_700_000_000);
+        for params in [
+            CandleParams {
+                limit: Some(0),
+                ..Default::default()
+            },
+            CandleParams {
+                limit: Some(MAX_CANDLE_LIMIT + 1),
+                ..Default::default()
+            },
+            CandleParams {
+                from: Some(100),
+                to: Some(100),
+                ..Default::default()
+            },
+            CandleParams {
+                from: Some(i64::MAX),
+                ..Default::default()
+            },
+            CandleParams {
+                to: Some(i64::MIN),
+                ..Default::default()
+            },
+            CandleParams {
+                to: Some(i64::MAX),
+                ..Default::default()
+            },
+        ] {
+            assert!(matches!(
+                CandleQuery::parse(&params, now),
+                Err(ApiError::Validation(_))
+            ));
+        }
+    }
+}
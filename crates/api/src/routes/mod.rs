@@ -1,11 +1,19 @@
 //! API routes
 
 pub mod health;
+pub mod market;
 pub mod orderbook;
 pub mod pairs;
 pub mod quote;
+pub mod simulate;
+pub mod stream;
+pub mod well_known;
+pub mod ws;
 
-use axum::{routing::get, Router};
+use axum::{
+    routing::{get, post},
+    Router,
+};
 use std::sync::Arc;
 
 use crate::state::AppState;
@@ -15,12 +23,27 @@ pub fn create_router(state: Arc<AppState>) -> Router {
     Router::new()
         // Health check
         .route("/health", get(health::health_check))
+        .route(
+            "/.well-known/stellarroute-quote-key",
+            get(well_known::get_quote_signing_key),
+        )
         // API v1 routes
         .route("/api/v1/pairs", get(pairs::list_pairs))
         .route(
             "/api/v1/orderbook/:base/:quote",
             get(orderbook::get_orderbook),
         )
+        .route(
+            "/api/v1/orderbook/:base/:quote/history",
+            get(orderbook::get_orderbook_history),
+        )
+        .route("/api/v1/candles/:base/:quote", get(market::get_candles))
+        .route("/api/v1/ticker/:base/:quote", get(market::get_ticker))
         .route("/api/v1/quote/:base/:quote", get(quote::get_quote))
+        .route("/api/v1/quote/by-id/:id", get(quote::get_quote_by_id))
+        .route("/api/v1/quotes", post(quote::post_quotes))
+        .route("/api/v1/simulate", post(simulate::simulate_swap))
+        .route("/api/v1/ws", get(ws::stream))
+        .route("/api/v1/stream/prices", get(stream::stream_prices))
         .with_state(state)
 }
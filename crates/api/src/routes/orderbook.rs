@@ -1,17 +1,26 @@
-//! Orderbook endpoint
+//! Orderbook endpoints
 
 use axum::{
-    extract::{Path, State},
+    extract::{Path, Query, State},
     Json,
 };
+use chrono::{DateTime, Utc};
 use sqlx::Row;
 use std::{collections::BTreeMap, sync::Arc, time::Duration};
+use stellarroute_routing::{
+    amount::{Amount, Price},
+    error::RoutingError,
+};
 use tracing::{debug, warn};
 
 use crate::{
     cache,
     error::{ApiError, Result},
-    models::{request::AssetPath, AssetInfo, OrderbookLevel, OrderbookResponse},
+    models::{
+        request::{AssetPath, OrderbookHistoryParams},
+        AssetInfo, OrderbookHistoryResponse, OrderbookLevel, OrderbookResponse, OrderbookSnapshot,
+        SnapshotLevel,
+    },
     state::AppState,
 };
 
@@ -105,8 +114,232 @@ pub async fn get_orderbook(
     Ok(Json(response))
 }
 
+/// Snapshots returned by a history request unless it asks for fewer
+pub const DEFAULT_HISTORY_LIMIT: usize = 500;
+
+/// Most snapshots one history request can return
+pub const MAX_HISTORY_LIMIT: usize = 1_000;
+
+/// Get historical orderbook snapshots for a trading pair
+///
+/// Returns the captured snapshots of the pair in a time and ledger range,
+/// oldest first, each with its bids, asks, spread and mid price. With
+/// `interval`, only the last snapshot in each `interval`-second bucket is
+/// kept. Results are paged by ledger: pass `next_from_ledger` as
+/// `from_ledger` to get the next page.
+#[utoipa::path(
+    get,
+    path = "/api/v1/orderbook/{base}/{quote}/history",
+    tag = "trading",
+    params(
+        ("base" = String, Path, description = "Base asset (e.g., 'native', 'USDC', or 'USDC:ISSUER')"),
+        ("quote" = String, Path, description = "Quote asset (e.g., 'native', 'USDC', or 'USDC:ISSUER')"),
+        ("from" = Option<i64>, Query, description = "Earliest snapshot time (Unix timestamp)"),
+        ("to" = Option<i64>, Query, description = "Latest snapshot time (Unix timestamp)"),
+        ("from_ledger" = Option<u64>, Query, description = "Earliest ledger"),
+        ("to_ledger" = Option<u64>, Query, description = "Latest ledger"),
+        ("interval" = Option<u64>, Query, description = "Downsample to the last snapshot of every interval, in seconds"),
+        ("limit" = Option<usize>, Query, description = "Most snapshots returned (default 500, max 1000)"),
+    ),
+    responses(
+        (status = 200, description = "Orderbook snapshots", body = OrderbookHistoryResponse),
+        (status = 400, description = "Invalid asset or range", body = ErrorResponse),
+        (status = 404, description = "Asset not found", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse),
+    )
+)]
+pub async fn get_orderbook_history(
+    State(state): State<Arc<AppState>>,
+    Path((base, quote)): Path<(String, String)>,
+    Query(params): Query<OrderbookHistoryParams>,
+) -> Result<Json<OrderbookHistoryResponse>> {
+    debug!(
+        "Fetching orderbook history for {}/{} with params: {:?}",
+        base, quote, params
+    );
+
+    let query = HistoryQuery::parse(&params)?;
+    let base_asset = AssetPath::parse(&base)
+        .map_err(|e| ApiError::InvalidAsset(format!("Invalid base asset: {}", e)))?;
+    let quote_asset = AssetPath::parse(&quote)
+        .map_err(|e| ApiError::InvalidAsset(format!("Invalid quote asset: {}", e)))?;
+    let base_id = find_asset_id(&state, &base_asset).await?;
+    let quote_id = find_asset_id(&state, &quote_asset).await?;
+
+    // One row past the limit tells whether there is another page
+    let rows = sqlx::query(
+        r#"
+        select
+          snapshot_time,
+          ledger_sequence,
+          bids::text as bids,
+          asks::text as asks,
+          spread::text as spread,
+          mid_price::text as mid_price,
+          total_bid_volume::text as total_bid_volume,
+          total_ask_volume::text as total_ask_volume
+        from (
+          select distinct on (bucket) *
+          from (
+            select
+              s.*,
+              case
+                when $7::bigint is null then s.id::text
+                else floor(extract(epoch from s.snapshot_time) / $7::bigint)::text
+              end as bucket
+            from orderbook_snapshots s
+            join trading_pairs tp on tp.id = s.trading_pair_id
+            where tp.base_asset_id = $1
+              and tp.counter_asset_id = $2
+              and ($3::timestamptz is null or s.snapshot_time >= $3)
+              and ($4::timestamptz is null or s.snapshot_time <= $4)
+              and ($5::bigint is null or s.ledger_sequence >= $5)
+              and ($6::bigint is null or s.ledger_sequence <= $6)
+          ) bucketed
+          order by bucket, snapshot_time desc
+        ) kept
+        order by ledger_sequence, snapshot_time
+        limit $8
+        "#,
+    )
+    .bind(base_id)
+    .bind(quote_id)
+    .bind(query.from)
+    .bind(query.to)
+    .bind(query.from_ledger)
+    .bind(query.to_ledger)
+    .bind(query.interval)
+    .bind(query.limit as i64 + 1)
+    .fetch_all(&state.db)
+    .await?;
+
+    let snapshots = rows
+        .into_iter()
+        .map(|row| {
+            Ok(OrderbookSnapshot {
+                timestamp: row.get::<DateTime<Utc>, _>("snapshot_time").timestamp(),
+                ledger: row.get::<i64, _>("ledger_sequence") as u64,
+                bids: parse_snapshot_levels(&row.get::<String, _>("bids"))?,
+                asks: parse_snapshot_levels(&row.get::<String, _>("asks"))?,
+                spread: row.get("spread"),
+                mid_price: row.get("mid_price"),
+                total_bid_volume: row.get("total_bid_volume"),
+                total_ask_volume: row.get("total_ask_volume"),
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let (snapshots, next_from_ledger) = paginate(snapshots, query.limit);
+
+    debug!(
+        "Orderbook history for {}/{}: {} snapshots",
+        base,
+        quote,
+        snapshots.len()
+    );
+
+    Ok(Json(OrderbookHistoryResponse {
+        base_asset: asset_path_to_info(&base_asset),
+        quote_asset: asset_path_to_info(&quote_asset),
+        snapshots,
+        next_from_ledger,
+    }))
+}
+
+/// A validated history request, in database terms
+#[derive(Debug, PartialEq)]
+struct HistoryQuery {
+    from: Option<DateTime<Utc>>,
+    to: Option<DateTime<Utc>>,
+    from_ledger: Option<i64>,
+    to_ledger: Option<i64>,
+    interval: Option<i64>,
+    limit: usize,
+}
+
+impl HistoryQuery {
+    fn parse(params: &OrderbookHistoryParams) -> Result<Self> {
+        let time = |secs: Option<i64>, name: &str| {
+            secs.map(|secs| {
+                DateTime::from_timestamp(secs, 0)
+                    .ok_or_else(|| ApiError::Validation(format!("Invalid '{}' timestamp", name)))
+            })
+            .transpose()
+        };
+        let ledger = |ledger: Option<u64>, name: &str| {
+            ledger
+                .map(|ledger| {
+                    i64::try_from(ledger)
+                        .map_err(|_| ApiError::Validation(format!("Invalid '{}' ledger", name)))
+                })
+                .transpose()
+        };
+
+        let query = Self {
+            from: time(params.from, "from")?,
+            to: time(params.to, "to")?,
+            from_ledger: ledger(params.from_ledger, "from_ledger")?,
+            to_ledger: ledger(params.to_ledger, "to_ledger")?,
+            interval: params
+                .interval
+                .map(|secs| match i64::try_from(secs) {
+                    Ok(secs) if secs > 0 => Ok(secs),
+                    _ => Err(ApiError::Validation(
+                        "Interval must be a positive number of seconds".to_string(),
+                    )),
+                })
+                .transpose()?,
+            limit: params.limit.unwrap_or(DEFAULT_HISTORY_LIMIT),
+        };
+
+        if query.limit == 0 || query.limit > MAX_HISTORY_LIMIT {
+            return Err(ApiError::Validation(format!(
+                "Limit must be between 1 and {}",
+                MAX_HISTORY_LIMIT
+            )));
+        }
+        if matches!((query.from, query.to), (Some(from), Some(to)) if from > to) {
+            return Err(ApiError::Validation("'from' is after 'to'".to_string()));
+        }
+        if matches!((query.from_ledger, query.to_ledger), (Some(from), Some(to)) if from > to) {
+            return Err(ApiError::Validation(
+                "'from_ledger' is after 'to_ledger'".to_string(),
+            ));
+        }
+        Ok(query)
+    }
+}
+
+/// Offers stored in a snapshot's `bids` or `asks` column
+fn parse_snapshot_levels(json: &str) -> Result<Vec<SnapshotLevel>> {
+    serde_json::from_str(json)
+        .map_err(|e| ApiError::Internal(anyhow::anyhow!("Invalid snapshot data: {}", e)))
+}
+
+/// Cut snapshots fetched with one extra row down to a page, and the ledger
+/// the next page starts at
+///
+/// A page never ends partway through a ledger, so resuming from the next
+/// ledger repeats nothing. Only when a whole page is one ledger does the next
+/// page skip past it.
+fn paginate(
+    mut snapshots: Vec<OrderbookSnapshot>,
+    limit: usize,
+) -> (Vec<OrderbookSnapshot>, Option<u64>) {
+    if snapshots.len() <= limit {
+        return (snapshots, None);
+    }
+    let next = snapshots[limit].ledger;
+    snapshots.truncate(limit);
+    let before_next = snapshots.iter().take_while(|s| s.ledger < next).count();
+    if before_next == 0 {
+        return (snapshots, Some(next + 1));
+    }
+    snapshots.truncate(before_next);
+    (snapshots, Some(next))
+}
+
 /// Find asset ID in database
-async fn find_asset_id(state: &AppState, asset: &AssetPath) -> Result<uuid::Uuid> {
+pub(crate) async fn find_asset_id(state: &AppState, asset: &AssetPath) -> Result<uuid::Uuid> {
     let asset_type = asset.to_asset_type();
 
     let row = if asset.asset_code == "native" {
@@ -172,34 +405,33 @@ async fn fetch_orderbook_side(
     .await?;
 
     // Aggregate by price level
-    let mut levels: BTreeMap<String, (f64, f64)> = BTreeMap::new();
+    let mut levels: BTreeMap<Price, Amount> = BTreeMap::new();
 
     for row in rows {
         let price_str: String = row.get("price");
         let amount_str: String = row.get("amount");
 
-        let price_f64: f64 = price_str.parse().unwrap_or(0.0);
-        let amount_f64: f64 = amount_str.parse().unwrap_or(0.0);
+        let price: Price = price_str.parse().map_err(invalid_offer)?;
+        let amount: Amount = amount_str.parse().map_err(invalid_offer)?;
 
-        levels
-            .entry(price_str.clone())
-            .and_modify(|(_, total_amount)| *total_amount += amount_f64)
-            .or_insert((price_f64, amount_f64));
+        let level = levels.entry(price).or_default();
+        *level = level.checked_add(amount).map_err(invalid_offer)?;
     }
 
     // Convert to response format with cumulative totals
-    let mut cumulative = 0.0;
-    let mut result: Vec<OrderbookLevel> = levels
-        .into_iter()
-        .map(|(price_str, (price_f64, amount))| {
-            cumulative += amount * price_f64;
-            OrderbookLevel {
-                price: price_str,
-                amount: format!("{:.7}", amount),
-                total: format!("{:.7}", cumulative),
-            }
-        })
-        .collect();
+    let mut cumulative = Amount::ZERO;
+    let mut result = Vec::with_capacity(levels.len());
+    for (price, amount) in levels {
+        cumulative = amount
+            .mul_price_floor(price)
+            .and_then(|value| cumulative.checked_add(value))
+            .map_err(invalid_offer)?;
+        result.push(OrderbookLevel {
+            price: price.to_string(),
+            amount: amount.to_string(),
+            total: cumulative.to_string(),
+        });
+    }
 
     // For bids, reverse the order (highest price first)
     if !is_asks {
@@ -209,11 +441,122 @@ async fn fetch_orderbook_side(
     Ok(result)
 }
 
+fn invalid_offer(err: RoutingError) -> ApiError {
+    ApiError::Internal(anyhow::anyhow!("Invalid offer data: {}", err))
+}
+
 /// Convert AssetPath to AssetInfo
-fn asset_path_to_info(asset: &AssetPath) -> AssetInfo {
+pub(crate) fn asset_path_to_info(asset: &AssetPath) -> AssetInfo {
     if asset.asset_code == "native" {
         AssetInfo::native()
     } else {
         AssetInfo::credit(asset.asset_code.clone(), asset.asset_issuer.clone())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot(ledger: u64) -> OrderbookSnapshot {
+        OrderbookSnapshot {
+            timestamp: 1_700_000_000 + ledger as i64 * 5,
+            ledger,
+            bids: vec![],
+            asks: vec![],
+            spread: None,
+            mid_price: None,
+            total_bid_volume: "0".to_string(),
+            total_ask_volume: "0".to_string(),
+        }
+    }
+
+    fn ledgers(snapshots: &[OrderbookSnapshot]) -> Vec<u64> {
+        snapshots.iter().map(|s| s.ledger).collect()
+    }
+
+    #[test]
+    fn test_history_query_defaults_and_limits() {
+        let query = HistoryQuery::parse(&OrderbookHistoryParams::default()).unwrap();
+        assert_eq!(query.limit, DEFAULT_HISTORY_LIMIT);
+        assert_eq!(query.interval, None);
+
+        let query = HistoryQuery::parse(&OrderbookHistoryParams {
+            from: Some(1_700_000_000),
+            to_ledger: Some(50),
+            interval: Some(60),
+            ..Default::default()
+        })
+        .unwrap();
+        assert_eq!(query.from.unwrap().timestamp(), 1_700_000_000);
+        assert_eq!(query.to_ledger, Some(50));
+        assert_eq!(query.interval, Some(60));
+    }
+
+    #[test]
+    fn test_history_query_rejects_bad_ranges() {
+        for params in [
+            OrderbookHistoryParams {
+                from: Some(20),
+                to: Some(10),
+                ..Default::default()
+            },
+            OrderbookHistoryParams {
+                from_ledger: Some(20),
+                to_ledger: Some(10),
+                ..Default::default()
+            },
+            OrderbookHistoryParams {
+                interval: Some(0),
+                ..Default::default()
+            },
+            OrderbookHistoryParams {
+                limit: Some(MAX_HISTORY_LIMIT + 1),
+                ..Default::default()
+            },
+            OrderbookHistoryParams {
+                to_ledger: Some(u64::MAX),
+                ..Default::default()
+            },
+        ] {
+            assert!(
+                matches!(HistoryQuery::parse(&params), Err(ApiError::Validation(_))),
+                "{:?}",
+                params
+            );
+        }
+    }
+
+    #[test]
+    fn test_snapshot_levels_parse_stored_json() {
+        let levels = parse_snapshot_levels(
+            r#"[{"price": "0.10000000000000", "amount": "25.5", "offer_id": 7}]"#,
+        )
+        .unwrap();
+        assert_eq!(
+            levels,
+            vec![SnapshotLevel {
+                price: "0.10000000000000".to_string(),
+                amount: "25.5".to_string(),
+                offer_id: 7,
+            }]
+        );
+        assert!(parse_snapshot_levels("{}").is_err());
+    }
+
+    #[test]
+    fn test_pages_end_on_whole_ledgers() {
+        let (page, next) = paginate(vec![snapshot(1), snapshot(2)], 2);
+        assert_eq!((ledgers(&page), next), (vec![1, 2], None));
+
+        let (page, next) = paginate(vec![snapshot(1), snapshot(2), snapshot(3)], 2);
+        assert_eq!((ledgers(&page), next), (vec![1, 2], Some(3)));
+
+        // The second snapshot at ledger 2 would be cut off, so ledger 2 waits
+        let (page, next) = paginate(vec![snapshot(1), snapshot(2), snapshot(2)], 2);
+        assert_eq!((ledgers(&page), next), (vec![1], Some(2)));
+
+        let (page, next) = paginate(vec![snapshot(4), snapshot(4), snapshot(4)], 2);
+        assert_eq!((ledgers(&page), next), (vec![4, 4], Some(5)));
+    }
+}
@@ -16,7 +16,8 @@ use crate::{
 ///
 /// Returns a list of trading pairs with active offers in the orderbook.
 /// Each pair exposes human-readable `base`/`counter` codes alongside
-/// canonical Stellar asset identifiers (`base_asset`/`counter_asset`).
+/// canonical Stellar asset identifiers (`base_asset`/`counter_asset`),
+/// and its last traded price and 24h volume.
 #[utoipa::path(
     get,
     path = "/api/v1/pairs",
@@ -40,7 +41,8 @@ pub async fn list_pairs(State(state): State<Arc<AppState>>) -> Result<Json<Pairs
     }
 
     // Query distinct trading pairs that have active offers in the orderbook.
-    // Results are ranked by offer depth so the most liquid pairs appear first.
+    // Results are ranked by offer depth so the most liquid pairs appear first;
+    // trade statistics are only looked up for the pairs returned.
     let rows = sqlx::query(
         r#"
         select
@@ -50,16 +52,32 @@ pub async fn list_pairs(State(state): State<Arc<AppState>>) -> Result<Json<Pairs
             ba.asset_type as buying_type,
             ba.asset_code as buying_code,
             ba.asset_issuer as buying_issuer,
-            count(*) as offer_count,
-            max(o.updated_at) as last_updated
-        from sdex_offers o
-        join assets sa on o.selling_asset_id = sa.id
-        join assets ba on o.buying_asset_id = ba.id
-        group by
-            sa.asset_type, sa.asset_code, sa.asset_issuer,
-            ba.asset_type, ba.asset_code, ba.asset_issuer
-        order by offer_count desc
-        limit 100
+            p.offer_count,
+            p.last_updated,
+            (
+                select round(t.price, 14)::text
+                from pair_trades(p.selling_asset_id, p.buying_asset_id, '-infinity', 'infinity') t
+                order by t.ledger_close_time desc, t.operation_id desc, t.trade_index desc
+                limit 1
+            ) as last_price,
+            (
+                select coalesce(sum(t.base_volume), 0::numeric(30, 14))::text
+                from pair_trades(p.selling_asset_id, p.buying_asset_id, now() - interval '24 hours', 'infinity') t
+            ) as volume_24h
+        from (
+            select
+                selling_asset_id,
+                buying_asset_id,
+                count(*) as offer_count,
+                max(updated_at) as last_updated
+            from sdex_offers
+            group by selling_asset_id, buying_asset_id
+            order by offer_count desc
+            limit 100
+        ) p
+        join assets sa on p.selling_asset_id = sa.id
+        join assets ba on p.buying_asset_id = ba.id
+        order by p.offer_count desc
         "#,
     )
     .fetch_all(&state.db)
@@ -104,6 +122,8 @@ pub async fn list_pairs(State(state): State<Arc<AppState>>) -> Result<Json<Pairs
             counter_asset: counter_info.to_canonical(),
             offer_count,
             last_updated: last_updated.map(|dt| dt.to_rfc3339()),
+            last_price: row.get("last_price"),
+            volume_24h: row.get("volume_24h"),
         });
     }
 
@@ -1,26 +1,45 @@
-//! Quote endpoint
+//! Quote endpoints
 
 use axum::{
     extract::{Path, Query, State},
     Json,
 };
 use sqlx::Row;
-use std::{sync::Arc, time::Duration};
+use std::{collections::HashMap, sync::Arc, time::Duration};
+use stellarroute_routing::{
+    amount::{Amount, Price},
+    export::{
+        export_route, export_route_exact_out, max_input, min_output, output_before_fee,
+        protocol_fee,
+    },
+    graph::{AssetId, LiquidityGraph},
+    pathfinder::Route,
+    score::{score_route, ResourcePricing},
+    RoutingEngine,
+};
 use tracing::debug;
+use uuid::Uuid;
 
 use crate::{
     cache,
     error::{ApiError, Result},
     models::{
-        request::{AssetPath, QuoteParams},
-        AssetInfo, PathStep, QuoteResponse,
+        request::{AssetPath, BatchQuoteItem, QuoteParams, QuoteType},
+        AssetInfo, BatchQuoteResponse, BatchQuoteResult, IssuedQuoteResponse, PathStep,
+        QuoteResponse,
     },
+    quote_store::{FirmQuote, QUOTE_TTL, QUOTE_TTL_LEDGERS},
     state::AppState,
 };
 
 /// Get price quote for a trading pair
 ///
-/// Returns the best available price for trading the specified amount
+/// Returns the best available price for trading the specified amount,
+/// routed across SDEX orderbooks and AMM pools over up to four hops.
+/// A `sell` quote prices selling `amount` of the base asset for the quote
+/// asset; a `buy` quote prices receiving exactly `amount` of the base asset.
+/// Amounts received are net of the router contract's fee. The quote comes
+/// with the route it was priced on, in the router contract's format.
 #[utoipa::path(
     get,
     path = "/api/v1/quote/{base}/{quote}",
@@ -30,9 +49,10 @@ use crate::{
         ("quote" = String, Path, description = "Quote asset (e.g., 'native', 'USDC', or 'USDC:ISSUER')"),
         ("amount" = Option<String>, Query, description = "Amount to trade (default: 1)"),
         ("quote_type" = Option<String>, Query, description = "Type of quote: 'sell' or 'buy' (default: sell)"),
+        ("slippage_bps" = Option<u32>, Query, description = "Slippage tolerated in min_received, or in max_sent for a buy, in basis points (default: 50)"),
     ),
     responses(
-        (status = 200, description = "Price quote", body = QuoteResponse),
+        (status = 200, description = "Price quote", body = IssuedQuoteResponse),
         (status = 400, description = "Invalid parameters", body = ErrorResponse),
         (status = 404, description = "No route found", body = ErrorResponse),
         (status = 500, description = "Internal server error", body = ErrorResponse),
@@ -42,78 +62,49 @@ pub async fn get_quote(
     State(state): State<Arc<AppState>>,
     Path((base, quote)): Path<(String, String)>,
     Query(params): Query<QuoteParams>,
-) -> Result<Json<QuoteResponse>> {
+) -> Result<Json<IssuedQuoteResponse>> {
     debug!(
         "Getting quote for {}/{} with params: {:?}",
         base, quote, params
     );
 
-    // Parse asset identifiers
-    let base_asset = AssetPath::parse(&base)
-        .map_err(|e| ApiError::InvalidAsset(format!("Invalid base asset: {}", e)))?;
-    let quote_asset = AssetPath::parse(&quote)
-        .map_err(|e| ApiError::InvalidAsset(format!("Invalid quote asset: {}", e)))?;
-
-    // Parse amount (default to 1)
-    let amount: f64 = params
-        .amount
-        .as_deref()
-        .unwrap_or("1")
-        .parse()
-        .map_err(|_| ApiError::Validation("Invalid amount".to_string()))?;
-
-    if amount <= 0.0 {
-        return Err(ApiError::Validation(
-            "Amount must be greater than zero".to_string(),
-        ));
-    }
+    let request = QuoteRequest::parse(
+        &base,
+        &quote,
+        params.amount.as_deref(),
+        params.quote_type,
+        params.slippage_bps,
+    )?;
 
     // Try to get from cache first
-    let amount_str = format!("{:.7}", amount);
+    let cache_key = cache::keys::quote(
+        &base,
+        &quote,
+        &request.amount.to_string(),
+        request.quote_type.as_str(),
+        request.slippage_bps,
+    );
     if let Some(cache) = &state.cache {
         if let Ok(mut cache) = cache.try_lock() {
-            if let Some(cached) = cache
-                .get::<QuoteResponse>(&cache::keys::quote(&base, &quote, &amount_str))
-                .await
-            {
+            if let Some(cached) = cache.get::<IssuedQuoteResponse>(&cache_key).await {
                 debug!("Returning cached quote for {}/{}", base, quote);
                 return Ok(Json(cached));
             }
         }
     }
 
-    // For now, implement simple direct path (SDEX only)
-    // TODO: Implement multi-hop routing in Phase 2
-    let (price, path) = find_best_price(&state, &base_asset, &quote_asset, amount).await?;
-
-    let total = amount * price;
-    let timestamp = chrono::Utc::now().timestamp();
-
-    let quote_type = match params.quote_type {
-        crate::models::request::QuoteType::Sell => "sell",
-        crate::models::request::QuoteType::Buy => "buy",
-    };
-
-    let response = QuoteResponse {
-        base_asset: asset_path_to_info(&base_asset),
-        quote_asset: asset_path_to_info(&quote_asset),
-        amount: format!("{:.7}", amount),
-        price: format!("{:.7}", price),
-        total: format!("{:.7}", total),
-        quote_type: quote_type.to_string(),
-        path,
-        timestamp,
-    };
+    let base_id = resolve_asset(&state, &request.base).await?;
+    let quote_id = resolve_asset(&state, &request.quote).await?;
+    let graph = state.routing.snapshot();
+    let firm = firm_quote(&state, &graph, &request, &base_id, &quote_id)?;
+    state.quotes.insert(&firm).await;
+    let response = firm.into_response();
 
     // Cache the response (TTL: 2 seconds for quote data)
     if let Some(cache) = &state.cache {
         if let Ok(mut cache) = cache.try_lock() {
             let _ = cache
-                .set(
-                    &cache::keys::quote(&base, &quote, &amount_str),
-                    &response,
-                    Duration::from_secs(2),
-                )
+                .set(&cache_key, &response, Duration::from_secs(2))
                 .await;
         }
     }
@@ -121,88 +112,376 @@ pub async fn get_quote(
     Ok(Json(response))
 }
 
-/// Find best price for a trading pair
-async fn find_best_price(
-    state: &AppState,
-    base: &AssetPath,
-    quote: &AssetPath,
-    _amount: f64,
-) -> Result<(f64, Vec<PathStep>)> {
-    // Get asset IDs
-    let base_id = find_asset_id(state, base).await?;
-    let quote_id = find_asset_id(state, quote).await?;
-
-    // Find best offer
-    let row = sqlx::query(
-        r#"
-        select price::text as price
-        from sdex_offers
-        where selling_asset_id = $1
-          and buying_asset_id = $2
-        order by price asc
-        limit 1
-        "#,
+/// Get price quotes for several trading pairs at once
+///
+/// Every quote is priced against the same snapshot of the liquidity graph,
+/// so the results are consistent with each other. Each request gets its own
+/// result, in order; one failing quote does not fail the batch.
+#[utoipa::path(
+    post,
+    path = "/api/v1/quotes",
+    tag = "trading",
+    request_body = Vec<BatchQuoteItem>,
+    responses(
+        (status = 200, description = "Per-quote results", body = BatchQuoteResponse),
+        (status = 400, description = "Empty or oversized batch", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse),
     )
-    .bind(base_id)
-    .bind(quote_id)
-    .fetch_optional(&state.db)
-    .await?;
+)]
+pub async fn post_quotes(
+    State(state): State<Arc<AppState>>,
+    Json(items): Json<Vec<BatchQuoteItem>>,
+) -> Result<Json<BatchQuoteResponse>> {
+    debug!("Getting {} quotes in a batch", items.len());
 
-    match row {
-        Some(row) => {
-            let price_str: String = row.get("price");
-            let price_f64: f64 = price_str.parse().unwrap_or(0.0);
-
-            // Create simple path
-            let path = vec![PathStep {
-                from_asset: asset_path_to_info(base),
-                to_asset: asset_path_to_info(quote),
-                price: format!("{:.7}", price_f64),
-                source: "sdex".to_string(),
-            }];
-
-            Ok((price_f64, path))
+    if items.is_empty() || items.len() > MAX_BATCH_QUOTES {
+        return Err(ApiError::Validation(format!(
+            "A batch must hold between 1 and {} quotes",
+            MAX_BATCH_QUOTES
+        )));
+    }
+
+    // Resolve every asset first, so all quotes are priced on one snapshot
+    let mut resolved: HashMap<String, AssetId> = HashMap::new();
+    let mut requests = Vec::with_capacity(items.len());
+    for item in &items {
+        requests.push(resolve_batch_item(&state, item, &mut resolved).await);
+    }
+
+    let graph = state.routing.snapshot();
+    let quotes: Vec<Result<FirmQuote>> = requests
+        .into_iter()
+        .map(|request| {
+            request.and_then(|(request, base_id, quote_id)| {
+                firm_quote(&state, &graph, &request, &base_id, &quote_id)
+            })
+        })
+        .collect();
+
+    let mut results = Vec::with_capacity(quotes.len());
+    for quote in quotes {
+        if let Ok(firm) = &quote {
+            state.quotes.insert(firm).await;
+        }
+        results.push(match quote.map(FirmQuote::into_response) {
+            Ok(quote) => BatchQuoteResult {
+                quote: Some(quote),
+                error: None,
+            },
+            Err(e) => BatchQuoteResult {
+                quote: None,
+                error: Some(e.into_parts().1),
+            },
+        });
+    }
+
+    Ok(Json(BatchQuoteResponse {
+        ledger: graph.watermark().ledger,
+        results,
+        timestamp: chrono::Utc::now().timestamp(),
+    }))
+}
+
+/// Get a previously issued quote
+///
+/// Quotes can be fetched for a while after `valid_until`, so a caller can
+/// see what was quoted; only unexpired quotes can be executed. The quote
+/// comes with the route it was priced on, in the router contract's format.
+#[utoipa::path(
+    get,
+    path = "/api/v1/quote/by-id/{id}",
+    tag = "trading",
+    params(
+        ("id" = String, Path, description = "Quote id returned with the quote"),
+    ),
+    responses(
+        (status = 200, description = "Issued quote", body = IssuedQuoteResponse),
+        (status = 400, description = "Malformed quote id", body = ErrorResponse),
+        (status = 404, description = "Unknown or purged quote", body = ErrorResponse),
+    )
+)]
+pub async fn get_quote_by_id(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> Result<Json<IssuedQuoteResponse>> {
+    let id = Uuid::parse_str(&id)
+        .map_err(|e| ApiError::Validation(format!("Invalid quote id: {}", e)))?;
+    match state.quotes.get(&id).await {
+        Some(firm) => Ok(Json(firm.into_response())),
+        None => Err(ApiError::NotFound(format!("Quote not found: {}", id))),
+    }
+}
+
+/// Most quotes accepted in one batch
+pub const MAX_BATCH_QUOTES: usize = 50;
+
+/// A validated quote request
+#[derive(Debug)]
+pub(crate) struct QuoteRequest {
+    pub(crate) base: AssetPath,
+    pub(crate) quote: AssetPath,
+    amount: Amount,
+    quote_type: QuoteType,
+    slippage_bps: u32,
+}
+
+impl QuoteRequest {
+    pub(crate) fn parse(
+        base: &str,
+        quote: &str,
+        amount: Option<&str>,
+        quote_type: QuoteType,
+        slippage_bps: u32,
+    ) -> Result<Self> {
+        // Parse asset identifiers
+        let base = AssetPath::parse(base)
+            .map_err(|e| ApiError::InvalidAsset(format!("Invalid base asset: {}", e)))?;
+        let quote = AssetPath::parse(quote)
+            .map_err(|e| ApiError::InvalidAsset(format!("Invalid quote asset: {}", e)))?;
+
+        // Parse amount (default to 1)
+        let amount: Amount = amount
+            .unwrap_or("1")
+            .parse()
+            .map_err(|e| ApiError::Validation(format!("Invalid amount: {}", e)))?;
+
+        if !amount.is_positive() {
+            return Err(ApiError::Validation(
+                "Amount must be greater than zero".to_string(),
+            ));
         }
-        None => Err(ApiError::NoRouteFound),
+
+        if slippage_bps > 10_000 {
+            return Err(ApiError::Validation(
+                "Slippage cannot exceed 10000 bps".to_string(),
+            ));
+        }
+
+        Ok(Self {
+            base,
+            quote,
+            amount,
+            quote_type,
+            slippage_bps,
+        })
     }
 }
 
-/// Find asset ID in database
-async fn find_asset_id(state: &AppState, asset: &AssetPath) -> Result<uuid::Uuid> {
-    use sqlx::Row;
+/// Parse a batch item and resolve its assets, reusing earlier lookups
+async fn resolve_batch_item(
+    state: &AppState,
+    item: &BatchQuoteItem,
+    resolved: &mut HashMap<String, AssetId>,
+) -> Result<(QuoteRequest, AssetId, AssetId)> {
+    let request = QuoteRequest::parse(
+        &item.base,
+        &item.quote,
+        item.amount.as_deref(),
+        item.quote_type,
+        item.slippage_bps,
+    )?;
+    let mut ids = Vec::with_capacity(2);
+    for (raw, asset) in [(&item.base, &request.base), (&item.quote, &request.quote)] {
+        let id = match resolved.get(raw) {
+            Some(id) => id.clone(),
+            None => {
+                let id = resolve_asset(state, asset).await?;
+                resolved.insert(raw.clone(), id.clone());
+                id
+            }
+        };
+        ids.push(id);
+    }
+    let quote_id = ids.pop().expect("two assets");
+    let base_id = ids.pop().expect("two assets");
+    Ok((request, base_id, quote_id))
+}
 
-    let asset_type = asset.to_asset_type();
+/// Price `request` on `graph` as a new quote, valid for [`QUOTE_TTL`] and
+/// signed when the API has a signing key
+pub(crate) fn firm_quote(
+    state: &AppState,
+    graph: &LiquidityGraph,
+    request: &QuoteRequest,
+    base_id: &AssetId,
+    quote_id: &AssetId,
+) -> Result<FirmQuote> {
+    let routed = route_quote(
+        &state.routing,
+        graph,
+        base_id,
+        quote_id,
+        request,
+        state.router.protocol_fee_bps,
+    )?;
 
-    let row = if asset.asset_code == "native" {
-        sqlx::query(
-            r#"
-            select id from assets
-            where asset_type = $1
-            limit 1
-            "#,
-        )
-        .bind(&asset_type)
-        .fetch_optional(&state.db)
-        .await?
-    } else {
-        sqlx::query(
-            r#"
-            select id from assets
-            where asset_type = $1
-              and asset_code = $2
-              and ($3::text is null or asset_issuer = $3)
-            limit 1
-            "#,
-        )
-        .bind(&asset_type)
-        .bind(&asset.asset_code)
-        .bind(&asset.asset_issuer)
-        .fetch_optional(&state.db)
-        .await?
+    let timestamp = chrono::Utc::now().timestamp();
+    let mut quote = QuoteResponse {
+        quote_id: Uuid::new_v4().to_string(),
+        base_asset: asset_path_to_info(&request.base),
+        quote_asset: asset_path_to_info(&request.quote),
+        amount: request.amount.to_string(),
+        price: routed.price.to_string(),
+        total: routed.total.to_string(),
+        quote_type: request.quote_type.as_str().to_string(),
+        path: routed.path,
+        price_impact: format_bps_as_percent(routed.price_impact_bps),
+        fee: routed.fee.to_string(),
+        min_received: routed.min_received.to_string(),
+        max_sent: routed.max_sent.map(|amount| amount.to_string()),
+        timestamp,
+        valid_until: timestamp + QUOTE_TTL.as_secs() as i64,
+        signature: None,
     };
+    // A route the router cannot execute, or that cannot be given an expiry
+    // ledger, is still quoted, just not with a contract route to execute it by
+    let route = state.ledger.current().and_then(|ledger| {
+        let expires_at = ledger + QUOTE_TTL_LEDGERS;
+        let config = state.router.export_config();
+        match request.quote_type {
+            QuoteType::Sell => {
+                export_route(&routed.route, request.slippage_bps, expires_at, &config)
+            }
+            QuoteType::Buy => {
+                export_route_exact_out(&routed.route, request.amount.stroops(), expires_at, &config)
+            }
+        }
+        .ok()
+    });
+    if let Some(signer) = &state.signer {
+        signer.sign(&mut quote, route.as_ref());
+    }
+    Ok(FirmQuote { quote, route })
+}
+
+/// A quote priced along the best route through the liquidity graph
+#[derive(Debug)]
+struct RoutedQuote {
+    /// Units of quote asset per unit of base asset
+    price: Price,
+    /// Quote asset received when selling, or paid when buying
+    total: Amount,
+    path: Vec<PathStep>,
+    price_impact_bps: u32,
+    /// Swap fees and the router's fee, in the asset received
+    fee: Amount,
+    min_received: Amount,
+    /// Most of the quote asset sent, for a buy
+    max_sent: Option<Amount>,
+    route: Route,
+}
+
+/// Route a quote through `engine` on the `graph` snapshot
+///
+/// A `sell` quote sells exactly `amount` of `base`; a `buy` quote receives
+/// exactly `amount` of `base` for the least `quote`. Either may cross any
+/// mix of SDEX books and AMM pools. The router contract takes
+/// `protocol_fee_bps` of the route's output, so a buy is routed for enough
+/// to leave `amount` after the fee, and slippage is tolerated on what it
+/// sends rather than on what it receives.
+fn route_quote(
+    engine: &RoutingEngine,
+    graph: &LiquidityGraph,
+    base: &AssetId,
+    quote: &AssetId,
+    request: &QuoteRequest,
+    protocol_fee_bps: u32,
+) -> Result<RoutedQuote> {
+    let amount = request.amount.stroops();
+    let route = match request.quote_type {
+        QuoteType::Sell => engine.find_route_on(graph, base, quote, amount)?,
+        QuoteType::Buy => engine.find_route_exact_out_on(
+            graph,
+            quote,
+            base,
+            output_before_fee(amount, protocol_fee_bps)?,
+        )?,
+    };
+    let score = score_route(graph, &route, &ResourcePricing::default())?;
+    let router_fee = protocol_fee(route.amount_out, protocol_fee_bps)?;
+
+    let (price, total, min_received, max_sent) = match request.quote_type {
+        QuoteType::Sell => {
+            let received = route.amount_out - router_fee;
+            (
+                Price::new(received, route.amount_in)?,
+                received,
+                min_output(received, request.slippage_bps)?,
+                None,
+            )
+        }
+        // The route may overshoot by rounding, but only `amount` is promised
+        QuoteType::Buy => (
+            Price::new(route.amount_in, amount)?,
+            route.amount_in,
+            amount,
+            Some(Amount::from_stroops(max_input(
+                route.amount_in,
+                request.slippage_bps,
+            )?)),
+        ),
+    };
+
+    let path = route
+        .hops
+        .iter()
+        .map(|hop| {
+            Ok(PathStep {
+                from_asset: asset_info(&hop.from),
+                to_asset: asset_info(&hop.to),
+                price: Price::new(hop.amount_out, hop.amount_in)?.to_string(),
+                source: hop.venue.to_string(),
+            })
+        })
+        .collect::<Result<_>>()?;
+
+    Ok(RoutedQuote {
+        price,
+        total: Amount::from_stroops(total),
+        path,
+        price_impact_bps: score.price_impact_bps,
+        fee: Amount::from_stroops(score.fees + router_fee),
+        min_received: Amount::from_stroops(min_received),
+        max_sent,
+        route,
+    })
+}
+
+/// `"1.25"` for 125 bps
+fn format_bps_as_percent(bps: u32) -> String {
+    format!("{}.{:02}", bps / 100, bps % 100)
+}
+
+/// Routing asset for a path parameter
+///
+/// Codes given without an issuer are looked up among the indexed assets.
+pub(crate) async fn resolve_asset(state: &AppState, asset: &AssetPath) -> Result<AssetId> {
+    if asset.asset_code == "native" {
+        return Ok(AssetId::native());
+    }
+    if let Some(issuer) = &asset.asset_issuer {
+        return Ok(AssetId::credit(&asset.asset_code, issuer));
+    }
+
+    let row = sqlx::query(
+        r#"
+        select asset_issuer from assets
+        where asset_type = $1
+          and asset_code = $2
+          and asset_issuer is not null
+        limit 1
+        "#,
+    )
+    .bind(asset.to_asset_type())
+    .bind(&asset.asset_code)
+    .fetch_optional(&state.db)
+    .await?;
 
     match row {
-        Some(row) => Ok(row.get("id")),
+        Some(row) => Ok(AssetId::credit(
+            &asset.asset_code,
+            &row.get::<String, _>("asset_issuer"),
+        )),
         None => Err(ApiError::NotFound(format!(
             "Asset not found: {}",
             asset.asset_code
@@ -210,6 +489,14 @@ async fn find_asset_id(state: &AppState, asset: &AssetPath) -> Result<uuid::Uuid
     }
 }
 
+/// Convert a routing asset to AssetInfo
+fn asset_info(asset: &AssetId) -> AssetInfo {
+    match asset.as_str().split_once(':') {
+        Some((code, issuer)) => AssetInfo::credit(code.to_string(), Some(issuer.to_string())),
+        None => AssetInfo::native(),
+    }
+}
+
 /// Convert AssetPath to AssetInfo
 fn asset_path_to_info(asset: &AssetPath) -> AssetInfo {
     if asset.asset_code == "native" {
@@ -218,3 +505,185 @@ fn asset_path_to_info(asset: &AssetPath) -> AssetInfo {
         AssetInfo::credit(asset.asset_code.clone(), asset.asset_issuer.clone())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use stellarroute_routing::{
+        delta::SdexOffer,
+        graph::{AmmPool, PoolType, Venue},
+    };
+
+    fn eurc() -> AssetId {
+        AssetId::credit("EURC", "GISSUER")
+    }
+
+    fn usdc() -> AssetId {
+        AssetId::credit("USDC", "GISSUER")
+    }
+
+    /// XLM reaches USDC only through an XLM/EURC pool and EURC/USDC books
+    fn engine() -> RoutingEngine {
+        let engine = RoutingEngine::new();
+        engine
+            .upsert_venue(Venue::Amm(AmmPool {
+                address: "CPOOL".to_string(),
+                pool_type: PoolType::AmmConstProd,
+                asset_a: AssetId::native(),
+                asset_b: eurc(),
+                reserve_a: 100_000_000_000_000,
+                reserve_b: 9_000_000_000_000,
+                fee_bps: 30,
+                amplification: None,
+            }))
+            .unwrap();
+        let mut graph = (*engine.snapshot()).clone();
+        for (id, selling, buying, price_n, price_d) in
+            [(1, usdc(), eurc(), 10, 11), (2, eurc(), usdc(), 11, 10)]
+        {
+            graph
+                .upsert_offer(SdexOffer {
+                    id,
+                    selling,
+                    buying,
+                    price_n,
+                    price_d,
+                    amount: 1_000_000_000_000,
+                })
+                .unwrap();
+        }
+        RoutingEngine::with_graph(graph)
+    }
+
+    fn request(quote: &str, amount: &str, quote_type: QuoteType) -> QuoteRequest {
+        QuoteRequest::parse("native", quote, Some(amount), quote_type, 50).unwrap()
+    }
+
+    #[test]
+    fn test_sell_quote_routes_through_intermediate_asset() {
+        let engine = engine();
+        let request = request("USDC:GISSUER", "100", QuoteType::Sell);
+        let quoted = route_quote(
+            &engine,
+            &engine.snapshot(),
+            &AssetId::native(),
+            &usdc(),
+            &request,
+            0,
+        )
+        .unwrap();
+
+        let sources: Vec<&str> = quoted.path.iter().map(|s| s.source.as_str()).collect();
+        assert_eq!(sources, ["amm:CPOOL", "sdex"]);
+        assert_eq!(quoted.path[0].to_asset.to_canonical(), "EURC:GISSUER");
+        assert_eq!(quoted.path[1].to_asset.to_canonical(), "USDC:GISSUER");
+
+        let total = quoted.total.stroops();
+        assert!(total > 0);
+        assert_eq!(quoted.price, Price::new(total, 1_000_000_000).unwrap());
+        assert_eq!(quoted.min_received.stroops(), total * 9_950 / 10_000);
+        assert_eq!(quoted.max_sent, None);
+        assert!(quoted.fee.stroops() > 0);
+    }
+
+    #[test]
+    fn test_sell_quote_is_net_of_router_fee() {
+        let engine = engine();
+        let request = request("USDC:GISSUER", "100", QuoteType::Sell);
+        let quote = |fee_bps| {
+            route_quote(
+                &engine,
+                &engine.snapshot(),
+                &AssetId::native(),
+                &usdc(),
+                &request,
+                fee_bps,
+            )
+            .unwrap()
+        };
+        let (gross, net) = (quote(0), quote(30));
+
+        let output = gross.total.stroops();
+        let router_fee = output * 30 / 10_000;
+        assert_eq!(net.total.stroops(), output - router_fee);
+        assert_eq!(net.fee.stroops(), gross.fee.stroops() + router_fee);
+        assert_eq!(
+            net.min_received.stroops(),
+            (output - router_fee) * 9_950 / 10_000
+        );
+    }
+
+    #[test]
+    fn test_buy_quote_receives_exact_amount() {
+        let amount = Amount::from_stroops(1_000_000_000);
+        let engine = engine();
+        let request = request("USDC:GISSUER", "100", QuoteType::Buy);
+        let quoted = route_quote(
+            &engine,
+            &engine.snapshot(),
+            &AssetId::native(),
+            &usdc(),
+            &request,
+            0,
+        )
+        .unwrap();
+
+        assert_eq!(quoted.min_received, amount);
+        assert_eq!(quoted.path.len(), 2);
+        assert_eq!(quoted.path[0].from_asset.to_canonical(), "USDC:GISSUER");
+        assert_eq!(quoted.path[1].to_asset.to_canonical(), "native");
+        assert_eq!(
+            quoted.price,
+            Price::new(quoted.total.stroops(), amount.stroops()).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_buy_quote_covers_router_fee_and_slips_on_input() {
+        let amount = 1_000_000_000;
+        let engine = engine();
+        let request = request("USDC:GISSUER", "100", QuoteType::Buy);
+        let quoted = route_quote(
+            &engine,
+            &engine.snapshot(),
+            &AssetId::native(),
+            &usdc(),
+            &request,
+            30,
+        )
+        .unwrap();
+
+        // Enough is routed to leave `amount` once the router takes its fee
+        let output = quoted.route.amount_out;
+        assert!(output - output * 30 / 10_000 >= amount);
+        assert_eq!(quoted.min_received.stroops(), amount);
+
+        let sent = quoted.total.stroops();
+        assert_eq!(quoted.route.amount_in, sent);
+        let max_sent = quoted.max_sent.unwrap().stroops();
+        assert_eq!(max_sent, (sent * 10_050 + 9_999) / 10_000);
+    }
+
+    #[test]
+    fn test_unroutable_pair_is_no_route() {
+        let engine = engine();
+        let request = request("BTC:GISSUER", "1", QuoteType::Sell);
+        let err = route_quote(
+            &engine,
+            &engine.snapshot(),
+            &AssetId::native(),
+            &AssetId::credit("BTC", "GISSUER"),
+            &request,
+            0,
+        )
+        .unwrap_err();
+        assert!(matches!(err, ApiError::NoRouteFound));
+    }
+
+    #[test]
+    fn test_format_bps_as_percent() {
+        assert_eq!(format_bps_as_percent(0), "0.00");
+        assert_eq!(format_bps_as_percent(5), "0.05");
+        assert_eq!(format_bps_as_percent(125), "1.25");
+    }
+}
@@ -0,0 +1,83 @@
+//! Swap simulation endpoint
+
+use axum::{extract::State, Json};
+use std::sync::Arc;
+use stellarroute_routing::simulate::{self, ContractSwapParams, SwapSimulation};
+use tracing::debug;
+
+use crate::{
+    error::{ApiError, Result},
+    models::{SimulateRequest, SimulateResponse, SimulatedHop, SimulationError},
+    state::AppState,
+};
+
+/// Simulate a swap through the router contract
+///
+/// Replays the checks `execute_swap` makes, in the contract's order, and
+/// reports the `ContractError` the router would return, so a transaction
+/// bound to fail need not be submitted. Pools are priced on the indexed
+/// liquidity, in the ledger after the latest the network has closed; when
+/// the API has no recent view of the network's ledger, `ledger` is null and
+/// the swap's deadline and not_before are not checked. The router's pause
+/// flag, fee, SDEX adapter, token allowlist and commit threshold are taken
+/// from the API's configuration. The per-sender rate limit and the sandwich
+/// check around pool reserves are not simulated.
+#[utoipa::path(
+    post,
+    path = "/api/v1/simulate",
+    tag = "trading",
+    request_body = SimulateRequest,
+    responses(
+        (status = 200, description = "Simulated outcome, successful or not", body = SimulateResponse),
+        (status = 400, description = "Invalid parameters", body = ErrorResponse),
+    )
+)]
+pub async fn simulate_swap(
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<SimulateRequest>,
+) -> Result<Json<SimulateResponse>> {
+    if request.amount_in <= 0 {
+        return Err(ApiError::Validation(
+            "amount_in must be greater than zero".to_string(),
+        ));
+    }
+
+    let params = ContractSwapParams::from(request);
+    let graph = state.routing.snapshot();
+    let ledger = state.ledger.current();
+    let simulation = simulate::simulate_swap(&graph, &state.router, &params, ledger);
+    debug!(
+        "Simulated a {}-hop swap at ledger {:?}: {:?}",
+        params.route.hops.len(),
+        ledger,
+        simulation.error
+    );
+
+    Ok(Json(simulate_response(simulation, ledger)))
+}
+
+fn simulate_response(simulation: SwapSimulation, ledger: Option<u64>) -> SimulateResponse {
+    SimulateResponse {
+        success: simulation.error.is_none(),
+        error: simulation.error.map(|e| SimulationError {
+            code: e.code(),
+            name: e.name().to_string(),
+            message: e.description().to_string(),
+        }),
+        hops: simulation
+            .hops
+            .into_iter()
+            .map(|hop| SimulatedHop {
+                pool: hop.pool,
+                amount_in: hop.amount_in,
+                amount_out: hop.amount_out,
+            })
+            .collect(),
+        amount_out: simulation.amount_out,
+        fee_amount: simulation.fee_amount,
+        price_impact_bps: simulation.price_impact_bps,
+        spread_bps: simulation.spread_bps,
+        ledger,
+        timestamp: chrono::Utc::now().timestamp(),
+    }
+}
@@ -0,0 +1,284 @@
+//! Server-Sent Events price stream
+//!
+//! A fallback to `/api/v1/ws` for clients behind proxies that drop
+//! WebSockets. Each `ticker` event carries a [`PriceTicker`] for one pair
+//! and is sent whenever the pair's best bid or ask changes. Event ids are
+//! liquidity feed sequences, so a client reconnecting with `Last-Event-ID`
+//! only receives the pairs that changed while it was away; when the feed no
+//! longer remembers that far back, every pair is sent again.
+
+use axum::{
+    extract::{Query, State},
+    http::HeaderMap,
+    response::sse::{Event, KeepAlive, Sse},
+};
+use futures_util::{stream, Stream};
+use std::{collections::VecDeque, convert::Infallible, sync::Arc};
+use stellarroute_routing::{amount::Price, graph::AssetId};
+use tokio::sync::broadcast::{self, error::RecvError};
+use tracing::{debug, warn};
+
+use crate::{
+    error::{ApiError, Result},
+    feed::{FeedEvent, LiquidityFeed, TopOfBook},
+    models::{
+        request::{AssetPath, PriceStreamParams},
+        PriceTicker,
+    },
+    routes::quote::resolve_asset,
+    state::AppState,
+};
+
+/// Most pairs one stream can follow
+pub const MAX_STREAM_PAIRS: usize = 20;
+
+/// Stream best bid, best ask, mid price and spread for several pairs
+///
+/// Responds with `text/event-stream`; every `ticker` event's data is a
+/// `PriceTicker`. The current prices of every pair are sent first, or only
+/// those that changed since `Last-Event-ID` when resuming.
+#[utoipa::path(
+    get,
+    path = "/api/v1/stream/prices",
+    tag = "trading",
+    params(
+        ("pairs" = String, Query, description = "Comma-separated base/quote pairs (e.g., 'native/USDC:ISSUER')"),
+        ("Last-Event-ID" = Option<String>, Header, description = "Id of the last event received, to resume after it"),
+    ),
+    responses(
+        (status = 200, description = "Stream of ticker events", body = PriceTicker, content_type = "text/event-stream"),
+        (status = 400, description = "Invalid pairs", body = ErrorResponse),
+        (status = 404, description = "Asset not found", body = ErrorResponse),
+    )
+)]
+pub async fn stream_prices(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Query(params): Query<PriceStreamParams>,
+) -> Result<Sse<impl Stream<Item = std::result::Result<Event, Infallible>>>> {
+    let pairs = parse_pairs(&state, &params.pairs).await?;
+    let after = headers
+        .get("last-event-id")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.trim().parse().ok());
+
+    let subscription = state.feed.subscribe_after(after);
+    let mut prices = PriceStream {
+        feed: state.feed.clone(),
+        events: subscription.events,
+        sequence: subscription.sequence,
+        last: vec![None; pairs.len()],
+        pairs,
+        pending: VecDeque::new(),
+    };
+    match subscription.missed {
+        // The client already has every pair the missed events left alone
+        Some(missed) => {
+            for idx in 0..prices.pairs.len() {
+                let (base, quote) = &prices.pairs[idx];
+                let changed = missed.iter().any(|e| e.touches(base, quote));
+                let top = prices.feed.top_of_book(base, quote);
+                if changed {
+                    prices.send(idx, top);
+                } else {
+                    prices.last[idx] = Some((top.best_bid, top.best_ask));
+                }
+            }
+        }
+        None => prices.refresh(|_, _| true),
+    }
+
+    let events = stream::unfold(prices, |mut prices| async move {
+        let event = prices.next().await?;
+        Some((Ok(event), prices))
+    });
+    Ok(Sse::new(events).keep_alive(KeepAlive::default()))
+}
+
+/// Ticker events for one client
+struct PriceStream {
+    feed: LiquidityFeed,
+    events: broadcast::Receiver<Arc<FeedEvent>>,
+    /// Feed sequence of the latest event the tickers sent so far account
+    /// for, and the id they are sent with
+    sequence: u64,
+    pairs: Vec<(AssetId, AssetId)>,
+    /// Best bid and ask last sent for each pair
+    last: Vec<Option<(Option<Price>, Option<Price>)>>,
+    pending: VecDeque<Event>,
+}
+
+impl PriceStream {
+    async fn next(&mut self) -> Option<Event> {
+        loop {
+            if let Some(event) = self.pending.pop_front() {
+                return Some(event);
+            }
+            match self.events.recv().await {
+                Ok(event) if event.sequence <= self.sequence => {}
+                Ok(event) => {
+                    // The books may already reflect later events, but only the
+                    // pairs this one touched are refreshed
+                    self.sequence = event.sequence;
+                    self.refresh(|base, quote| event.touches(base, quote))
+                }
+                // Tickers are absolute, so catching up only needs a full
+                // refresh; ids stay at the last event received, as which
+                // events were missed is not known
+                Err(RecvError::Lagged(missed)) => {
+                    debug!("Price stream fell {} feed events behind", missed);
+                    self.refresh(|_, _| true);
+                }
+                Err(RecvError::Closed) => return None,
+            }
+        }
+    }
+
+    /// Queue tickers for the pairs `affected` selects whose best prices moved
+    fn refresh(&mut self, affected: impl Fn(&AssetId, &AssetId) -> bool) {
+        for idx in 0..self.pairs.len() {
+            let (base, quote) = &self.pairs[idx];
+            if !affected(base, quote) {
+                continue;
+            }
+            let top = self.feed.top_of_book(base, quote);
+            if self.last[idx] != Some((top.best_bid, top.best_ask)) {
+                self.send(idx, top);
+            }
+        }
+    }
+
+    fn send(&mut self, idx: usize, top: TopOfBook) {
+        let (base, quote) = &self.pairs[idx];
+        let ticker = price_ticker(base, quote, &top);
+        match serde_json::to_string(&ticker) {
+            Ok(data) => {
+                self.last[idx] = Some((top.best_bid, top.best_ask));
+                self.pending.push_back(
+                    Event::default()
+                        .event("ticker")
+                        .id(self.sequence.to_string())
+                        .data(data),
+                );
+            }
+            Err(e) => warn!("Failed to encode price ticker: {}", e),
+        }
+    }
+}
+
+/// Parse and resolve `base/quote,base/quote,...`
+async fn parse_pairs(state: &AppState, raw: &str) -> Result<Vec<(AssetId, AssetId)>> {
+    let raw: Vec<&str> = raw
+        .split(',')
+        .map(str::trim)
+        .filter(|pair| !pair.is_empty())
+        .collect();
+    if raw.is_empty() || raw.len() > MAX_STREAM_PAIRS {
+        return Err(ApiError::Validation(format!(
+            "Between 1 and {} pairs can be streamed",
+            MAX_STREAM_PAIRS
+        )));
+    }
+
+    let mut pairs = Vec::with_capacity(raw.len());
+    for pair in raw {
+        let (base, quote) = pair.split_once('/').ok_or_else(|| {
+            ApiError::Validation(format!("Invalid pair '{}': expected base/quote", pair))
+        })?;
+        let base = AssetPath::parse(base)
+            .map_err(|e| ApiError::InvalidAsset(format!("Invalid base asset: {}", e)))?;
+        let quote = AssetPath::parse(quote)
+            .map_err(|e| ApiError::InvalidAsset(format!("Invalid quote asset: {}", e)))?;
+        pairs.push((
+            resolve_asset(state, &base).await?,
+            resolve_asset(state, &quote).await?,
+        ));
+    }
+    Ok(pairs)
+}
+
+fn price_ticker(base: &AssetId, quote: &AssetId, top: &TopOfBook) -> PriceTicker {
+    let (mid, spread, spread_bps) = match (top.best_bid, top.best_ask) {
+        (Some(bid), Some(ask)) => spread(bid, ask),
+        _ => (None, None, None),
+    };
+    PriceTicker {
+        base: base.as_str().to_string(),
+        quote: quote.as_str().to_string(),
+        best_bid: top.best_bid.map(|p| p.to_string()),
+        best_ask: top.best_ask.map(|p| p.to_string()),
+        mid_price: mid.map(|p| p.to_string()),
+        spread,
+        spread_bps,
+        ledger: top.ledger,
+    }
+}
+
+/// Mid price, spread and spread in bps of the mid, computed exactly; parts
+/// that overflow are left out
+fn spread(bid: Price, ask: Price) -> (Option<Price>, Option<String>, Option<i64>) {
+    let (bn, bd) = (bid.numerator(), bid.denominator());
+    let (an, ad) = (ask.numerator(), ask.denominator());
+    let cross = an.checked_mul(bd).zip(bn.checked_mul(ad));
+    let d = ad.checked_mul(bd);
+
+    let mid = cross
+        .and_then(|(a, b)| a.checked_add(b))
+        .zip(d.and_then(|d| d.checked_mul(2)))
+        .and_then(|(n, d)| Price::new(n, d).ok());
+
+    // `ask - bid` as `diff / d`, which may be negative
+    let diff = cross.and_then(|(a, b)| a.checked_sub(b));
+    let spread = diff.zip(d).and_then(|(diff, d)| {
+        let size = Price::new(diff.abs(), d).ok()?;
+        Some(format!("{}{}", if diff < 0 { "-" } else { "" }, size))
+    });
+    let spread_bps = diff.zip(d).zip(mid).and_then(|((diff, d), mid)| {
+        if mid.numerator() == 0 {
+            return None;
+        }
+        let n = diff.checked_mul(mid.denominator())?.checked_mul(10_000)?;
+        let d = d.checked_mul(mid.numerator())?;
+        (n / d).try_into().ok()
+    });
+    (mid, spread, spread_bps)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn price(n: i128, d: i128) -> Price {
+        Price::new(n, d).unwrap()
+    }
+
+    #[test]
+    fn test_spread_is_exact() {
+        let (mid, spread, bps) = spread(price(99, 1000), price(101, 1000));
+        assert_eq!(mid, Some(price(1, 10)));
+        assert_eq!(spread.as_deref(), Some("0.0020000"));
+        assert_eq!(bps, Some(200));
+    }
+
+    #[test]
+    fn test_crossed_book_has_negative_spread() {
+        let (mid, spread, bps) = spread(price(11, 100), price(9, 100));
+        assert_eq!(mid, Some(price(1, 10)));
+        assert_eq!(spread.as_deref(), Some("-0.0200000"));
+        assert_eq!(bps, Some(-2000));
+    }
+
+    #[test]
+    fn test_one_sided_book_has_no_mid() {
+        let top = TopOfBook {
+            sequence: 4,
+            ledger: 9,
+            best_bid: None,
+            best_ask: Some(price(1, 10)),
+        };
+        let ticker = price_ticker(&AssetId::native(), &AssetId::credit("USDC", "G"), &top);
+        assert_eq!(ticker.best_ask.as_deref(), Some("0.1000000"));
+        assert_eq!(ticker.quote, "USDC:G");
+        assert!(ticker.mid_price.is_none() && ticker.spread.is_none());
+    }
+}
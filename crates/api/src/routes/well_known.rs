@@ -0,0 +1,37 @@
+//! Well-known discovery endpoints
+
+use axum::{extract::State, Json};
+use std::sync::Arc;
+
+use crate::{
+    error::{ApiError, Result},
+    models::QuoteSigningKeyResponse,
+    signing::MESSAGE_VERSION,
+    state::AppState,
+};
+
+/// Public key quote signatures can be verified with
+///
+/// Returns **404 Not Found** when the API is not configured to sign quotes.
+#[utoipa::path(
+    get,
+    path = "/.well-known/stellarroute-quote-key",
+    tag = "trading",
+    responses(
+        (status = 200, description = "Quote signing key", body = QuoteSigningKeyResponse),
+        (status = 404, description = "Quotes are not signed", body = ErrorResponse),
+    )
+)]
+pub async fn get_quote_signing_key(
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<QuoteSigningKeyResponse>> {
+    let signer = state
+        .signer
+        .as_ref()
+        .ok_or_else(|| ApiError::NotFound("Quote signing is not enabled".to_string()))?;
+    Ok(Json(QuoteSigningKeyResponse {
+        algorithm: "ed25519".to_string(),
+        public_key: signer.public_key(),
+        message_version: MESSAGE_VERSION.to_string(),
+    }))
+}
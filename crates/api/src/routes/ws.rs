@@ -0,0 +1,310 @@
+//! WebSocket streaming endpoint
+//!
+//! Clients send JSON requests to open and close subscriptions:
+//!
+//! ```text
+//! {"op": "subscribe", "channel": "orderbook", "base": "native", "quote": "USDC:G..."}
+//! {"op": "subscribe", "channel": "quote", "base": "native", "quote": "USDC:G...", "amount": "100"}
+//! {"op": "unsubscribe", "subscription": 1}
+//! ```
+//!
+//! An orderbook subscription starts with a snapshot of the book, then gets
+//! one update per liquidity feed event with the levels that changed, even
+//! when none did. Updates carry the feed sequence: if one is not exactly one
+//! above the last, updates were missed and the client should resync from
+//! `GET /api/v1/orderbook`. Levels carry their new total depth, so applying
+//! updates to a fresher book converges on the current state.
+//!
+//! A quote subscription gets a new firm quote on every feed event. Streamed
+//! quotes are not kept, so their ids cannot be fetched back from
+//! `/api/v1/quote/by-id`; take a quote from `GET /api/v1/quote` for that.
+
+use axum::{
+    extract::{
+        ws::{Message, WebSocket, WebSocketUpgrade},
+        State,
+    },
+    response::Response,
+};
+use std::{collections::BTreeMap, sync::Arc};
+use stellarroute_routing::{
+    amount::{Amount, Price},
+    graph::AssetId,
+};
+use tokio::sync::broadcast::error::RecvError;
+use tracing::{debug, warn};
+
+use crate::{
+    error::{ApiError, Result},
+    feed::FeedEvent,
+    models::{
+        request::{AssetPath, StreamChannel, StreamRequest, StreamSubscription},
+        BookLevel, ErrorResponse, OrderbookUpdate, QuoteUpdate, StreamMessage,
+    },
+    routes::quote::{firm_quote, resolve_asset, QuoteRequest},
+    state::AppState,
+};
+
+/// Most subscriptions open at once on one connection
+pub const MAX_SUBSCRIPTIONS: usize = 20;
+
+/// Stream orderbook changes and live quotes
+///
+/// Upgrades to a WebSocket; see the module documentation for the protocol.
+#[utoipa::path(
+    get,
+    path = "/api/v1/ws",
+    tag = "trading",
+    responses(
+        (status = 101, description = "Switching to the WebSocket protocol"),
+    )
+)]
+pub async fn stream(ws: WebSocketUpgrade, State(state): State<Arc<AppState>>) -> Response {
+    ws.on_upgrade(move |socket| run(socket, state))
+}
+
+async fn run(mut socket: WebSocket, state: Arc<AppState>) {
+    // Subscribed before any snapshot is taken, so none misses an event
+    let mut events = state.feed.subscribe();
+    let mut session = Session::new(state);
+    loop {
+        let replies = tokio::select! {
+            message = socket.recv() => match message {
+                Some(Ok(Message::Text(text))) => session.handle_request(&text).await,
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                // Pings are answered by axum
+                Some(Ok(_)) => continue,
+            },
+            event = events.recv() => match event {
+                Ok(event) => session.handle_event(&event).await,
+                // The sequence gap tells the client
+                Err(RecvError::Lagged(missed)) => {
+                    debug!("WebSocket client fell {} feed events behind", missed);
+                    continue;
+                }
+                Err(RecvError::Closed) => break,
+            },
+        };
+        for reply in replies {
+            let text = match serde_json::to_string(&reply) {
+                Ok(text) => text,
+                Err(e) => {
+                    warn!("Failed to encode stream message: {}", e);
+                    continue;
+                }
+            };
+            if socket.send(Message::Text(text)).await.is_err() {
+                return;
+            }
+        }
+    }
+}
+
+enum Subscription {
+    Orderbook {
+        base: AssetId,
+        quote: AssetId,
+        /// Sequence of the snapshot sent; earlier events are already in it
+        since: u64,
+    },
+    Quote {
+        request: QuoteRequest,
+        base: AssetId,
+        quote: AssetId,
+        /// Sequence the first quote was priced at
+        since: u64,
+    },
+}
+
+/// Subscriptions of one connection
+struct Session {
+    state: Arc<AppState>,
+    next_id: u64,
+    subscriptions: BTreeMap<u64, Subscription>,
+}
+
+impl Session {
+    fn new(state: Arc<AppState>) -> Self {
+        Self {
+            state,
+            next_id: 1,
+            subscriptions: BTreeMap::new(),
+        }
+    }
+
+    async fn handle_request(&mut self, text: &str) -> Vec<StreamMessage> {
+        let request = match serde_json::from_str::<StreamRequest>(text) {
+            Ok(request) => request,
+            Err(e) => {
+                return vec![error_message(
+                    None,
+                    ApiError::BadRequest(format!("Invalid message: {}", e)),
+                )]
+            }
+        };
+        match request {
+            StreamRequest::Subscribe(subscription) => match self.subscribe(subscription).await {
+                Ok(messages) => messages,
+                Err(e) => vec![error_message(None, e)],
+            },
+            StreamRequest::Unsubscribe { subscription } => {
+                match self.subscriptions.remove(&subscription) {
+                    Some(_) => vec![StreamMessage::Unsubscribed { subscription }],
+                    None => vec![error_message(
+                        Some(subscription),
+                        ApiError::NotFound(format!("No subscription {}", subscription)),
+                    )],
+                }
+            }
+        }
+    }
+
+    async fn subscribe(&mut self, request: StreamSubscription) -> Result<Vec<StreamMessage>> {
+        if self.subscriptions.len() >= MAX_SUBSCRIPTIONS {
+            return Err(ApiError::Validation(format!(
+                "At most {} subscriptions per connection",
+                MAX_SUBSCRIPTIONS
+            )));
+        }
+
+        let id = self.next_id;
+        let (subscription, first) = match request.channel {
+            StreamChannel::Orderbook => {
+                let base = parse_asset(&self.state, &request.base, "base").await?;
+                let quote = parse_asset(&self.state, &request.quote, "quote").await?;
+                let book = self.state.feed.book(&base, &quote);
+                let first = StreamMessage::Orderbook(OrderbookUpdate {
+                    subscription: id,
+                    sequence: book.sequence,
+                    ledger: book.ledger,
+                    snapshot: true,
+                    asks: book_levels(&book.asks),
+                    bids: book_levels(&book.bids),
+                });
+                let since = book.sequence;
+                (Subscription::Orderbook { base, quote, since }, first)
+            }
+            StreamChannel::Quote => {
+                let request = QuoteRequest::parse(
+                    &request.base,
+                    &request.quote,
+                    request.amount.as_deref(),
+                    request.quote_type,
+                    request.slippage_bps,
+                )?;
+                let base = resolve_asset(&self.state, &request.base).await?;
+                let quote = resolve_asset(&self.state, &request.quote).await?;
+                let sequence = self.state.feed.sequence();
+                let first = self.price(id, sequence, &request, &base, &quote);
+                (
+                    Subscription::Quote {
+                        request,
+                        base,
+                        quote,
+                        since: sequence,
+                    },
+                    first,
+                )
+            }
+        };
+
+        self.next_id += 1;
+        self.subscriptions.insert(id, subscription);
+        Ok(vec![
+            StreamMessage::Subscribed {
+                subscription: id,
+                channel: request.channel,
+            },
+            first,
+        ])
+    }
+
+    async fn handle_event(&self, event: &FeedEvent) -> Vec<StreamMessage> {
+        let mut messages = Vec::with_capacity(self.subscriptions.len());
+        for (&id, subscription) in &self.subscriptions {
+            match subscription {
+                Subscription::Orderbook { since, .. } | Subscription::Quote { since, .. }
+                    if event.sequence <= *since => {}
+                Subscription::Orderbook { base, quote, .. } => {
+                    let changed = |selling: &AssetId, buying: &AssetId| {
+                        event
+                            .levels
+                            .iter()
+                            .filter(|l| &l.selling == selling && &l.buying == buying)
+                            .map(|l| (l.price, l.amount))
+                            .collect::<Vec<_>>()
+                    };
+                    let mut bids = changed(quote, base);
+                    bids.reverse();
+                    messages.push(StreamMessage::Orderbook(OrderbookUpdate {
+                        subscription: id,
+                        sequence: event.sequence,
+                        ledger: event.ledger,
+                        snapshot: false,
+                        asks: book_levels(&changed(base, quote)),
+                        bids: book_levels(&bids),
+                    }));
+                }
+                Subscription::Quote {
+                    request,
+                    base,
+                    quote,
+                    ..
+                } => messages.push(self.price(id, event.sequence, request, base, quote)),
+            }
+        }
+        messages
+    }
+
+    /// A new firm quote for a subscription, or why there is none
+    fn price(
+        &self,
+        subscription: u64,
+        sequence: u64,
+        request: &QuoteRequest,
+        base: &AssetId,
+        quote: &AssetId,
+    ) -> StreamMessage {
+        let graph = self.state.routing.snapshot();
+        // Not stored: every feed event would keep one more quote per
+        // subscription for the whole retention period
+        let (quote, error) = match firm_quote(&self.state, &graph, request, base, quote) {
+            Ok(firm) => (Some(firm.into_response()), None),
+            Err(e) => (None, Some(error_response(e))),
+        };
+        StreamMessage::Quote(Box::new(QuoteUpdate {
+            subscription,
+            sequence,
+            ledger: graph.watermark().ledger,
+            quote,
+            error,
+        }))
+    }
+}
+
+async fn parse_asset(state: &AppState, raw: &str, side: &str) -> Result<AssetId> {
+    let asset = AssetPath::parse(raw)
+        .map_err(|e| ApiError::InvalidAsset(format!("Invalid {} asset: {}", side, e)))?;
+    resolve_asset(state, &asset).await
+}
+
+fn book_levels(levels: &[(Price, Amount)]) -> Vec<BookLevel> {
+    levels
+        .iter()
+        .map(|(price, amount)| BookLevel {
+            price: price.to_string(),
+            amount: amount.to_string(),
+        })
+        .collect()
+}
+
+fn error_response(error: ApiError) -> ErrorResponse {
+    error.into_parts().1
+}
+
+fn error_message(subscription: Option<u64>, error: ApiError) -> StreamMessage {
+    StreamMessage::Error {
+        subscription,
+        error: error_response(error),
+    }
+}
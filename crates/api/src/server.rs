@@ -3,6 +3,7 @@
 use axum::Router;
 use sqlx::PgPool;
 use std::{net::SocketAddr, sync::Arc};
+use stellarroute_routing::simulate::RouterState;
 use tower_http::{
     compression::CompressionLayer,
     cors::{Any, CorsLayer},
@@ -16,8 +17,11 @@ use crate::{
     cache::CacheManager,
     docs::ApiDoc,
     error::Result,
+    ledger::{self, LedgerSync},
+    liquidity::{LiquiditySync, REFRESH_INTERVAL},
     middleware::{EndpointConfig, RateLimitLayer},
     routes,
+    signing::QuoteSigner,
     state::AppState,
 };
 
@@ -34,6 +38,10 @@ pub struct ServerConfig {
     pub enable_compression: bool,
     /// Redis URL (optional)
     pub redis_url: Option<String>,
+    /// Key to sign quotes with (optional)
+    pub quote_signer: Option<Arc<QuoteSigner>>,
+    /// Router contract state swaps are simulated against
+    pub router: RouterState,
 }
 
 impl Default for ServerConfig {
@@ -44,6 +52,8 @@ impl Default for ServerConfig {
             enable_cors: true,
             enable_compression: true,
             redis_url: None,
+            quote_signer: None,
+            router: RouterState::default(),
         }
     }
 }
@@ -81,12 +91,12 @@ impl Server {
                         }
                     };
 
-                    (Arc::new(AppState::with_cache(db, cache)), rate_limit)
+                    (AppState::with_cache(db, cache), rate_limit)
                 }
                 Err(e) => {
                     warn!("⚠️  Redis connection failed, running without cache: {}", e);
                     (
-                        Arc::new(AppState::new(db)),
+                        AppState::new(db),
                         RateLimitLayer::in_memory(EndpointConfig::default()),
                     )
                 }
@@ -94,11 +104,34 @@ impl Server {
         } else {
             info!("ℹ️  Running without Redis cache");
             (
-                Arc::new(AppState::new(db)),
+                AppState::new(db),
                 RateLimitLayer::in_memory(EndpointConfig::default()),
             )
         };
 
+        let state = match &config.quote_signer {
+            Some(signer) => {
+                info!("✅ Signing quotes with key {}", signer.public_key());
+                state.with_quote_signer(signer.clone())
+            }
+            None => {
+                info!("ℹ️  Quote signing disabled");
+                state
+            }
+        }
+        .with_router_state(config.router.clone())
+        .into_arc();
+
+        // Load the indexed offers before serving quotes, then keep them fresh
+        let mut sync =
+            LiquiditySync::new(state.db.clone(), state.routing.clone(), state.feed.clone());
+        match sync.refresh().await {
+            Ok(offers) => info!("✅ Liquidity graph loaded ({} offers)", offers),
+            Err(e) => warn!("⚠️  Initial liquidity graph load failed: {}", e),
+        }
+        sync.spawn(REFRESH_INTERVAL);
+        LedgerSync::new(state.db.clone(), state.ledger.clone()).spawn(ledger::REFRESH_INTERVAL);
+
         let app = Self::build_app(state, &config, rate_limit_layer);
 
         Self { config, app }
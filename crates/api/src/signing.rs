@@ -0,0 +1,307 @@
+//! Quote signing
+//!
+//! Quotes are signed with an ed25519 key so that services relaying them can
+//! prove the route, amounts and expiry were not altered on the way. The key
+//! is a Stellar secret seed (`S...`); its public half is published as a
+//! Stellar account id (`G...`) at `/.well-known/stellarroute-quote-key`.
+//!
+//! The signature covers a line-oriented message built from the quote, so
+//! clients in any language can rebuild it without a canonical JSON encoder:
+//!
+//! ```text
+//! stellarroute-quote-v2
+//! quote_id:<quote_id>
+//! base:<base asset>
+//! quote:<quote asset>
+//! type:<quote_type>
+//! amount:<amount>
+//! price:<price>
+//! total:<total>
+//! fee:<fee>
+//! min_received:<min_received>
+//! max_sent:<max_sent>                   (buy quotes only)
+//! price_impact:<price_impact>
+//! timestamp:<timestamp>
+//! valid_until:<valid_until>
+//! hop:<from>><to> <source> <price>      (one line per path step)
+//! route:<estimated_output> <min_output> <expires_at>
+//! route_hop:<source>><destination> <pool> <pool_type>   (one line per route hop)
+//! ```
+//!
+//! The `route` lines describe the contract route served with the quote, so
+//! the pools it executes through and the output and ledger it is bound to
+//! are signed too; a quote served without one has the single line
+//! `route:none`. Assets are in canonical form ("native", "CODE:ISSUER", or a
+//! token contract address), pool types as served ("sdex", "amm_const_prod"
+//! or "amm_stable"), and lines are joined with `\n` without a trailing
+//! newline. The signature is base64.
+
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use ed25519_dalek::{Signer, SigningKey};
+use std::fmt;
+use stellar_strkey::ed25519::{PrivateKey, PublicKey};
+use stellarroute_routing::{
+    export::{ContractAsset, ContractRoute},
+    graph::PoolType,
+};
+
+use crate::models::QuoteResponse;
+
+/// First line of every signed message, naming the format
+pub const MESSAGE_VERSION: &str = "stellarroute-quote-v2";
+
+/// Signs quotes with the API's ed25519 key
+pub struct QuoteSigner {
+    key: SigningKey,
+}
+
+impl QuoteSigner {
+    /// Signer for a Stellar secret seed (`S...`)
+    pub fn from_secret_seed(seed: &str) -> anyhow::Result<Self> {
+        let seed = PrivateKey::from_string(seed.trim())
+            .map_err(|e| anyhow::anyhow!("invalid secret seed: {}", e))?;
+        Ok(Self {
+            key: SigningKey::from_bytes(&seed.0),
+        })
+    }
+
+    /// Public key as a Stellar account id (`G...`)
+    pub fn public_key(&self) -> String {
+        PublicKey(self.key.verifying_key().to_bytes()).to_string()
+    }
+
+    /// Set `quote.signature` over its [`signing_message`] with the contract
+    /// `route` it is served with
+    pub fn sign(&self, quote: &mut QuoteResponse, route: Option<&ContractRoute>) {
+        let signature = self.key.sign(signing_message(quote, route).as_bytes());
+        quote.signature = Some(BASE64.encode(signature.to_bytes()));
+    }
+}
+
+impl fmt::Debug for QuoteSigner {
+    /// Shows the public key only, so configs can be logged safely
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("QuoteSigner")
+            .field("public_key", &self.public_key())
+            .finish()
+    }
+}
+
+/// The message the signature of a quote served with `route` covers
+pub fn signing_message(quote: &QuoteResponse, route: Option<&ContractRoute>) -> String {
+    let mut lines = vec![
+        MESSAGE_VERSION.to_string(),
+        format!("quote_id:{}", quote.quote_id),
+        format!("base:{}", quote.base_asset.to_canonical()),
+        format!("quote:{}", quote.quote_asset.to_canonical()),
+        format!("type:{}", quote.quote_type),
+        format!("amount:{}", quote.amount),
+        format!("price:{}", quote.price),
+        format!("total:{}", quote.total),
+        format!("fee:{}", quote.fee),
+        format!("min_received:{}", quote.min_received),
+    ];
+    if let Some(max_sent) = &quote.max_sent {
+        lines.push(format!("max_sent:{}", max_sent));
+    }
+    lines.extend([
+        format!("price_impact:{}", quote.price_impact),
+        format!("timestamp:{}", quote.timestamp),
+        format!("valid_until:{}", quote.valid_until),
+    ]);
+    lines.extend(quote.path.iter().map(|step| {
+        format!(
+            "hop:{}>{} {} {}",
+            step.from_asset.to_canonical(),
+            step.to_asset.to_canonical(),
+            step.source,
+            step.price
+        )
+    }));
+    match route {
+        Some(route) => {
+            lines.push(format!(
+                "route:{} {} {}",
+                route.estimated_output, route.min_output, route.expires_at
+            ));
+            lines.extend(route.hops.iter().map(|hop| {
+                format!(
+                    "route_hop:{}>{} {} {}",
+                    canonical(&hop.source),
+                    canonical(&hop.destination),
+                    hop.pool,
+                    pool_type(hop.pool_type)
+                )
+            }));
+        }
+        None => lines.push("route:none".to_string()),
+    }
+    lines.join("\n")
+}
+
+fn canonical(asset: &ContractAsset) -> String {
+    match asset {
+        ContractAsset::Native => "native".to_string(),
+        ContractAsset::Issued { issuer, code } => format!("{}:{}", code, issuer),
+        ContractAsset::Soroban { address } => address.clone(),
+    }
+}
+
+fn pool_type(pool_type: PoolType) -> &'static str {
+    match pool_type {
+        PoolType::Sdex => "sdex",
+        PoolType::AmmConstProd => "amm_const_prod",
+        PoolType::AmmStable => "amm_stable",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{AssetInfo, PathStep};
+    use ed25519_dalek::{Signature, Verifier, VerifyingKey};
+    use stellarroute_routing::export::ContractRouteHop;
+
+    fn signer() -> QuoteSigner {
+        QuoteSigner::from_secret_seed(&PrivateKey([7; 32]).to_string()).unwrap()
+    }
+
+    fn quote() -> QuoteResponse {
+        let usdc = AssetInfo::credit("USDC".to_string(), Some("GISSUER".to_string()));
+        let eurc = AssetInfo::credit("EURC".to_string(), Some("GISSUER".to_string()));
+        QuoteResponse {
+            quote_id: "2b1f0c1e-8f4e-4c52-9d0a-4e0f5b8a7c11".to_string(),
+            base_asset: AssetInfo::native(),
+            quote_asset: usdc.clone(),
+            amount: "100.0000000".to_string(),
+            price: "0.1000000".to_string(),
+            total: "10.0000000".to_string(),
+            quote_type: "sell".to_string(),
+            path: vec![
+                PathStep {
+                    from_asset: AssetInfo::native(),
+                    to_asset: eurc.clone(),
+                    price: "0.0900000".to_string(),
+                    source: "amm:CPOOL".to_string(),
+                },
+                PathStep {
+                    from_asset: eurc,
+                    to_asset: usdc,
+                    price: "1.1000000".to_string(),
+                    source: "sdex".to_string(),
+                },
+            ],
+            price_impact: "0.12".to_string(),
+            fee: "0.0300000".to_string(),
+            min_received: "9.9500000".to_string(),
+            max_sent: None,
+            timestamp: 1_700_000_000,
+            valid_until: 1_700_000_030,
+            signature: None,
+        }
+    }
+
+    fn route() -> ContractRoute {
+        let eurc = ContractAsset::Issued {
+            issuer: "GISSUER".to_string(),
+            code: "EURC".to_string(),
+        };
+        ContractRoute {
+            hops: vec![
+                ContractRouteHop {
+                    source: ContractAsset::Native,
+                    destination: eurc.clone(),
+                    pool: "CPOOL".to_string(),
+                    pool_type: PoolType::AmmConstProd,
+                },
+                ContractRouteHop {
+                    source: eurc,
+                    destination: ContractAsset::Soroban {
+                        address: "CUSDC".to_string(),
+                    },
+                    pool: "CADAPTER".to_string(),
+                    pool_type: PoolType::Sdex,
+                },
+            ],
+            estimated_output: 100_000_000,
+            min_output: 99_500_000,
+            expires_at: 51_000_007,
+        }
+    }
+
+    #[test]
+    fn test_signing_message_layout() {
+        assert_eq!(
+            signing_message(&quote(), Some(&route())),
+            "stellarroute-quote-v2\n\
+             quote_id:2b1f0c1e-8f4e-4c52-9d0a-4e0f5b8a7c11\n\
+             base:native\n\
+             quote:USDC:GISSUER\n\
+             type:sell\n\
+             amount:100.0000000\n\
+             price:0.1000000\n\
+             total:10.0000000\n\
+             fee:0.0300000\n\
+             min_received:9.9500000\n\
+             price_impact:0.12\n\
+             timestamp:1700000000\n\
+             valid_until:1700000030\n\
+             hop:native>EURC:GISSUER amm:CPOOL 0.0900000\n\
+             hop:EURC:GISSUER>USDC:GISSUER sdex 1.1000000\n\
+             route:100000000 99500000 51000007\n\
+             route_hop:native>EURC:GISSUER CPOOL amm_const_prod\n\
+             route_hop:EURC:GISSUER>CUSDC CADAPTER sdex"
+        );
+    }
+
+    #[test]
+    fn test_signing_message_marks_buys_and_missing_routes() {
+        let mut quote = quote();
+        quote.quote_type = "buy".to_string();
+        quote.max_sent = Some("10.0500000".to_string());
+        let message = signing_message(&quote, None);
+        assert!(message.contains("\nmin_received:9.9500000\nmax_sent:10.0500000\nprice_impact:"));
+        assert!(message.ends_with("\nroute:none"));
+    }
+
+    #[test]
+    fn test_signature_verifies_against_published_key() {
+        let signer = signer();
+        let mut quote = quote();
+        signer.sign(&mut quote, Some(&route()));
+
+        let public = PublicKey::from_string(&signer.public_key()).unwrap();
+        let key = VerifyingKey::from_bytes(&public.0).unwrap();
+        let bytes = BASE64.decode(quote.signature.as_deref().unwrap()).unwrap();
+        let signature = Signature::from_slice(&bytes).unwrap();
+        let verifies = |quote: &QuoteResponse, route: Option<&ContractRoute>| {
+            key.verify(signing_message(quote, route).as_bytes(), &signature)
+                .is_ok()
+        };
+        assert!(verifies(&quote, Some(&route())));
+
+        // Any change to the route or amounts breaks the signature, as does
+        // dropping the route
+        assert!(!verifies(&quote, None));
+        let mut other_pool = route();
+        other_pool.hops[0].pool = "CELSEWHERE".to_string();
+        assert!(!verifies(&quote, Some(&other_pool)));
+        let mut looser = route();
+        looser.min_output = 90_000_000;
+        assert!(!verifies(&quote, Some(&looser)));
+        let mut later = route();
+        later.expires_at += 100;
+        assert!(!verifies(&quote, Some(&later)));
+        quote.min_received = "9.0000000".to_string();
+        assert!(!verifies(&quote, Some(&route())));
+    }
+
+    #[test]
+    fn test_debug_does_not_reveal_the_seed() {
+        let seed = PrivateKey([7; 32]).to_string();
+        let debug = format!("{:?}", signer());
+        assert!(debug.contains(&signer().public_key()));
+        assert!(!debug.contains(&seed));
+        assert!(QuoteSigner::from_secret_seed("not a seed").is_err());
+    }
+}
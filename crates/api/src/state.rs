@@ -2,9 +2,13 @@
 
 use sqlx::PgPool;
 use std::sync::Arc;
+use stellarroute_routing::{simulate::RouterState, RoutingEngine};
 use tokio::sync::Mutex;
 
-use crate::cache::CacheManager;
+use crate::{
+    cache::CacheManager, feed::LiquidityFeed, ledger::LedgerClock, quote_store::QuoteStore,
+    signing::QuoteSigner,
+};
 
 /// Shared API state
 #[derive(Clone)]
@@ -13,6 +17,18 @@ pub struct AppState {
     pub db: PgPool,
     /// Redis cache manager (optional)
     pub cache: Option<Arc<Mutex<CacheManager>>>,
+    /// Routing engine over the indexed liquidity
+    pub routing: Arc<RoutingEngine>,
+    /// Updates applied to the routing engine, for streaming clients
+    pub feed: LiquidityFeed,
+    /// Latest ledger the network has closed
+    pub ledger: LedgerClock,
+    /// Quotes issued, by id
+    pub quotes: QuoteStore,
+    /// Key quotes are signed with (optional)
+    pub signer: Option<Arc<QuoteSigner>>,
+    /// Router contract state swaps are simulated against
+    pub router: RouterState,
     /// API version
     pub version: String,
 }
@@ -23,6 +39,12 @@ impl AppState {
         Self {
             db,
             cache: None,
+            routing: Arc::new(RoutingEngine::new()),
+            feed: LiquidityFeed::new(),
+            ledger: LedgerClock::new(),
+            quotes: QuoteStore::in_memory(),
+            signer: None,
+            router: RouterState::default(),
             version: env!("CARGO_PKG_VERSION").to_string(),
         }
     }
@@ -31,11 +53,29 @@ impl AppState {
     pub fn with_cache(db: PgPool, cache: CacheManager) -> Self {
         Self {
             db,
+            quotes: QuoteStore::redis(cache.clone()),
             cache: Some(Arc::new(Mutex::new(cache))),
+            routing: Arc::new(RoutingEngine::new()),
+            feed: LiquidityFeed::new(),
+            ledger: LedgerClock::new(),
+            signer: None,
+            router: RouterState::default(),
             version: env!("CARGO_PKG_VERSION").to_string(),
         }
     }
 
+    /// Sign every quote issued with `signer`
+    pub fn with_quote_signer(mut self, signer: Arc<QuoteSigner>) -> Self {
+        self.signer = Some(signer);
+        self
+    }
+
+    /// Simulate swaps against `router`
+    pub fn with_router_state(mut self, router: RouterState) -> Self {
+        self.router = router;
+        self
+    }
+
     /// Wrap in Arc for sharing across handlers
     pub fn into_arc(self) -> Arc<Self> {
         Arc::new(self)
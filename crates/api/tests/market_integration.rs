@@ -0,0 +1,140 @@
+//! Integration tests for the candle and ticker endpoints
+//!
+//! These seed trades directly and are `#[ignore]` — run with:
+//!   DATABASE_URL=postgres://... cargo test -p stellarroute-api -- --ignored
+
+mod common;
+
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+    Router,
+};
+use common::{get, Pair};
+use sqlx::PgPool;
+use tower::ServiceExt; // for `oneshot`
+use uuid::Uuid;
+
+/// Three trades of a fresh pair, the second reported the other way round:
+/// 10 BASE for 20 QUOT, 10 BASE for 30 QUOT half an hour later, then
+/// 5 BASE for 20 QUOT in the next hour. Returns the pair and the hour the
+/// first two fall in.
+async fn seed(pool: &PgPool) -> (String, String, i64) {
+    let Pair {
+        base_id: base,
+        quote_id: quote,
+        base: base_asset,
+        quote: quote_asset,
+    } = common::seed_pair(pool).await;
+
+    let hour = chrono::Utc::now().timestamp() / 3_600 * 3_600 - 3 * 3_600;
+    let op = 1_000_000_000_000 + (Uuid::new_v4().as_u128() % 1_000_000_000) as i64;
+    for (i, (offset, sold, bought, base_amount, counter_amount)) in [
+        (10, base, quote, "10", "20"),
+        (1_800, quote, base, "30", "10"),
+        (3_900, base, quote, "5", "20"),
+    ]
+    .into_iter()
+    .enumerate()
+    {
+        sqlx::query(
+            "insert into sdex_trades (
+               id, operation_id, trade_index, ledger_sequence, ledger_close_time, trade_type,
+               base_asset_id, counter_asset_id, base_amount, counter_amount, price, base_is_seller)
+             values ($1, $2, 0, $2 >> 32, to_timestamp($3), 'orderbook',
+                     $4, $5, $6::numeric, $7::numeric, $7::numeric / $6::numeric, true)",
+        )
+        .bind(format!("{}-0", op + i as i64))
+        .bind(op + i as i64)
+        .bind((hour + offset) as f64)
+        .bind(sold)
+        .bind(bought)
+        .bind(base_amount)
+        .bind(counter_amount)
+        .execute(pool)
+        .await
+        .unwrap();
+    }
+    (base_asset, quote_asset, hour)
+}
+
+async fn router() -> (Router, String, String, i64) {
+    let pool = common::connect().await;
+    let (base, quote, hour) = seed(&pool).await;
+    (common::router(pool), base, quote, hour)
+}
+
+#[tokio::test]
+#[ignore = "requires a running PostgreSQL database (set DATABASE_URL)"]
+async fn candles_aggregate_both_trade_orientations() {
+    let (router, base, quote, hour) = router().await;
+    let uri = format!(
+        "/api/v1/candles/{}/{}?interval=1h&from={}&to={}",
+        base,
+        quote,
+        hour,
+        hour + 2 * 3_600
+    );
+    let (status, json) = get(router.clone(), &uri).await;
+
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["interval"], "1h");
+    let candles = json["candles"].as_array().expect("candles is an array");
+    assert_eq!(candles.len(), 2);
+    assert_eq!(candles[0]["time"], hour);
+    assert_eq!(candles[0]["open"], "2.00000000000000");
+    assert_eq!(candles[0]["high"], "3.00000000000000");
+    assert_eq!(candles[0]["low"], "2.00000000000000");
+    assert_eq!(candles[0]["close"], "3.00000000000000");
+    assert_eq!(candles[0]["volume"], "20.00000000000000");
+    assert_eq!(candles[0]["quote_volume"], "50.00000000000000");
+    assert_eq!(candles[0]["trades"], 2);
+    assert_eq!(candles[1]["time"], hour + 3_600);
+    assert_eq!(candles[1]["close"], "4.00000000000000");
+
+    // Seen from the other side, prices invert and volumes swap
+    let uri = format!(
+        "/api/v1/candles/{}/{}?interval=1d&from={}&to={}",
+        quote,
+        base,
+        hour - 86_400,
+        hour + 86_400
+    );
+    let (_, json) = get(router.clone(), &uri).await;
+    let candles = json["candles"].as_array().unwrap();
+    let volume: f64 = candles
+        .iter()
+        .map(|c| c["volume"].as_str().unwrap().parse::<f64>().unwrap())
+        .sum();
+    assert_eq!(volume, 70.0);
+
+    let uri = format!("/api/v1/candles/{}/{}?interval=2h", base, quote);
+    let response = router
+        .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+#[ignore = "requires a running PostgreSQL database (set DATABASE_URL)"]
+async fn ticker_reports_the_last_24_hours() {
+    let (router, base, quote, _) = router().await;
+    let (status, json) = get(router, &format!("/api/v1/ticker/{}/{}", base, quote)).await;
+
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["open"], "2.00000000000000");
+    assert_eq!(json["high"], "4.00000000000000");
+    assert_eq!(json["low"], "2.00000000000000");
+    assert_eq!(json["close"], "4.00000000000000");
+    assert_eq!(json["volume"], "25.00000000000000");
+    assert_eq!(json["quote_volume"], "70.00000000000000");
+    assert_eq!(json["trades"], 3);
+    assert_eq!(json["change"], "2.00000000000000");
+    assert_eq!(json["change_percent"], "100.00");
+    assert!(json["mid_price"].is_null());
+    assert_eq!(
+        json["to"].as_i64().unwrap() - json["from"].as_i64().unwrap(),
+        86_400
+    );
+}
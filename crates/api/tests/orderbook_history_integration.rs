@@ -0,0 +1,121 @@
+//! Integration tests for GET /api/v1/orderbook/{base}/{quote}/history
+//!
+//! The live tests are `#[ignore]` — run with:
+//!   DATABASE_URL=postgres://... cargo test -p stellarroute-api -- --ignored
+
+mod common;
+
+use axum::{http::StatusCode, Router};
+use common::get;
+use serde_json::Value;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+fn ledgers(json: &Value) -> Vec<u64> {
+    json["snapshots"]
+        .as_array()
+        .expect("snapshots is an array")
+        .iter()
+        .map(|s| s["ledger"].as_u64().unwrap())
+        .collect()
+}
+
+/// Two fresh assets with a one-offer book captured at ledgers 100..=105,
+/// one snapshot every 20 seconds; returns the pair as `BASE:ISSUER` strings
+async fn seed(pool: &PgPool) -> (String, String) {
+    let pair = common::seed_pair(pool).await;
+    let offer_id = (Uuid::new_v4().as_u128() as i64).abs();
+    sqlx::query(
+        "insert into sdex_offers
+           (offer_id, seller, selling_asset_id, buying_asset_id, amount, price, last_modified_ledger)
+         values ($1, 'GSELLER', $2, $3, 10, 2, 100)",
+    )
+    .bind(offer_id)
+    .bind(pair.base_id)
+    .bind(pair.quote_id)
+    .execute(pool)
+    .await
+    .unwrap();
+
+    for ledger in 100..=105i64 {
+        let snapshot: Uuid = sqlx::query_scalar("select capture_orderbook_snapshot($1, $2, $3)")
+            .bind(pair.base_id)
+            .bind(pair.quote_id)
+            .bind(ledger)
+            .fetch_one(pool)
+            .await
+            .unwrap();
+        sqlx::query(
+            "update orderbook_snapshots
+             set snapshot_time = to_timestamp(1699999980 + ($2 - 100) * 20)
+             where id = $1",
+        )
+        .bind(snapshot)
+        .bind(ledger)
+        .execute(pool)
+        .await
+        .unwrap();
+    }
+    (pair.base, pair.quote)
+}
+
+async fn router() -> (Router, String, String) {
+    let pool = common::connect().await;
+    let (base, quote) = seed(&pool).await;
+    (common::router(pool), base, quote)
+}
+
+#[tokio::test]
+#[ignore = "requires a running PostgreSQL database (set DATABASE_URL)"]
+async fn history_returns_snapshots_in_range() {
+    let (router, base, quote) = router().await;
+    let uri = format!(
+        "/api/v1/orderbook/{}/{}/history?from_ledger=101&to={}",
+        base,
+        quote,
+        1_699_999_980 + 4 * 20
+    );
+    let (status, json) = get(router, &uri).await;
+
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(ledgers(&json), vec![101, 102, 103, 104]);
+    assert!(json["next_from_ledger"].is_null());
+    let snapshot = &json["snapshots"][0];
+    assert_eq!(snapshot["timestamp"], 1_700_000_000);
+    assert_eq!(snapshot["bids"][0]["price"], "2.00000000000000");
+    assert_eq!(snapshot["bids"][0]["amount"], "10.00000000000000");
+    assert_eq!(snapshot["asks"], Value::Array(vec![]));
+    assert!(snapshot["mid_price"].is_null());
+}
+
+#[tokio::test]
+#[ignore = "requires a running PostgreSQL database (set DATABASE_URL)"]
+async fn history_downsamples_and_pages() {
+    let (router, base, quote) = router().await;
+
+    // 60 s buckets hold ledgers 100-102 and 103-105; the last of each is kept
+    let uri = format!("/api/v1/orderbook/{}/{}/history?interval=60", base, quote);
+    let (_, json) = get(router.clone(), &uri).await;
+    assert_eq!(ledgers(&json), vec![102, 105]);
+
+    let uri = format!("/api/v1/orderbook/{}/{}/history?limit=4", base, quote);
+    let (_, first) = get(router.clone(), &uri).await;
+    assert_eq!(ledgers(&first), vec![100, 101, 102, 103]);
+    assert_eq!(first["next_from_ledger"], 104);
+
+    let uri = format!(
+        "/api/v1/orderbook/{}/{}/history?limit=4&from_ledger=104",
+        base, quote
+    );
+    let (_, second) = get(router.clone(), &uri).await;
+    assert_eq!(ledgers(&second), vec![104, 105]);
+    assert!(second["next_from_ledger"].is_null());
+
+    let uri = format!(
+        "/api/v1/orderbook/{}/{}/history?from_ledger=5&to_ledger=1",
+        base, quote
+    );
+    let (status, json) = get(router, &uri).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(json["error"], "validation_error");
+}
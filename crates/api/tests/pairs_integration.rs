@@ -28,6 +28,8 @@ fn trading_pair_serializes_to_spec_shape() {
         counter_asset: "USDC:GBBD47IF6LWK7P7MDEVSCWR7DPUWV3NY3DTQEVFL4NAT4AQH3ZLLFLA5".to_string(),
         offer_count: 42,
         last_updated: None,
+        last_price: Some("0.11000000000000".to_string()),
+        volume_24h: "1500.00000000000000".to_string(),
     };
 
     let response = PairsResponse {
@@ -46,6 +48,8 @@ fn trading_pair_serializes_to_spec_shape() {
     assert_eq!(first["base"], "XLM");
     assert_eq!(first["counter"], "USDC");
     assert_eq!(first["base_asset"], "native");
+    assert_eq!(first["last_price"], "0.11000000000000");
+    assert_eq!(first["volume_24h"], "1500.00000000000000");
     assert!(
         first["counter_asset"]
             .as_str()
@@ -109,6 +113,8 @@ async fn get_pairs_returns_200_and_valid_json() {
         enable_cors: false,
         enable_compression: false,
         redis_url: None,
+        quote_signer: None,
+        router: Default::default(),
     };
 
     let router = Server::new(config, pool).await.into_router();
@@ -0,0 +1,362 @@
+//! Integration tests for the quote endpoints
+//!
+//! Quotes are priced from the routing engine's in-memory graph, so these run
+//! without a database: assets are given with their issuer, and the lazy pool
+//! is never connected.
+
+use std::sync::Arc;
+
+use axum::{
+    body::Body,
+    http::{header, Method, Request, StatusCode},
+    Router,
+};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use ed25519_dalek::{Signature, Verifier, VerifyingKey};
+use serde_json::{json, Value};
+use sqlx::postgres::PgPoolOptions;
+use stellar_strkey::ed25519::{PrivateKey, PublicKey};
+use stellarroute_api::{
+    models::IssuedQuoteResponse,
+    routes::create_router,
+    signing::{signing_message, QuoteSigner},
+    AppState,
+};
+use stellarroute_routing::{
+    delta::{GraphDelta, GraphUpdate, SdexOffer},
+    graph::{AmmPool, AssetId, PoolType, Venue},
+    simulate::RouterState,
+};
+use tower::ServiceExt; // for `oneshot`
+
+const EURC: &str = "EURC:GISSUER";
+const USDC: &str = "USDC:GISSUER";
+
+fn state() -> AppState {
+    let db = PgPoolOptions::new()
+        .connect_lazy("postgres://unused@localhost/unused")
+        .expect("lazy pool");
+    AppState::new(db)
+}
+
+fn router() -> Router {
+    router_with(state())
+}
+
+/// XLM reaches USDC only through an XLM/EURC pool and EURC/USDC books
+fn router_with(state: AppState) -> Router {
+    let state = Arc::new(state);
+    state
+        .routing
+        .upsert_venue(Venue::Amm(AmmPool {
+            address: "CPOOL".to_string(),
+            pool_type: PoolType::AmmConstProd,
+            asset_a: AssetId::native(),
+            asset_b: AssetId::new(EURC),
+            reserve_a: 100_000_000_000_000,
+            reserve_b: 9_000_000_000_000,
+            fee_bps: 30,
+            amplification: None,
+        }))
+        .unwrap();
+    state
+        .routing
+        .apply_update(&GraphUpdate {
+            ledger: 1_234,
+            deltas: vec![
+                GraphDelta::UpsertOffer(SdexOffer {
+                    id: 1,
+                    selling: AssetId::new(USDC),
+                    buying: AssetId::new(EURC),
+                    price_n: 10,
+                    price_d: 11,
+                    amount: 1_000_000_000_000,
+                }),
+                GraphDelta::UpsertOffer(SdexOffer {
+                    id: 2,
+                    selling: AssetId::new(EURC),
+                    buying: AssetId::new(USDC),
+                    price_n: 11,
+                    price_d: 10,
+                    amount: 1_000_000_000_000,
+                }),
+            ],
+        })
+        .unwrap();
+    create_router(state)
+}
+
+async fn send(router: Router, request: Request<Body>) -> (StatusCode, Value) {
+    let response = router.oneshot(request).await.expect("request failed");
+    let status = response.status();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .expect("failed to read body");
+    (status, serde_json::from_slice(&body).expect("body is JSON"))
+}
+
+fn post_quotes(body: Value) -> Request<Body> {
+    Request::builder()
+        .method(Method::POST)
+        .uri("/api/v1/quotes")
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Body::from(body.to_string()))
+        .unwrap()
+}
+
+#[tokio::test]
+async fn quote_routes_through_intermediate_asset() {
+    let request = Request::builder()
+        .uri(format!("/api/v1/quote/native/{}?amount=100", USDC))
+        .body(Body::empty())
+        .unwrap();
+    let (status, json) = send(router(), request).await;
+
+    assert_eq!(status, StatusCode::OK);
+    let path = json["path"].as_array().expect("path is an array");
+    assert_eq!(path.len(), 2);
+    assert_eq!(path[0]["source"], "amm:CPOOL");
+    assert_eq!(path[1]["source"], "sdex");
+    for field in ["price_impact", "fee", "min_received"] {
+        assert!(json[field].is_string(), "missing '{}'", field);
+    }
+}
+
+#[tokio::test]
+async fn batch_returns_results_in_request_order() {
+    let (status, json) = send(
+        router(),
+        post_quotes(json!([
+            { "base": "native", "quote": USDC, "amount": "100" },
+            { "base": "native", "quote": "BTC:GISSUER", "amount": "100" },
+            { "base": "native", "quote": USDC, "amount": "-1" },
+            { "base": "native", "quote": USDC, "amount": "5", "quote_type": "buy" },
+        ])),
+    )
+    .await;
+
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["ledger"], 1_234);
+    let results = json["results"].as_array().expect("results is an array");
+    assert_eq!(results.len(), 4);
+
+    assert_eq!(results[0]["quote"]["path"].as_array().unwrap().len(), 2);
+    assert!(results[0].get("error").is_none());
+    assert_eq!(results[1]["error"]["error"], "no_route");
+    assert!(results[1].get("quote").is_none());
+    assert_eq!(results[2]["error"]["error"], "validation_error");
+    assert_eq!(results[3]["quote"]["quote_type"], "buy");
+    assert_eq!(results[3]["quote"]["min_received"], "5.0000000");
+}
+
+#[tokio::test]
+async fn batch_matches_single_quotes() {
+    let single = Request::builder()
+        .uri(format!("/api/v1/quote/native/{}?amount=100", USDC))
+        .body(Body::empty())
+        .unwrap();
+    let (_, single) = send(router(), single).await;
+    let (_, batch) = send(
+        router(),
+        post_quotes(json!([{ "base": "native", "quote": USDC, "amount": "100" }])),
+    )
+    .await;
+
+    let batched = &batch["results"][0]["quote"];
+    assert_ne!(batched["quote_id"], single["quote_id"]);
+    for field in ["price", "total", "fee", "min_received", "path"] {
+        assert_eq!(batched[field], single[field], "'{}' differs", field);
+    }
+}
+
+#[tokio::test]
+async fn batch_rejects_empty_and_oversized_requests() {
+    let (status, json) = send(router(), post_quotes(json!([]))).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(json["error"], "validation_error");
+
+    let item = json!({ "base": "native", "quote": USDC });
+    let oversized = Value::Array(vec![item; 51]);
+    let (status, _) = send(router(), post_quotes(oversized)).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn issued_quotes_can_be_fetched_by_id() {
+    let router = router();
+    let request = Request::builder()
+        .uri(format!("/api/v1/quote/native/{}?amount=100", USDC))
+        .body(Body::empty())
+        .unwrap();
+    let (_, quote) = send(router.clone(), request).await;
+    assert_eq!(
+        quote["valid_until"].as_i64().unwrap(),
+        quote["timestamp"].as_i64().unwrap() + 30
+    );
+
+    let id = quote["quote_id"].as_str().expect("quote has an id");
+    let request = Request::builder()
+        .uri(format!("/api/v1/quote/by-id/{}", id))
+        .body(Body::empty())
+        .unwrap();
+    let (status, fetched) = send(router.clone(), request).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(fetched, quote);
+
+    let (_, batch) = send(
+        router.clone(),
+        post_quotes(json!([{ "base": "native", "quote": USDC, "amount": "7" }])),
+    )
+    .await;
+    let id = batch["results"][0]["quote"]["quote_id"].as_str().unwrap();
+    let request = Request::builder()
+        .uri(format!("/api/v1/quote/by-id/{}", id))
+        .body(Body::empty())
+        .unwrap();
+    let (status, fetched) = send(router, request).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(fetched["amount"], "7.0000000");
+}
+
+#[tokio::test]
+async fn fetched_quotes_carry_their_contract_route() {
+    let pool = stellar_strkey::Contract([1; 32]).to_string();
+    let usdc = format!("USDC:{}", stellar_strkey::ed25519::PublicKey([2; 32]));
+    let state = Arc::new(state().with_router_state(RouterState {
+        protocol_fee_bps: 30,
+        ..RouterState::default()
+    }));
+    state.ledger.observe(51_000_000, chrono::Utc::now());
+    state
+        .routing
+        .upsert_venue(Venue::Amm(AmmPool {
+            address: pool.clone(),
+            pool_type: PoolType::AmmConstProd,
+            asset_a: AssetId::native(),
+            asset_b: AssetId::new(&usdc),
+            reserve_a: 100_000_000_000_000,
+            reserve_b: 10_000_000_000_000,
+            fee_bps: 30,
+            amplification: None,
+        }))
+        .unwrap();
+    let router = create_router(state.clone());
+
+    let fetch = |router: Router, quote_type: &str| {
+        let uri = format!(
+            "/api/v1/quote/native/{}?amount=100&slippage_bps=50&quote_type={}",
+            usdc, quote_type
+        );
+        async move {
+            let request = Request::builder().uri(uri).body(Body::empty()).unwrap();
+            let (_, quote) = send(router.clone(), request).await;
+            let id = quote["quote_id"].as_str().unwrap();
+            let request = Request::builder()
+                .uri(format!("/api/v1/quote/by-id/{}", id))
+                .body(Body::empty())
+                .unwrap();
+            let (status, fetched) = send(router, request).await;
+            (quote, status, fetched)
+        }
+    };
+    let stroops =
+        |amount: &Value| -> i64 { amount.as_str().unwrap().replace('.', "").parse().unwrap() };
+
+    let (quote, status, fetched) = fetch(router.clone(), "sell").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(fetched["total"], quote["total"]);
+    assert_eq!(fetched["route"], quote["route"]);
+
+    // The quote and the route agree on the output after the router's fee
+    let route = &fetched["route"];
+    assert_eq!(route["hops"][0]["pool"], pool);
+    assert_eq!(route["hops"][0]["pool_type"], "amm_const_prod");
+    assert_eq!(route["estimated_output"], stroops(&quote["total"]));
+    assert_eq!(route["min_output"], stroops(&quote["min_received"]));
+    // Six ledgers after the one the quote's transaction would land in
+    assert_eq!(route["expires_at"], 51_000_007);
+
+    // A buy must pay out the amount, with slippage taken on what it sends
+    let (quote, _, fetched) = fetch(router.clone(), "buy").await;
+    let route = &fetched["route"];
+    assert_eq!(route["min_output"], 1_000_000_000);
+    assert!(route["estimated_output"].as_i64().unwrap() >= 1_000_000_000);
+    let sent = stroops(&quote["total"]);
+    assert!(stroops(&quote["max_sent"]) > sent);
+
+    // Without a recent ledger the route cannot be given an expiry
+    state.ledger.observe(
+        51_000_000,
+        chrono::Utc::now() - chrono::Duration::minutes(5),
+    );
+    let (_, status, fetched) = fetch(router, "sell").await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(fetched.get("route").is_none());
+}
+
+#[tokio::test]
+async fn unknown_or_malformed_quote_ids_are_rejected() {
+    let request = Request::builder()
+        .uri("/api/v1/quote/by-id/7f9c24e5-0000-4000-8000-000000000000")
+        .body(Body::empty())
+        .unwrap();
+    let (status, json) = send(router(), request).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert_eq!(json["error"], "not_found");
+
+    let request = Request::builder()
+        .uri("/api/v1/quote/by-id/not-a-uuid")
+        .body(Body::empty())
+        .unwrap();
+    let (status, _) = send(router(), request).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn signed_quotes_publish_their_key() {
+    let seed = PrivateKey([7; 32]).to_string();
+    let signer = Arc::new(QuoteSigner::from_secret_seed(&seed).unwrap());
+    let router = router_with(state().with_quote_signer(signer.clone()));
+
+    let request = Request::builder()
+        .uri(format!("/api/v1/quote/native/{}?amount=100", USDC))
+        .body(Body::empty())
+        .unwrap();
+    let (_, quote) = send(router.clone(), request).await;
+    let issued: IssuedQuoteResponse = serde_json::from_value(quote).unwrap();
+    let signature = issued.quote.signature.as_deref().expect("quote is signed");
+    let signature = Signature::from_slice(&BASE64.decode(signature).unwrap()).unwrap();
+    let public = PublicKey::from_string(&signer.public_key()).unwrap();
+    let message = signing_message(&issued.quote, issued.route.as_ref());
+    assert!(VerifyingKey::from_bytes(&public.0)
+        .unwrap()
+        .verify(message.as_bytes(), &signature)
+        .is_ok());
+
+    let request = Request::builder()
+        .uri("/.well-known/stellarroute-quote-key")
+        .body(Body::empty())
+        .unwrap();
+    let (status, key) = send(router, request).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(key["algorithm"], "ed25519");
+    assert_eq!(key["public_key"], signer.public_key());
+    assert_eq!(key["message_version"], "stellarroute-quote-v2");
+}
+
+#[tokio::test]
+async fn unsigned_deployments_have_no_quote_key() {
+    let request = Request::builder()
+        .uri(format!("/api/v1/quote/native/{}?amount=100", USDC))
+        .body(Body::empty())
+        .unwrap();
+    let (_, quote) = send(router(), request).await;
+    assert!(quote.get("signature").is_none());
+
+    let request = Request::builder()
+        .uri("/.well-known/stellarroute-quote-key")
+        .body(Body::empty())
+        .unwrap();
+    let (status, _) = send(router(), request).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
@@ -0,0 +1,228 @@
+//! Integration tests for the swap simulation endpoint
+//!
+//! Swaps are simulated on the routing engine's in-memory graph, so these run
+//! without a database; the lazy pool is never connected.
+
+use std::sync::Arc;
+
+use axum::{
+    body::Body,
+    http::{header, Method, Request, StatusCode},
+    Router,
+};
+use serde_json::{json, Value};
+use sqlx::postgres::PgPoolOptions;
+use stellarroute_api::{routes::create_router, AppState};
+use stellarroute_routing::{
+    delta::{GraphDelta, GraphUpdate, SdexOffer},
+    export::ContractAsset,
+    graph::{AmmPool, AssetId, PoolType, Venue},
+    simulate::RouterState,
+};
+use tower::ServiceExt; // for `oneshot`
+
+const ADAPTER: &str = "CADAPTER";
+const USDC: &str = "USDC:GISSUER";
+const EURC: &str = "EURC:GISSUER";
+
+fn router_state() -> RouterState {
+    RouterState {
+        protocol_fee_bps: 30,
+        sdex_adapter: Some(ADAPTER.to_string()),
+        ..RouterState::default()
+    }
+}
+
+/// An XLM/USDC pool, and a book selling EURC for USDC, with the network at
+/// ledger 1,250
+fn router_with(router: RouterState) -> Router {
+    let state = state_with(router);
+    state.ledger.observe(1_250, chrono::Utc::now());
+    create_router(state)
+}
+
+/// The liquidity of [`router_with`], last changed at ledger 1,234, without
+/// any view of the network's ledger
+fn state_with(router: RouterState) -> Arc<AppState> {
+    let db = PgPoolOptions::new()
+        .connect_lazy("postgres://unused@localhost/unused")
+        .expect("lazy pool");
+    let state = Arc::new(AppState::new(db).with_router_state(router));
+    state
+        .routing
+        .upsert_venue(Venue::Amm(AmmPool {
+            address: "CPOOL".to_string(),
+            pool_type: PoolType::AmmConstProd,
+            asset_a: AssetId::native(),
+            asset_b: AssetId::new(USDC),
+            reserve_a: 1_000_000_000_000,
+            reserve_b: 1_000_000_000_000,
+            fee_bps: 30,
+            amplification: None,
+        }))
+        .unwrap();
+    state
+        .routing
+        .apply_update(&GraphUpdate {
+            ledger: 1_234,
+            deltas: vec![GraphDelta::UpsertOffer(SdexOffer {
+                id: 1,
+                selling: AssetId::new(EURC),
+                buying: AssetId::new(USDC),
+                price_n: 1,
+                price_d: 2,
+                amount: 1_000_000_000,
+            })],
+        })
+        .unwrap();
+    state
+}
+
+fn issued(code: &str) -> Value {
+    json!({ "kind": "issued", "issuer": "GISSUER", "code": code })
+}
+
+/// XLM to USDC through the pool, then USDC to EURC on the SDEX
+fn swap() -> Value {
+    json!({
+        "route": {
+            "hops": [
+                {
+                    "source": { "kind": "native" },
+                    "destination": issued("USDC"),
+                    "pool": "CPOOL",
+                    "pool_type": "amm_const_prod"
+                },
+                {
+                    "source": issued("USDC"),
+                    "destination": issued("EURC"),
+                    "pool": ADAPTER,
+                    "pool_type": "sdex"
+                }
+            ],
+            "estimated_output": 0,
+            "min_output": 0,
+            "expires_at": 1_300
+        },
+        "amount_in": 100_000_000,
+        "min_amount_out": 0,
+        "recipient": "GRECIPIENT",
+        "deadline": 1_300
+    })
+}
+
+async fn simulate(router: Router, body: Value) -> (StatusCode, Value) {
+    let request = Request::builder()
+        .method(Method::POST)
+        .uri("/api/v1/simulate")
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Body::from(body.to_string()))
+        .unwrap();
+    let response = router.oneshot(request).await.expect("request failed");
+    let status = response.status();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .expect("failed to read body");
+    (status, serde_json::from_slice(&body).expect("body is JSON"))
+}
+
+#[tokio::test]
+async fn test_simulated_swap_reports_output_or_contract_error() {
+    let (status, body) = simulate(router_with(router_state()), swap()).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["success"], true);
+    assert_eq!(body["error"], Value::Null);
+    assert_eq!(body["ledger"], 1_251);
+    assert_eq!(body["price_impact_bps"], 10);
+    let hops = body["hops"].as_array().unwrap();
+    assert_eq!(hops.len(), 2);
+    assert_eq!(hops[1]["pool"], ADAPTER);
+    let gross = hops[1]["amount_out"].as_i64().unwrap();
+    let amount_out = body["amount_out"].as_i64().unwrap();
+    assert_eq!(body["fee_amount"].as_i64().unwrap(), gross * 30 / 10_000);
+    assert_eq!(amount_out, gross - gross * 30 / 10_000);
+
+    // One stroop more than the swap pays is slippage
+    let mut short = swap();
+    short["min_amount_out"] = json!(amount_out + 1);
+    let (status, body) = simulate(router_with(router_state()), short).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["success"], false);
+    assert_eq!(body["error"]["code"], 22);
+    assert_eq!(body["error"]["name"], "SlippageExceeded");
+    assert_eq!(body["amount_out"], amount_out);
+
+    let mut tight = swap();
+    tight["max_price_impact_bps"] = json!(5);
+    let (_, body) = simulate(router_with(router_state()), tight).await;
+    assert_eq!(body["error"]["name"], "PriceImpactTooHigh");
+
+    // The swap lands in the ledger after the network's latest, however long
+    // ago the liquidity last changed
+    let mut late = swap();
+    late["deadline"] = json!(1_250);
+    let (_, body) = simulate(router_with(router_state()), late).await;
+    assert_eq!(body["error"]["name"], "DeadlineExceeded");
+    assert!(body["hops"].as_array().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_unknown_ledger_is_reported_not_guessed() {
+    let mut late = swap();
+    late["deadline"] = json!(1);
+
+    // Nothing recorded yet
+    let state = state_with(router_state());
+    let (status, body) = simulate(create_router(state.clone()), late.clone()).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["ledger"], Value::Null);
+    assert_eq!(body["success"], true);
+
+    // Last recorded too long ago
+    state
+        .ledger
+        .observe(1_250, chrono::Utc::now() - chrono::Duration::minutes(5));
+    let (_, body) = simulate(create_router(state), late).await;
+    assert_eq!(body["ledger"], Value::Null);
+}
+
+#[tokio::test]
+async fn test_router_state_is_applied() {
+    let allowlisted = RouterState {
+        allowed_tokens: vec![
+            ContractAsset::Native,
+            ContractAsset::Issued {
+                issuer: "GISSUER".to_string(),
+                code: "USDC".to_string(),
+            },
+        ],
+        ..router_state()
+    };
+    let (_, body) = simulate(router_with(allowlisted), swap()).await;
+    assert_eq!(body["error"]["code"], 80);
+    assert_eq!(body["error"]["name"], "TokenNotAllowed");
+
+    let mev = RouterState {
+        commit_threshold: Some(100_000_000),
+        ..router_state()
+    };
+    let (_, body) = simulate(router_with(mev), swap()).await;
+    assert_eq!(body["error"]["name"], "CommitmentRequired");
+
+    let no_adapter = RouterState {
+        sdex_adapter: None,
+        ..router_state()
+    };
+    let (_, body) = simulate(router_with(no_adapter), swap()).await;
+    assert_eq!(body["error"]["name"], "PoolNotSupported");
+    assert_eq!(body["hops"].as_array().unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn test_non_positive_amount_is_rejected() {
+    let mut body = swap();
+    body["amount_in"] = json!(0);
+    let (status, body) = simulate(router_with(router_state()), body).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["error"], "validation_error");
+}
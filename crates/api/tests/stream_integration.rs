@@ -0,0 +1,210 @@
+//! Integration tests for the Server-Sent Events price stream
+//!
+//! Feed events are published by the tests themselves, standing in for the
+//! liquidity sync, and the stream is read straight from the response body.
+
+use std::{sync::Arc, time::Duration};
+
+use axum::{
+    body::{Body, BodyDataStream},
+    http::{header, Request, StatusCode},
+};
+use futures_util::StreamExt;
+use serde_json::Value;
+use sqlx::postgres::PgPoolOptions;
+use stellarroute_api::{routes::create_router, AppState};
+use stellarroute_routing::{
+    delta::{GraphDelta, SdexOffer},
+    graph::AssetId,
+};
+use tower::ServiceExt; // for `oneshot`
+
+const USDC: &str = "USDC:GISSUER";
+const EURC: &str = "EURC:GISSUER";
+
+/// Offer selling `selling` for `buying` at `price_n/price_d`
+fn offer(id: u64, selling: &str, buying: &str, price_n: i64, price_d: i64) -> GraphDelta {
+    let asset = |raw: &str| match raw {
+        "native" => AssetId::native(),
+        raw => AssetId::new(raw),
+    };
+    GraphDelta::UpsertOffer(SdexOffer {
+        id,
+        selling: asset(selling),
+        buying: asset(buying),
+        price_n,
+        price_d,
+        amount: 1_000_000_000,
+    })
+}
+
+/// XLM/USDC asked at 0.101 and bid at 0.099, EURC/USDC asked at 1.1
+fn state() -> Arc<AppState> {
+    let db = PgPoolOptions::new()
+        .connect_lazy("postgres://unused@localhost/unused")
+        .expect("lazy pool");
+    let state = Arc::new(AppState::new(db));
+    state.feed.publish(
+        50,
+        &[
+            offer(1, "native", USDC, 101, 1000),
+            offer(2, USDC, "native", 1000, 99),
+            offer(3, EURC, USDC, 11, 10),
+        ],
+    );
+    state
+}
+
+async fn open(state: &Arc<AppState>, last_event_id: Option<&str>) -> BodyDataStream {
+    let mut request = Request::builder().uri(format!(
+        "/api/v1/stream/prices?pairs=native/{},{}/{}",
+        USDC, EURC, USDC
+    ));
+    if let Some(id) = last_event_id {
+        request = request.header("last-event-id", id);
+    }
+    let response = create_router(state.clone())
+        .oneshot(request.body(Body::empty()).unwrap())
+        .await
+        .expect("request failed");
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        response.headers()[header::CONTENT_TYPE],
+        "text/event-stream"
+    );
+    response.into_body().into_data_stream()
+}
+
+/// Next `ticker` event as `(id, data)`
+async fn next_ticker(body: &mut BodyDataStream, buffer: &mut String) -> (u64, Value) {
+    loop {
+        if let Some(end) = buffer.find("\n\n") {
+            let frame: String = buffer.drain(..end + 2).collect();
+            let field = |name: &str| {
+                frame
+                    .lines()
+                    .find_map(|line| line.strip_prefix(name))
+                    .map(str::to_string)
+            };
+            if field("event: ").as_deref() != Some("ticker") {
+                continue;
+            }
+            let id = field("id: ").unwrap().parse().unwrap();
+            return (id, serde_json::from_str(&field("data: ").unwrap()).unwrap());
+        }
+        let chunk = tokio::time::timeout(Duration::from_secs(5), body.next())
+            .await
+            .expect("no event within 5s")
+            .expect("stream ended")
+            .unwrap();
+        buffer.push_str(std::str::from_utf8(&chunk).unwrap());
+    }
+}
+
+async fn assert_quiet(body: &mut BodyDataStream) {
+    let next = tokio::time::timeout(Duration::from_millis(200), body.next()).await;
+    assert!(next.is_err(), "unexpected event: {:?}", next);
+}
+
+#[tokio::test]
+async fn stream_sends_current_prices_then_changes() {
+    let state = state();
+    let mut body = open(&state, None).await;
+    let mut buffer = String::new();
+
+    let (id, xlm) = next_ticker(&mut body, &mut buffer).await;
+    assert_eq!(id, 1);
+    assert_eq!(xlm["base"], "native");
+    assert_eq!(xlm["best_bid"], "0.0990000");
+    assert_eq!(xlm["best_ask"], "0.1010000");
+    assert_eq!(xlm["mid_price"], "0.1000000");
+    assert_eq!(xlm["spread"], "0.0020000");
+    assert_eq!(xlm["spread_bps"], 200);
+    let (_, eurc) = next_ticker(&mut body, &mut buffer).await;
+    assert_eq!(eurc["best_ask"], "1.1000000");
+    assert_eq!(eurc["best_bid"], Value::Null);
+
+    // A deeper ask leaves the top of the book alone
+    state
+        .feed
+        .publish(51, &[offer(4, "native", USDC, 102, 1000)]);
+    assert_quiet(&mut body).await;
+
+    state
+        .feed
+        .publish(52, &[offer(5, "native", USDC, 100, 1000)]);
+    let (id, xlm) = next_ticker(&mut body, &mut buffer).await;
+    assert_eq!(id, 3);
+    assert_eq!(xlm["best_ask"], "0.1000000");
+    assert_eq!(xlm["ledger"], 52);
+    assert_quiet(&mut body).await;
+}
+
+#[tokio::test]
+async fn ids_are_the_events_that_changed_each_pair() {
+    let state = state();
+    let mut body = open(&state, None).await;
+    let mut buffer = String::new();
+    next_ticker(&mut body, &mut buffer).await;
+    next_ticker(&mut body, &mut buffer).await;
+
+    // Both are published before the stream reads the first
+    state
+        .feed
+        .publish(51, &[offer(4, "native", USDC, 100, 1000)]);
+    state.feed.publish(52, &[offer(5, EURC, USDC, 1, 1)]);
+
+    let (id, xlm) = next_ticker(&mut body, &mut buffer).await;
+    assert_eq!((id, xlm["base"].as_str()), (2, Some("native")));
+    let (id, eurc) = next_ticker(&mut body, &mut buffer).await;
+    assert_eq!((id, eurc["base"].as_str()), (3, Some(EURC)));
+
+    // Resuming after the first still gets the second
+    let mut body = open(&state, Some("2")).await;
+    let mut buffer = String::new();
+    let (id, eurc) = next_ticker(&mut body, &mut buffer).await;
+    assert_eq!((id, eurc["best_ask"].as_str()), (3, Some("1.0000000")));
+    assert_quiet(&mut body).await;
+}
+
+#[tokio::test]
+async fn resuming_sends_only_pairs_changed_since_last_event() {
+    let state = state();
+    state.feed.publish(51, &[GraphDelta::RemoveOffer { id: 3 }]);
+
+    let mut body = open(&state, Some("1")).await;
+    let mut buffer = String::new();
+    let (id, eurc) = next_ticker(&mut body, &mut buffer).await;
+    assert_eq!(id, 2);
+    assert_eq!(eurc["base"], EURC);
+    assert_eq!(eurc["best_ask"], Value::Null);
+    assert_quiet(&mut body).await;
+
+    // Nothing happened after the latest event
+    let mut body = open(&state, Some("2")).await;
+    assert_quiet(&mut body).await;
+
+    // An id the feed never issued (e.g. before a restart) resends everything
+    let mut body = open(&state, Some("90")).await;
+    let mut buffer = String::new();
+    let (_, first) = next_ticker(&mut body, &mut buffer).await;
+    let (_, second) = next_ticker(&mut body, &mut buffer).await;
+    assert_eq!(first["base"], "native");
+    assert_eq!(second["base"], EURC);
+}
+
+#[tokio::test]
+async fn invalid_pairs_are_rejected() {
+    for pairs in ["", "native", "native/USDC:GISSUER:EXTRA"] {
+        let response = create_router(state())
+            .oneshot(
+                Request::builder()
+                    .uri(format!("/api/v1/stream/prices?pairs={}", pairs))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST, "{}", pairs);
+    }
+}
@@ -0,0 +1,237 @@
+//! Integration tests for the WebSocket stream
+//!
+//! Runs the router on a local port and drives it with a WebSocket client.
+//! Feed events are published by the tests themselves, standing in for the
+//! liquidity sync.
+
+use std::{net::SocketAddr, sync::Arc, time::Duration};
+
+use futures_util::{SinkExt, StreamExt};
+use serde_json::{json, Value};
+use sqlx::postgres::PgPoolOptions;
+use stellarroute_api::{routes::create_router, AppState};
+use stellarroute_routing::{
+    delta::{GraphDelta, GraphUpdate, SdexOffer},
+    graph::AssetId,
+};
+use tokio::net::{TcpListener, TcpStream};
+use tokio_tungstenite::{connect_async, tungstenite::Message, MaybeTlsStream, WebSocketStream};
+use uuid::Uuid;
+
+const USDC: &str = "USDC:GISSUER";
+
+type Client = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+fn ask(id: u64, price_d: i64, amount: i128) -> GraphDelta {
+    GraphDelta::UpsertOffer(SdexOffer {
+        id,
+        selling: AssetId::native(),
+        buying: AssetId::new(USDC),
+        price_n: 1,
+        price_d,
+        amount,
+    })
+}
+
+/// Offer selling USDC for XLM, at `price_n` XLM per USDC
+fn bid(id: u64, price_n: i64, amount: i128) -> GraphDelta {
+    GraphDelta::UpsertOffer(SdexOffer {
+        id,
+        selling: AssetId::new(USDC),
+        buying: AssetId::native(),
+        price_n,
+        price_d: 1,
+        amount,
+    })
+}
+
+/// Applies `deltas` to the engine and publishes them, as the sync does
+fn update(state: &AppState, ledger: u64, deltas: Vec<GraphDelta>) {
+    state
+        .routing
+        .apply_update(&GraphUpdate {
+            ledger,
+            deltas: deltas.clone(),
+        })
+        .unwrap();
+    state.feed.publish(ledger, &deltas);
+}
+
+async fn serve() -> (Arc<AppState>, SocketAddr) {
+    let db = PgPoolOptions::new()
+        .connect_lazy("postgres://unused@localhost/unused")
+        .expect("lazy pool");
+    let state = Arc::new(AppState::new(db));
+    update(
+        &state,
+        100,
+        vec![ask(1, 10, 1_000_000_000), bid(3, 10, 1_000_000_000)],
+    );
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let router = create_router(state.clone());
+    tokio::spawn(async move { axum::serve(listener, router).await.unwrap() });
+    (state, addr)
+}
+
+async fn connect(addr: SocketAddr) -> Client {
+    let (client, _) = connect_async(format!("ws://{}/api/v1/ws", addr))
+        .await
+        .expect("WebSocket handshake");
+    client
+}
+
+async fn send(client: &mut Client, message: Value) {
+    client
+        .send(Message::Text(message.to_string()))
+        .await
+        .unwrap();
+}
+
+async fn recv(client: &mut Client) -> Value {
+    loop {
+        let message = tokio::time::timeout(Duration::from_secs(5), client.next())
+            .await
+            .expect("no message within 5s")
+            .expect("stream ended")
+            .unwrap();
+        if let Message::Text(text) = message {
+            return serde_json::from_str(&text).unwrap();
+        }
+    }
+}
+
+#[tokio::test]
+async fn orderbook_stream_sends_snapshot_then_sequenced_changes() {
+    let (state, addr) = serve().await;
+    let mut client = connect(addr).await;
+    send(
+        &mut client,
+        json!({ "op": "subscribe", "channel": "orderbook", "base": "native", "quote": USDC }),
+    )
+    .await;
+
+    let subscribed = recv(&mut client).await;
+    assert_eq!(subscribed["type"], "subscribed");
+    assert_eq!(subscribed["channel"], "orderbook");
+    let snapshot = recv(&mut client).await;
+    assert_eq!(snapshot["type"], "orderbook");
+    assert_eq!(snapshot["snapshot"], true);
+    assert_eq!(snapshot["sequence"], 1);
+    assert_eq!(
+        snapshot["asks"],
+        json!([{ "price": "0.1000000", "amount": "100.0000000" }])
+    );
+
+    update(
+        &state,
+        101,
+        vec![ask(1, 10, 400_000_000), ask(2, 20, 50_000_000)],
+    );
+    let changes = recv(&mut client).await;
+    assert_eq!(changes["snapshot"], false);
+    assert_eq!(changes["sequence"], 2);
+    assert_eq!(changes["ledger"], 101);
+    assert_eq!(
+        changes["asks"],
+        json!([
+            { "price": "0.0500000", "amount": "5.0000000" },
+            { "price": "0.1000000", "amount": "40.0000000" },
+        ])
+    );
+
+    // Updates elsewhere still advance the sequence
+    update(&state, 102, vec![]);
+    let heartbeat = recv(&mut client).await;
+    assert_eq!(heartbeat["sequence"], 3);
+    assert_eq!(heartbeat["asks"], json!([]));
+    assert_eq!(heartbeat["bids"], json!([]));
+}
+
+#[tokio::test]
+async fn quote_stream_reprices_on_every_update() {
+    let (state, addr) = serve().await;
+    let mut client = connect(addr).await;
+    send(
+        &mut client,
+        json!({
+            "op": "subscribe",
+            "channel": "quote",
+            "base": "native",
+            "quote": USDC,
+            "amount": "10",
+        }),
+    )
+    .await;
+
+    assert_eq!(recv(&mut client).await["channel"], "quote");
+    let first = recv(&mut client).await;
+    assert_eq!(first["type"], "quote");
+    assert_eq!(first["sequence"], 1);
+    assert_eq!(first["quote"]["total"], "1.0000000");
+
+    update(&state, 101, vec![bid(3, 5, 1_000_000_000)]);
+    let second = recv(&mut client).await;
+    assert_eq!(second["sequence"], 2);
+    assert_eq!(second["ledger"], 101);
+    assert_eq!(second["quote"]["total"], "2.0000000");
+    assert_ne!(second["quote"]["quote_id"], first["quote"]["quote_id"]);
+
+    // Liquidity disappearing is reported on the subscription, which stays open
+    update(&state, 102, vec![GraphDelta::RemoveOffer { id: 3 }]);
+    let third = recv(&mut client).await;
+    assert_eq!(third["sequence"], 3);
+    assert_eq!(third["error"]["error"], "no_route");
+    assert!(third.get("quote").is_none());
+
+    // Streamed quotes are not kept
+    for update in [first, second] {
+        let id = Uuid::parse_str(update["quote"]["quote_id"].as_str().unwrap()).unwrap();
+        assert!(state.quotes.get(&id).await.is_none());
+    }
+}
+
+#[tokio::test]
+async fn invalid_requests_and_unsubscribe() {
+    let (state, addr) = serve().await;
+    let mut client = connect(addr).await;
+
+    send(
+        &mut client,
+        json!({ "op": "subscribe", "channel": "trades" }),
+    )
+    .await;
+    let error = recv(&mut client).await;
+    assert_eq!(error["type"], "error");
+    assert_eq!(error["error"]["error"], "bad_request");
+
+    send(
+        &mut client,
+        json!({ "op": "unsubscribe", "subscription": 9 }),
+    )
+    .await;
+    let error = recv(&mut client).await;
+    assert_eq!(error["subscription"], 9);
+    assert_eq!(error["error"]["error"], "not_found");
+
+    send(
+        &mut client,
+        json!({ "op": "subscribe", "channel": "orderbook", "base": "native", "quote": USDC }),
+    )
+    .await;
+    let id = recv(&mut client).await["subscription"].clone();
+    recv(&mut client).await;
+    send(
+        &mut client,
+        json!({ "op": "unsubscribe", "subscription": id }),
+    )
+    .await;
+    let unsubscribed = recv(&mut client).await;
+    assert_eq!(unsubscribed["type"], "unsubscribed");
+
+    // Nothing more arrives for the closed subscription
+    update(&state, 101, vec![]);
+    let quiet = tokio::time::timeout(Duration::from_millis(200), client.next()).await;
+    assert!(quiet.is_err());
+}
@@ -0,0 +1,86 @@
+-- StellarRoute - Phase 1.5
+-- Executed trades, for candles and market statistics
+
+-- Trades as reported by Horizon, in Horizon's base/counter orientation
+create table if not exists sdex_trades (
+  id text primary key, -- Horizon trade id ("<operation id>-<index>")
+  operation_id bigint not null,
+  trade_index integer not null,
+  ledger_sequence bigint not null,
+  ledger_close_time timestamptz not null,
+  trade_type text not null, -- "orderbook" | "liquidity_pool"
+  base_asset_id uuid not null references assets(id),
+  counter_asset_id uuid not null references assets(id),
+  base_amount numeric(30, 14) not null,
+  counter_amount numeric(30, 14) not null,
+  price numeric(30, 14) not null, -- counter per base
+  base_is_seller boolean not null,
+  created_at timestamptz not null default now(),
+  check (base_asset_id != counter_asset_id),
+  check (base_amount > 0 and counter_amount > 0)
+);
+
+-- Candles and tickers scan one pair over a time range
+create index if not exists idx_sdex_trades_pair_time
+  on sdex_trades (base_asset_id, counter_asset_id, ledger_close_time desc);
+
+create index if not exists idx_sdex_trades_time
+  on sdex_trades (ledger_close_time desc);
+
+-- Keep trading_pairs.total_volume and last_trade_at current
+create or replace function record_sdex_trade()
+returns trigger as $$
+begin
+  insert into trading_pairs (base_asset_id, counter_asset_id, total_volume, last_trade_at)
+  values (new.base_asset_id, new.counter_asset_id, new.base_amount, new.ledger_close_time)
+  on conflict (base_asset_id, counter_asset_id)
+  do update set
+    total_volume = trading_pairs.total_volume + excluded.total_volume,
+    last_trade_at = greatest(trading_pairs.last_trade_at, excluded.last_trade_at),
+    updated_at = now();
+  return new;
+end;
+$$ language plpgsql;
+
+drop trigger if exists trg_sdex_trades_volume on sdex_trades;
+create trigger trg_sdex_trades_volume
+  after insert on sdex_trades
+  for each row execute function record_sdex_trade();
+
+-- Trades of a pair from one side, whichever way Horizon oriented them
+create or replace function pair_trades(
+  p_base_asset_id uuid,
+  p_counter_asset_id uuid,
+  p_from timestamptz,
+  p_to timestamptz
+)
+returns table (
+  ledger_close_time timestamptz,
+  operation_id bigint,
+  trade_index integer,
+  price numeric,
+  base_volume numeric,
+  counter_volume numeric
+) as $$
+  select t.ledger_close_time, t.operation_id, t.trade_index,
+         t.counter_amount / t.base_amount, t.base_amount, t.counter_amount
+  from sdex_trades t
+  where t.base_asset_id = p_base_asset_id
+    and t.counter_asset_id = p_counter_asset_id
+    and t.ledger_close_time >= p_from
+    and t.ledger_close_time < p_to
+  union all
+  select t.ledger_close_time, t.operation_id, t.trade_index,
+         t.base_amount / t.counter_amount, t.counter_amount, t.base_amount
+  from sdex_trades t
+  where t.base_asset_id = p_counter_asset_id
+    and t.counter_asset_id = p_base_asset_id
+    and t.ledger_close_time >= p_from
+    and t.ledger_close_time < p_to
+$$ language sql stable;
+
+comment on table sdex_trades is 'Executed SDEX and liquidity pool trades ingested from Horizon';
+comment on column sdex_trades.price is 'Counter asset paid per unit of base asset';
+comment on column sdex_trades.base_is_seller is 'Whether the base side of the trade sold the base asset';
+comment on function record_sdex_trade is 'Adds each new trade to its trading pair volume';
+comment on function pair_trades is 'Trades of a pair in a time range, priced in counter asset per base asset';
@@ -8,7 +8,9 @@ use tracing::{error, info};
 use stellarroute_indexer::config::IndexerConfig;
 use stellarroute_indexer::db::Database;
 use stellarroute_indexer::horizon::HorizonClient;
+use stellarroute_indexer::ledger::LedgerTracker;
 use stellarroute_indexer::sdex::SdexIndexer;
+use stellarroute_indexer::trades::TradeIndexer;
 
 #[tokio::main]
 async fn main() {
@@ -44,6 +46,22 @@ async fn main() {
     // Initialize Horizon client
     let horizon = HorizonClient::new(&config.stellar_horizon_url);
 
+    // Trades are followed alongside the orderbook
+    let trades = TradeIndexer::new(horizon.clone(), db.pool().clone());
+    tokio::spawn(async move {
+        if let Err(e) = trades.start_indexing().await {
+            error!("Trade indexer error: {}", e);
+        }
+    });
+
+    // The latest ledger is recorded for services reading the database
+    let ledgers = LedgerTracker::new(horizon.clone(), db.pool().clone());
+    tokio::spawn(async move {
+        if let Err(e) = ledgers.start_tracking().await {
+            error!("Ledger tracker error: {}", e);
+        }
+    });
+
     // Create indexer
     let indexer = SdexIndexer::new(horizon, db);
 
@@ -54,6 +54,9 @@ pub enum IndexerError {
     #[error("Invalid offer: {offer_id}, reason: {reason}")]
     InvalidOffer { offer_id: String, reason: String },
 
+    #[error("Invalid trade: {trade_id}, reason: {reason}")]
+    InvalidTrade { trade_id: String, reason: String },
+
     #[error("JSON parsing error: {context}, error: {error}")]
     JsonParse { context: String, error: String },
 
@@ -87,7 +90,9 @@ impl IndexerError {
             Self::Config(_) | Self::InvalidConfig { .. } => Level::ERROR,
             Self::JsonParse { .. } | Self::NumericParse { .. } => Level::WARN,
             Self::MissingField { .. } => Level::WARN,
-            Self::InvalidAsset { .. } | Self::InvalidOffer { .. } => Level::WARN,
+            Self::InvalidAsset { .. } | Self::InvalidOffer { .. } | Self::InvalidTrade { .. } => {
+                Level::WARN
+            }
             Self::StellarApi { .. } | Self::StellarApiInvalidResponse(_) => Level::WARN,
             Self::DatabaseQuery(_) => Level::ERROR,
             _ => Level::ERROR,
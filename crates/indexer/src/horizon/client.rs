@@ -1,5 +1,7 @@
 use crate::error::{IndexerError, Result};
-use crate::models::horizon::{HorizonOffer, HorizonOrderbook, HorizonPage};
+use crate::models::horizon::{
+    HorizonLedger, HorizonOffer, HorizonOrderbook, HorizonPage, HorizonTrade,
+};
 use std::time::Duration;
 use tracing::{debug, warn};
 
@@ -150,6 +152,90 @@ impl HorizonClient {
         .await
     }
 
+    /// Fetch a page of trades, oldest first, with retry logic.
+    ///
+    /// Endpoint: `GET /trades?order=asc`
+    /// Parameters:
+    /// - `limit`: Number of trades to fetch (default: 200)
+    /// - `cursor`: Paging token of the last trade already seen
+    pub async fn get_trades(
+        &self,
+        limit: Option<u32>,
+        cursor: Option<&str>,
+    ) -> Result<Vec<HorizonTrade>> {
+        self.fetch_trades("asc", limit.unwrap_or(200), cursor).await
+    }
+
+    /// Fetch the most recent trade, if the network has any.
+    ///
+    /// Endpoint: `GET /trades?order=desc&limit=1`
+    pub async fn get_latest_trade(&self) -> Result<Option<HorizonTrade>> {
+        Ok(self.fetch_trades("desc", 1, None).await?.into_iter().next())
+    }
+
+    async fn fetch_trades(
+        &self,
+        order: &str,
+        limit: u32,
+        cursor: Option<&str>,
+    ) -> Result<Vec<HorizonTrade>> {
+        let mut url = format!("{}/trades?order={}&limit={}", self.base_url, order, limit);
+
+        if let Some(c) = cursor {
+            url.push_str("&cursor=");
+            url.push_str(c);
+        }
+
+        let client = self.http.clone();
+        let url_clone = url.clone();
+
+        self.retry_request(|| async {
+            debug!("Fetching trades from: {}", url_clone);
+            let resp = client.get(&url_clone).send().await?;
+
+            let status = resp.status();
+            if !status.is_success() {
+                let error_body = resp.text().await.unwrap_or_default();
+                return Err(IndexerError::StellarApi {
+                    endpoint: url_clone.clone(),
+                    status: status.as_u16(),
+                    message: error_body,
+                });
+            }
+
+            let page: HorizonPage<HorizonTrade> = resp.json().await?;
+            Ok(page.embedded.records)
+        })
+        .await
+    }
+
+    /// Fetch the latest closed ledger, if Horizon has ingested any.
+    ///
+    /// Endpoint: `GET /ledgers?order=desc&limit=1`
+    pub async fn get_latest_ledger(&self) -> Result<Option<HorizonLedger>> {
+        let url = format!("{}/ledgers?order=desc&limit=1", self.base_url);
+        let client = self.http.clone();
+
+        self.retry_request(|| async {
+            debug!("Fetching latest ledger from: {}", url);
+            let resp = client.get(&url).send().await?;
+
+            let status = resp.status();
+            if !status.is_success() {
+                let error_body = resp.text().await.unwrap_or_default();
+                return Err(IndexerError::StellarApi {
+                    endpoint: url.clone(),
+                    status: status.as_u16(),
+                    message: error_body,
+                });
+            }
+
+            let page: HorizonPage<HorizonLedger> = resp.json().await?;
+            Ok(page.embedded.records.into_iter().next())
+        })
+        .await
+    }
+
     /// Fetch orderbook snapshot for a trading pair.
     ///
     /// Endpoint: `GET /order_book`
@@ -620,6 +706,122 @@ mod tests {
         assert!(result.is_err());
     }
 
+    // -----------------------------------------------------------------------
+    // get_trades
+    // -----------------------------------------------------------------------
+
+    #[tokio::test]
+    async fn test_get_trades_pages_forward_from_cursor() {
+        let mock_server = MockServer::start().await;
+
+        let records = serde_json::json!([{
+            "id": "107449584845914113-0",
+            "paging_token": "107449584845914113-0",
+            "ledger_close_time": "2019-07-26T09:17:02Z",
+            "trade_type": "orderbook",
+            "base_amount": "4433.2000000",
+            "base_asset_type": "native",
+            "counter_amount": "443.3200000",
+            "counter_asset_type": "credit_alphanum4",
+            "counter_asset_code": "USDC",
+            "counter_asset_issuer": "GA5ZSEJYB37JRC5AVCIA5MOP4RHTM335X2KGX3IHOJAPP5RE34K4KZVN",
+            "base_is_seller": true,
+            "price": { "n": "1", "d": "10" }
+        }]);
+        Mock::given(method("GET"))
+            .and(path("/trades"))
+            .and(query_param("order", "asc"))
+            .and(query_param("limit", "200"))
+            .and(query_param("cursor", "107449584845914112-0"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(offers_page_json(records)))
+            .mount(&mock_server)
+            .await;
+
+        let client = HorizonClient::new(mock_server.uri());
+        let trades = client
+            .get_trades(None, Some("107449584845914112-0"))
+            .await
+            .unwrap();
+        assert_eq!(trades.len(), 1);
+        assert_eq!(trades[0].paging_token, "107449584845914113-0");
+        assert_eq!(trades[0].counter_asset_code.as_deref(), Some("USDC"));
+    }
+
+    #[tokio::test]
+    async fn test_get_latest_trade_reads_newest_first() {
+        let mock_server = MockServer::start().await;
+
+        let records = serde_json::json!([{
+            "id": "107449584845914113-0",
+            "paging_token": "107449584845914113-0",
+            "ledger_close_time": "2019-07-26T09:17:02Z",
+            "trade_type": "orderbook",
+            "base_amount": "4433.2000000",
+            "base_asset_type": "native",
+            "counter_amount": "443.3200000",
+            "counter_asset_type": "credit_alphanum4",
+            "counter_asset_code": "USDC",
+            "counter_asset_issuer": "GA5ZSEJYB37JRC5AVCIA5MOP4RHTM335X2KGX3IHOJAPP5RE34K4KZVN",
+            "base_is_seller": true,
+            "price": { "n": "1", "d": "10" }
+        }]);
+        Mock::given(method("GET"))
+            .and(path("/trades"))
+            .and(query_param("order", "desc"))
+            .and(query_param("limit", "1"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(offers_page_json(records)))
+            .mount(&mock_server)
+            .await;
+
+        let client = HorizonClient::new(mock_server.uri());
+        let latest = client.get_latest_trade().await.unwrap().unwrap();
+        assert_eq!(latest.paging_token, "107449584845914113-0");
+    }
+
+    #[tokio::test]
+    async fn test_get_latest_trade_empty_network() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(path("/trades"))
+            .and(query_param("order", "desc"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_string(offers_page_json(serde_json::json!([]))),
+            )
+            .mount(&mock_server)
+            .await;
+
+        let client = HorizonClient::new(mock_server.uri());
+        assert!(client.get_latest_trade().await.unwrap().is_none());
+    }
+
+    // -----------------------------------------------------------------------
+    // get_latest_ledger
+    // -----------------------------------------------------------------------
+
+    #[tokio::test]
+    async fn test_get_latest_ledger_reads_newest_first() {
+        let mock_server = MockServer::start().await;
+
+        let records = serde_json::json!([{
+            "id": "f3ae4b3a0ac4c7b8d10f5c24dd8e5c1e0a2b5e4e4b1a0f0d8d4c3b2a19080706",
+            "sequence": 51_000_123,
+            "closed_at": "2024-03-01T12:00:05Z"
+        }]);
+        Mock::given(method("GET"))
+            .and(path("/ledgers"))
+            .and(query_param("order", "desc"))
+            .and(query_param("limit", "1"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(offers_page_json(records)))
+            .mount(&mock_server)
+            .await;
+
+        let client = HorizonClient::new(mock_server.uri());
+        let ledger = client.get_latest_ledger().await.unwrap().unwrap();
+        assert_eq!(ledger.sequence, 51_000_123);
+        assert_eq!(ledger.closed_at, "2024-03-01T12:00:05Z");
+    }
+
     // -----------------------------------------------------------------------
     // get_orderbook – success
     // -----------------------------------------------------------------------
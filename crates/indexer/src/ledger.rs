@@ -0,0 +1,74 @@
+//! Network ledger tracking
+//!
+//! Records the latest ledger Horizon has closed in `ingestion_state`, so
+//! services reading the database know where the network is, rather than
+//! inferring it from the liquidity they were last given: offers only carry
+//! the ledger they were last modified in, which stands still on a quiet book.
+
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use tracing::{debug, error};
+
+use crate::error::{IndexerError, Result};
+use crate::horizon::HorizonClient;
+
+/// `ingestion_state` key holding the sequence of the latest closed ledger;
+/// the row's `updated_at` is that ledger's close time
+pub const LATEST_LEDGER_KEY: &str = "latest_ledger";
+
+/// Latest closed ledger tracker
+pub struct LedgerTracker {
+    horizon: HorizonClient,
+    pool: PgPool,
+}
+
+impl LedgerTracker {
+    pub fn new(horizon: HorizonClient, pool: PgPool) -> Self {
+        Self { horizon, pool }
+    }
+
+    /// Follow the latest ledger until the process stops
+    pub async fn start_tracking(&self) -> Result<()> {
+        loop {
+            match self.record_latest().await {
+                Ok(Some(sequence)) => debug!("Latest ledger is {}", sequence),
+                Ok(None) => {}
+                Err(e) => error!("Error tracking the latest ledger: {}", e),
+            }
+
+            // Ledgers close about every 5 seconds
+            tokio::time::sleep(tokio::time::Duration::from_secs(5)).await;
+        }
+    }
+
+    /// Store the latest closed ledger; returns its sequence, or `None` when
+    /// Horizon has not ingested any
+    pub async fn record_latest(&self) -> Result<Option<u64>> {
+        let Some(ledger) = self.horizon.get_latest_ledger().await? else {
+            return Ok(None);
+        };
+        let closed_at = DateTime::parse_from_rfc3339(&ledger.closed_at)
+            .map_err(|_| {
+                IndexerError::StellarApiInvalidResponse(format!(
+                    "Invalid close time for ledger {}: {}",
+                    ledger.sequence, ledger.closed_at
+                ))
+            })?
+            .with_timezone(&Utc);
+
+        sqlx::query(
+            r#"
+            insert into ingestion_state (key, value, updated_at)
+            values ($1, $2, $3)
+            on conflict (key) do update set value = excluded.value, updated_at = excluded.updated_at
+            "#,
+        )
+        .bind(LATEST_LEDGER_KEY)
+        .bind(ledger.sequence.to_string())
+        .bind(closed_at)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(Some(ledger.sequence))
+    }
+}
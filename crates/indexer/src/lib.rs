@@ -6,8 +6,10 @@ pub mod config;
 pub mod db;
 pub mod error;
 pub mod horizon;
+pub mod ledger;
 pub mod models;
 pub mod telemetry;
+pub mod trades;
 
 // Legacy placeholders (kept for now; will be replaced as Phase 1.2 progresses)
 pub mod sdex;
@@ -24,6 +24,37 @@ pub struct HorizonOffer {
     pub sponsor: Option<String>,
 }
 
+/// Trade record returned by `GET /trades`
+///
+/// Assets are flattened into `base_asset_*` and `counter_asset_*` fields.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct HorizonTrade {
+    pub id: String,
+    pub paging_token: String,
+    pub ledger_close_time: String,
+    #[serde(default)]
+    pub trade_type: Option<String>,
+
+    pub base_amount: String,
+    pub base_asset_type: String,
+    pub base_asset_code: Option<String>,
+    pub base_asset_issuer: Option<String>,
+
+    pub counter_amount: String,
+    pub counter_asset_type: String,
+    pub counter_asset_code: Option<String>,
+    pub counter_asset_issuer: Option<String>,
+
+    pub base_is_seller: bool,
+}
+
+/// Ledger record returned by `GET /ledgers`
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct HorizonLedger {
+    pub sequence: u64,
+    pub closed_at: String,
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct HorizonEmbedded<T> {
     pub records: Vec<T>,
@@ -1,6 +1,8 @@
 pub mod asset;
 pub mod horizon;
 pub mod offer;
+pub mod trade;
 
 pub use asset::Asset;
 pub use offer::Offer;
+pub use trade::Trade;
@@ -121,7 +121,7 @@ impl TryFrom<HorizonOffer> for Offer {
     }
 }
 
-fn parse_asset_from_value(v: &serde_json::Value) -> Result<Asset> {
+pub(crate) fn parse_asset_from_value(v: &serde_json::Value) -> Result<Asset> {
     let asset_type = v
         .get("asset_type")
         .and_then(|x| x.as_str())
@@ -0,0 +1,206 @@
+//! Trade model for executed SDEX and liquidity pool trades
+
+use chrono::{DateTime, Utc};
+
+use super::{asset::Asset, horizon::HorizonTrade, offer::parse_asset_from_value};
+use crate::error::{IndexerError, Result};
+
+/// Normalized trade, in Horizon's base/counter orientation
+#[derive(Debug, Clone)]
+pub struct Trade {
+    pub id: String,
+    pub paging_token: String,
+    /// Operation that executed the trade
+    pub operation_id: i64,
+    /// Position of the trade among those of its operation
+    pub trade_index: i32,
+    pub ledger_sequence: u32,
+    pub ledger_close_time: DateTime<Utc>,
+    /// "orderbook" or "liquidity_pool"
+    pub trade_type: String,
+    pub base: Asset,
+    pub counter: Asset,
+    pub base_amount: String,
+    pub counter_amount: String,
+    pub base_is_seller: bool,
+}
+
+impl Trade {
+    /// Validate trade data
+    pub fn validate(&self) -> Result<()> {
+        for amount in [&self.base_amount, &self.counter_amount] {
+            let value: f64 = amount.parse().map_err(|_| IndexerError::NumericParse {
+                value: amount.clone(),
+                expected_type: "positive number".to_string(),
+            })?;
+            if value <= 0.0 {
+                return Err(IndexerError::InvalidTrade {
+                    trade_id: self.id.clone(),
+                    reason: format!("Amount must be positive: {}", amount),
+                });
+            }
+        }
+
+        if self.base == self.counter {
+            return Err(IndexerError::InvalidTrade {
+                trade_id: self.id.clone(),
+                reason: "Base and counter assets must be different".to_string(),
+            });
+        }
+
+        Ok(())
+    }
+}
+
+impl TryFrom<HorizonTrade> for Trade {
+    type Error = IndexerError;
+
+    fn try_from(trade: HorizonTrade) -> Result<Self> {
+        // Trade ids are "<operation id>-<index>"; operation ids are TOIDs,
+        // which carry the ledger sequence in their upper 32 bits
+        let (operation_id, trade_index) = trade
+            .id
+            .split_once('-')
+            .and_then(|(op, idx)| Some((op.parse::<i64>().ok()?, idx.parse::<i32>().ok()?)))
+            .filter(|(op, idx)| *op > 0 && *idx >= 0)
+            .ok_or_else(|| IndexerError::InvalidTrade {
+                trade_id: trade.id.clone(),
+                reason: "Expected an id of the form <operation id>-<index>".to_string(),
+            })?;
+
+        let ledger_close_time = DateTime::parse_from_rfc3339(&trade.ledger_close_time)
+            .map_err(|_| IndexerError::InvalidTrade {
+                trade_id: trade.id.clone(),
+                reason: format!("Invalid ledger close time: {}", trade.ledger_close_time),
+            })?
+            .with_timezone(&Utc);
+
+        let base = parse_asset_from_value(&serde_json::json!({
+            "asset_type": trade.base_asset_type,
+            "asset_code": trade.base_asset_code,
+            "asset_issuer": trade.base_asset_issuer,
+        }))?;
+        let counter = parse_asset_from_value(&serde_json::json!({
+            "asset_type": trade.counter_asset_type,
+            "asset_code": trade.counter_asset_code,
+            "asset_issuer": trade.counter_asset_issuer,
+        }))?;
+
+        let trade = Trade {
+            id: trade.id,
+            paging_token: trade.paging_token,
+            operation_id,
+            trade_index,
+            ledger_sequence: (operation_id >> 32) as u32,
+            ledger_close_time,
+            trade_type: trade.trade_type.unwrap_or_else(|| "orderbook".to_string()),
+            base,
+            counter,
+            base_amount: trade.base_amount,
+            counter_amount: trade.counter_amount,
+            base_is_seller: trade.base_is_seller,
+        };
+
+        trade.validate()?;
+        Ok(trade)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const ISSUER: &str = "GA5ZSEJYB37JRC5AVCIA5MOP4RHTM335X2KGX3IHOJAPP5RE34K4KZVN";
+
+    fn horizon_trade_json() -> serde_json::Value {
+        json!({
+            "id": "107449584845914113-0",
+            "paging_token": "107449584845914113-0",
+            "ledger_close_time": "2019-07-26T09:17:02Z",
+            "trade_type": "orderbook",
+            "base_offer_id": "4611686044197195777",
+            "base_account": ISSUER,
+            "base_amount": "4433.2000000",
+            "base_asset_type": "native",
+            "counter_offer_id": "104078276",
+            "counter_account": ISSUER,
+            "counter_amount": "443.3200000",
+            "counter_asset_type": "credit_alphanum4",
+            "counter_asset_code": "USDC",
+            "counter_asset_issuer": ISSUER,
+            "base_is_seller": true,
+            "price": { "n": "1", "d": "10" }
+        })
+    }
+
+    fn horizon_trade() -> HorizonTrade {
+        serde_json::from_value(horizon_trade_json()).unwrap()
+    }
+
+    #[test]
+    fn test_trade_from_horizon_trade() {
+        let trade = Trade::try_from(horizon_trade()).unwrap();
+
+        assert_eq!(trade.operation_id, 107449584845914113);
+        assert_eq!(trade.trade_index, 0);
+        assert_eq!(trade.ledger_sequence, 25017556);
+        assert_eq!(trade.ledger_close_time.timestamp(), 1564132622);
+        assert_eq!(trade.trade_type, "orderbook");
+        assert_eq!(trade.base, Asset::Native);
+        assert_eq!(
+            trade.counter,
+            Asset::CreditAlphanum4 {
+                asset_code: "USDC".to_string(),
+                asset_issuer: ISSUER.to_string(),
+            }
+        );
+        assert_eq!(trade.base_amount, "4433.2000000");
+        assert!(trade.base_is_seller);
+    }
+
+    #[test]
+    fn test_liquidity_pool_trade_without_accounts() {
+        let mut value = horizon_trade_json();
+        let object = value.as_object_mut().unwrap();
+        object.remove("base_account");
+        object.remove("base_offer_id");
+        object.insert("base_liquidity_pool_id".to_string(), json!("abcdef"));
+        object.insert("trade_type".to_string(), json!("liquidity_pool"));
+
+        let trade: HorizonTrade = serde_json::from_value(value).unwrap();
+        let trade = Trade::try_from(trade).unwrap();
+        assert_eq!(trade.trade_type, "liquidity_pool");
+    }
+
+    #[test]
+    fn test_trade_with_malformed_id_is_rejected() {
+        for id in ["107449584845914113", "abc-0", "107449584845914113-x"] {
+            let mut trade = horizon_trade();
+            trade.id = id.to_string();
+            assert!(matches!(
+                Trade::try_from(trade),
+                Err(IndexerError::InvalidTrade { .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn test_trade_with_zero_amount_is_rejected() {
+        let mut trade = horizon_trade();
+        trade.counter_amount = "0.0000000".to_string();
+        assert!(matches!(
+            Trade::try_from(trade),
+            Err(IndexerError::InvalidTrade { .. })
+        ));
+    }
+
+    #[test]
+    fn test_trade_of_an_asset_for_itself_is_rejected() {
+        let mut trade = horizon_trade();
+        trade.counter_asset_type = "native".to_string();
+        trade.counter_asset_code = None;
+        trade.counter_asset_issuer = None;
+        assert!(Trade::try_from(trade).is_err());
+    }
+}
@@ -0,0 +1,227 @@
+//! Trade ingestion
+//!
+//! Follows Horizon's `/trades` feed oldest first and stores every executed
+//! trade in `sdex_trades`, which feeds candles, 24h statistics and the
+//! cumulative volume of `trading_pairs`. The paging token of the last stored
+//! trade is kept in `ingestion_state`, so a restart resumes where it stopped;
+//! a fresh database starts after the newest trade on the network rather than
+//! replaying history.
+
+use sqlx::{PgPool, Postgres, Transaction};
+use tracing::{debug, error, info, warn};
+use uuid::Uuid;
+
+use crate::error::{IndexerError, Result};
+use crate::horizon::HorizonClient;
+use crate::models::{asset::Asset, horizon::HorizonTrade, trade::Trade};
+
+/// `ingestion_state` key holding the paging token of the last stored trade
+pub const TRADES_CURSOR_KEY: &str = "sdex_trades_cursor";
+
+/// Trades fetched per Horizon request
+const PAGE_SIZE: u32 = 200;
+
+/// Most pages fetched in one poll, so a backlog is worked off in steps
+const MAX_PAGES_PER_POLL: usize = 10;
+
+/// Executed trade indexer
+pub struct TradeIndexer {
+    horizon: HorizonClient,
+    pool: PgPool,
+}
+
+impl TradeIndexer {
+    pub fn new(horizon: HorizonClient, pool: PgPool) -> Self {
+        Self { horizon, pool }
+    }
+
+    /// Poll Horizon for new trades until the process stops
+    pub async fn start_indexing(&self) -> Result<()> {
+        info!("Starting SDEX trade indexing");
+
+        loop {
+            match self.index_trades().await {
+                Ok(0) => {}
+                Ok(count) => info!("Indexed {} trades", count),
+                Err(e) => error!("Error indexing trades: {}", e),
+            }
+
+            tokio::time::sleep(tokio::time::Duration::from_secs(5)).await;
+        }
+    }
+
+    /// Store the trades executed since the last call; returns how many were new
+    pub async fn index_trades(&self) -> Result<usize> {
+        let mut cursor = match self.cursor().await? {
+            Some(cursor) => cursor,
+            None => match self.start_cursor().await? {
+                Some(cursor) => cursor,
+                None => return Ok(0),
+            },
+        };
+        let mut indexed = 0;
+
+        for _ in 0..MAX_PAGES_PER_POLL {
+            let page = self
+                .horizon
+                .get_trades(Some(PAGE_SIZE), Some(&cursor))
+                .await?;
+            let Some(last) = page.last() else {
+                break;
+            };
+            let next = last.paging_token.clone();
+            let full = page.len() as u32 == PAGE_SIZE;
+            debug!("Fetched {} trades from Horizon", page.len());
+
+            indexed += self.store_page(page, &next).await?;
+            cursor = next;
+            if !full {
+                break;
+            }
+        }
+
+        Ok(indexed)
+    }
+
+    /// Store one page of trades and advance the cursor past it, atomically
+    async fn store_page(&self, page: Vec<HorizonTrade>, cursor: &str) -> Result<usize> {
+        let mut tx = self.pool.begin().await?;
+        let mut indexed = 0;
+
+        for horizon_trade in page {
+            let trade = match Trade::try_from(horizon_trade) {
+                Ok(trade) => trade,
+                Err(e) => {
+                    warn!("Failed to parse trade: {}", e);
+                    continue;
+                }
+            };
+            if insert_trade(&mut tx, &trade).await? {
+                indexed += 1;
+            }
+        }
+
+        save_cursor(&mut tx, cursor).await?;
+
+        tx.commit().await?;
+        Ok(indexed)
+    }
+
+    /// Anchor a fresh database at the newest trade on the network, so paging
+    /// forward picks up everything executed after it. Horizon's `cursor=now`
+    /// cannot serve here: it yields an empty page and hence no token to save.
+    async fn start_cursor(&self) -> Result<Option<String>> {
+        let Some(latest) = self.horizon.get_latest_trade().await? else {
+            debug!("No trades on the network yet");
+            return Ok(None);
+        };
+
+        let mut tx = self.pool.begin().await?;
+        save_cursor(&mut tx, &latest.paging_token).await?;
+        tx.commit().await?;
+
+        info!("Starting trade ingestion after {}", latest.paging_token);
+        Ok(Some(latest.paging_token))
+    }
+
+    async fn cursor(&self) -> Result<Option<String>> {
+        sqlx::query_scalar("select value from ingestion_state where key = $1")
+            .bind(TRADES_CURSOR_KEY)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(IndexerError::DatabaseQuery)
+    }
+}
+
+/// Record the paging token ingestion resumes from
+async fn save_cursor(tx: &mut Transaction<'_, Postgres>, cursor: &str) -> Result<()> {
+    sqlx::query(
+        r#"
+        insert into ingestion_state (key, value, updated_at)
+        values ($1, $2, now())
+        on conflict (key) do update set value = excluded.value, updated_at = now()
+        "#,
+    )
+    .bind(TRADES_CURSOR_KEY)
+    .bind(cursor)
+    .execute(&mut **tx)
+    .await?;
+    Ok(())
+}
+
+/// Insert a trade unless it is already stored; returns whether it was new
+async fn insert_trade(tx: &mut Transaction<'_, Postgres>, trade: &Trade) -> Result<bool> {
+    let base_id = asset_id(tx, &trade.base).await?;
+    let counter_id = asset_id(tx, &trade.counter).await?;
+
+    let result = sqlx::query(
+        r#"
+        insert into sdex_trades (
+            id, operation_id, trade_index, ledger_sequence, ledger_close_time, trade_type,
+            base_asset_id, counter_asset_id, base_amount, counter_amount, price, base_is_seller
+        )
+        values (
+            $1, $2, $3, $4, $5, $6, $7, $8,
+            $9::numeric, $10::numeric, $10::numeric / $9::numeric, $11
+        )
+        on conflict (id) do nothing
+        "#,
+    )
+    .bind(&trade.id)
+    .bind(trade.operation_id)
+    .bind(trade.trade_index)
+    .bind(trade.ledger_sequence as i64)
+    .bind(trade.ledger_close_time)
+    .bind(&trade.trade_type)
+    .bind(base_id)
+    .bind(counter_id)
+    .bind(&trade.base_amount)
+    .bind(&trade.counter_amount)
+    .bind(trade.base_is_seller)
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(result.rows_affected() == 1)
+}
+
+/// Id of an asset, inserting it if it is new
+///
+/// Looked up first because the unique constraint on `assets` does not
+/// catch duplicate native rows, whose code and issuer are null.
+async fn asset_id(tx: &mut Transaction<'_, Postgres>, asset: &Asset) -> Result<Uuid> {
+    let (asset_type, asset_code, asset_issuer) = asset.key();
+
+    let existing: Option<Uuid> = sqlx::query_scalar(
+        r#"
+        select id from assets
+        where asset_type = $1
+          and asset_code is not distinct from $2
+          and asset_issuer is not distinct from $3
+        limit 1
+        "#,
+    )
+    .bind(&asset_type)
+    .bind(&asset_code)
+    .bind(&asset_issuer)
+    .fetch_optional(&mut **tx)
+    .await?;
+    if let Some(id) = existing {
+        return Ok(id);
+    }
+
+    let id = sqlx::query_scalar(
+        r#"
+        insert into assets (asset_type, asset_code, asset_issuer)
+        values ($1, $2, $3)
+        on conflict (asset_type, asset_code, asset_issuer)
+        do update set asset_type = excluded.asset_type
+        returning id
+        "#,
+    )
+    .bind(&asset_type)
+    .bind(&asset_code)
+    .bind(&asset_issuer)
+    .fetch_one(&mut **tx)
+    .await?;
+    Ok(id)
+}
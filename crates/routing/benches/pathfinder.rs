@@ -0,0 +1,398 @@
+//! Pathfinder latency benchmarks over synthetic markets
+//!
+//! Builds a market with thousands of assets, tens of thousands of SDEX offers
+//! and hundreds of AMM pools, then times single-hop, multi-hop and split
+//! routing against it. Every scenario must stay inside the Roadmap's 500ms
+//! quote target, and within `REGRESSION_TOLERANCE` of the latency recorded in
+//! `benches/baselines.json`.
+//!
+//! Baselines are stored as multiples of a calibration search, a fixed
+//! best-amount search over a synthetic integer graph run before every sample,
+//! so they carry over between machines and do not drift with the load on the
+//! one that runs them.
+//!
+//! ```text
+//! cargo bench -p stellarroute-routing --bench pathfinder                      # check
+//! cargo bench -p stellarroute-routing --bench pathfinder -- --save-baseline   # record
+//! ```
+
+use std::collections::{BTreeMap, BinaryHeap, HashMap};
+use std::hint::black_box;
+use std::path::Path;
+use std::process::ExitCode;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+use stellarroute_routing::constraints::RouteConstraints;
+use stellarroute_routing::delta::SdexOffer;
+use stellarroute_routing::graph::{AmmPool, AssetId, LiquidityGraph, PoolType, Venue};
+use stellarroute_routing::pathfinder::Pathfinder;
+use stellarroute_routing::split::{SplitConfig, SplitRouter};
+
+const ASSETS: usize = 2_000;
+const OFFERS: usize = 20_000;
+const POOLS: usize = 400;
+const SEED: u64 = 0x5EED_2024;
+
+const WARMUP: usize = 3;
+const SAMPLES: usize = 30;
+
+/// Quote latency budget from the Roadmap
+const QUOTE_TARGET: Duration = Duration::from_millis(500);
+
+/// Slowdown against the stored baseline that counts as a regression
+const REGRESSION_TOLERANCE: f64 = 0.25;
+
+/// Size of the calibration graph
+const CALIBRATION_NODES: usize = 10_000;
+const CALIBRATION_DEGREE: usize = 8;
+
+/// Hubs most offers and pools quote against, with their value in XLM
+const HUBS: [(&str, f64); 4] = [
+    ("USDC", 10.0),
+    ("EURC", 11.0),
+    ("BTC", 600_000.0),
+    ("ETH", 30_000.0),
+];
+
+const ISSUER: &str = "GBENCHISSUERXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXX";
+
+/// xorshift64*, so markets are identical across runs and machines
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+
+    /// Uniform in `[lo, hi)`
+    fn range(&mut self, lo: f64, hi: f64) -> f64 {
+        lo + (self.next() >> 11) as f64 / (1u64 << 53) as f64 * (hi - lo)
+    }
+
+    /// Log-uniform in `[lo, hi)`
+    fn log_range(&mut self, lo: f64, hi: f64) -> f64 {
+        self.range(lo.ln(), hi.ln()).exp()
+    }
+}
+
+struct Market {
+    graph: LiquidityGraph,
+    /// Asset id and value in XLM, hubs first after XLM
+    assets: Vec<(AssetId, f64)>,
+}
+
+impl Market {
+    fn generate(seed: u64) -> Self {
+        let mut rng = Rng(seed);
+        let mut assets = vec![(AssetId::native(), 1.0)];
+        assets.extend(
+            HUBS.iter()
+                .map(|(code, value)| (AssetId::credit(code, ISSUER), *value)),
+        );
+        while assets.len() < ASSETS {
+            let code = format!("T{:04}", assets.len());
+            assets.push((
+                AssetId::credit(&code, ISSUER),
+                rng.log_range(0.001, 1_000.0),
+            ));
+        }
+        let hubs = 1 + HUBS.len();
+
+        let mut graph = LiquidityGraph::new();
+        for id in 0..OFFERS as u64 {
+            // Most books quote a long-tail asset against XLM or a hub
+            let tail = hubs + rng.below(ASSETS - hubs);
+            let other = if rng.below(10) == 0 {
+                hubs + rng.below(ASSETS - hubs)
+            } else {
+                rng.below(hubs)
+            };
+            if tail == other {
+                continue;
+            }
+            let (selling, buying) = if rng.below(2) == 0 {
+                (tail, other)
+            } else {
+                (other, tail)
+            };
+            let (selling, selling_value) = &assets[selling];
+            let (buying, buying_value) = &assets[buying];
+
+            let price = selling_value / buying_value * rng.range(1.001, 1.02);
+            let notional = rng.log_range(100.0, 100_000.0);
+            graph
+                .upsert_offer(SdexOffer {
+                    id,
+                    selling: selling.clone(),
+                    buying: buying.clone(),
+                    price_n: ((price * 1e7).round() as i64).max(1),
+                    price_d: 10_000_000,
+                    amount: (notional / selling_value * 1e7) as i128,
+                })
+                .expect("generated offer is valid");
+        }
+
+        for idx in 0..POOLS {
+            let (a, b) = if idx < 10 {
+                (rng.below(hubs), rng.below(hubs))
+            } else {
+                (hubs + rng.below(ASSETS - hubs), rng.below(hubs))
+            };
+            if a == b {
+                continue;
+            }
+            let (asset_a, value_a) = &assets[a];
+            let (asset_b, value_b) = &assets[b];
+            let depth = rng.log_range(10_000.0, 10_000_000.0);
+            graph
+                .upsert_venue(Venue::Amm(AmmPool {
+                    address: format!("CPOOL{:04}", idx),
+                    pool_type: PoolType::AmmConstProd,
+                    asset_a: asset_a.clone(),
+                    asset_b: asset_b.clone(),
+                    reserve_a: (depth / value_a * 1e7) as i128,
+                    reserve_b: (depth / value_b * 1e7) as i128,
+                    fee_bps: 30,
+                    amplification: None,
+                }))
+                .expect("generated pool is valid");
+        }
+        // A stable pool between the two dollar-like hubs
+        graph
+            .upsert_venue(Venue::Amm(AmmPool {
+                address: "CSTABLE".to_string(),
+                pool_type: PoolType::AmmStable,
+                asset_a: assets[1].0.clone(),
+                asset_b: assets[2].0.clone(),
+                reserve_a: 5_000_000 * 10_000_000,
+                reserve_b: 4_545_454 * 10_000_000,
+                fee_bps: 4,
+                amplification: Some(100),
+            }))
+            .expect("stable pool is valid");
+
+        Self { graph, assets }
+    }
+
+    /// Stroops of `asset` worth `xlm` XLM
+    fn amount(&self, idx: usize, xlm: f64) -> i128 {
+        (xlm / self.assets[idx].1 * 1e7) as i128
+    }
+}
+
+/// Latency percentiles for one scenario
+#[derive(Debug, Clone, Copy)]
+struct Measurement {
+    p50_us: u64,
+    p95_us: u64,
+    relative: Baseline,
+}
+
+/// Latency percentiles as multiples of the calibration search
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+struct Baseline {
+    p50: f64,
+    p95: f64,
+}
+
+type Case = Box<dyn Fn(usize)>;
+
+/// Best-amount search over a random integer graph, the same shape of work
+/// as the pathfinder's (hash map labels, a binary heap, 128-bit amounts)
+/// without depending on any of its code
+fn calibration() -> Case {
+    let mut rng = Rng(SEED ^ 0xCA11);
+    let adjacency: Vec<Vec<(u32, i128)>> = (0..CALIBRATION_NODES)
+        .map(|_| {
+            (0..CALIBRATION_DEGREE)
+                .map(|_| {
+                    let to = rng.below(CALIBRATION_NODES) as u32;
+                    let rate = 9_000_000 + rng.below(2_000_000) as i128;
+                    (to, rate)
+                })
+                .collect()
+        })
+        .collect();
+
+    Box::new(move |i| {
+        let source = (i % 16) as u32;
+        let mut best: HashMap<u32, i128> = HashMap::new();
+        let mut heap = BinaryHeap::new();
+        best.insert(source, 1_000_000_000_000);
+        heap.push((1_000_000_000_000i128, source));
+        while let Some((amount, node)) = heap.pop() {
+            if best.get(&node).is_some_and(|&seen| seen > amount) {
+                continue;
+            }
+            for &(to, rate) in &adjacency[node as usize] {
+                let out = amount * rate / 10_000_000 * 997 / 1_000;
+                if best.get(&to).is_none_or(|&seen| seen < out) {
+                    best.insert(to, out);
+                    heap.push((out, to));
+                }
+            }
+        }
+        black_box(best.len());
+    })
+}
+
+/// Time `case`, pairing every sample with a run of `calibration` just
+/// before it so both see the machine in the same state
+fn measure(case: &Case, calibration: &Case) -> Measurement {
+    let time = |f: &Case, i: usize| {
+        let start = Instant::now();
+        f(i);
+        start.elapsed().as_secs_f64()
+    };
+    for i in 0..WARMUP {
+        calibration(i);
+        case(i);
+    }
+    let (mut latencies, mut ratios): (Vec<f64>, Vec<f64>) = (0..SAMPLES)
+        .map(|i| {
+            let unit = time(calibration, i);
+            let latency = time(case, i);
+            (latency, latency / unit)
+        })
+        .unzip();
+    let at = |samples: &mut Vec<f64>, q: f64| {
+        samples.sort_by(f64::total_cmp);
+        samples[((samples.len() - 1) as f64 * q).round() as usize]
+    };
+    let ratio = |samples: &mut Vec<f64>, q: f64| (at(samples, q) * 1_000.0).round() / 1_000.0;
+    Measurement {
+        p50_us: (at(&mut latencies, 0.50) * 1e6) as u64,
+        p95_us: (at(&mut latencies, 0.95) * 1e6) as u64,
+        relative: Baseline {
+            p50: ratio(&mut ratios, 0.50),
+            p95: ratio(&mut ratios, 0.95),
+        },
+    }
+}
+
+fn scenarios(market: &'static Market) -> Vec<(&'static str, Case)> {
+    let pathfinder = Pathfinder::default();
+    let graph = &market.graph;
+    let xlm = &market.assets[0].0;
+    let usdc = &market.assets[1].0;
+
+    // Long-tail pairs with a route between them, for multi-hop searches
+    let mut rng = Rng(SEED ^ 0xFF);
+    let hubs = 1 + HUBS.len();
+    let mut pairs = Vec::new();
+    while pairs.len() < 16 {
+        let from = hubs + rng.below(ASSETS - hubs);
+        let to = hubs + rng.below(ASSETS - hubs);
+        let amount = market.amount(from, 50.0);
+        if from != to
+            && pathfinder
+                .find_best_route(graph, &market.assets[from].0, &market.assets[to].0, amount)
+                .is_ok_and(|route| route.hop_count() > 1)
+        {
+            pairs.push((from, to, amount));
+        }
+    }
+
+    let single = pathfinder.clone();
+    let multi = pathfinder.clone();
+    let split = SplitRouter::new(pathfinder, SplitConfig::default());
+    vec![
+        (
+            "single_hop",
+            Box::new(move |_| {
+                black_box(
+                    single
+                        .find_best_route(graph, xlm, usdc, 1_000 * 10_000_000)
+                        .unwrap(),
+                );
+            }),
+        ),
+        (
+            "multi_hop",
+            Box::new(move |i| {
+                let (from, to, amount) = pairs[i % pairs.len()];
+                let (from, to) = (&market.assets[from].0, &market.assets[to].0);
+                black_box(multi.find_best_route(graph, from, to, amount).unwrap());
+            }),
+        ),
+        (
+            "split",
+            Box::new(move |_| {
+                black_box(
+                    split
+                        .find_split_route(
+                            graph,
+                            xlm,
+                            usdc,
+                            100_000 * 10_000_000,
+                            &RouteConstraints::default(),
+                        )
+                        .unwrap(),
+                );
+            }),
+        ),
+    ]
+}
+
+fn main() -> ExitCode {
+    let save = std::env::args().any(|arg| arg == "--save-baseline");
+    let baseline_path = Path::new(env!("CARGO_MANIFEST_DIR")).join("benches/baselines.json");
+    let baselines: BTreeMap<String, Baseline> = std::fs::read_to_string(&baseline_path)
+        .ok()
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default();
+
+    let start = Instant::now();
+    let market: &'static Market = Box::leak(Box::new(Market::generate(SEED)));
+    println!(
+        "market: {} assets, {} venues, built in {:?}",
+        market.graph.asset_count(),
+        market.graph.venue_count(),
+        start.elapsed()
+    );
+    let calibration = calibration();
+
+    let mut results = BTreeMap::new();
+    let mut failed = false;
+    for (name, case) in scenarios(market) {
+        let m = measure(&case, &calibration);
+        let mut verdict = "ok";
+        if Duration::from_micros(m.p95_us) > QUOTE_TARGET {
+            verdict = "over quote target";
+            failed = true;
+        } else if let Some(base) = baselines.get(name) {
+            if m.relative.p50 > base.p50 * (1.0 + REGRESSION_TOLERANCE) {
+                verdict = "regressed";
+                failed = true;
+            }
+        }
+        let base = baselines
+            .get(name)
+            .map_or_else(|| "-".to_string(), |b| format!("{:.3}x", b.p50));
+        println!(
+            "{:<12} p50 {:>8}us  p95 {:>8}us  p50 {:>7.3}x  baseline {:>7}  {}",
+            name, m.p50_us, m.p95_us, m.relative.p50, base, verdict
+        );
+        results.insert(name.to_string(), m.relative);
+    }
+
+    if save {
+        let json = serde_json::to_string_pretty(&results).expect("baselines serialize");
+        std::fs::write(&baseline_path, json + "\n").expect("baseline file is writable");
+        println!("saved baselines to {}", baseline_path.display());
+        return ExitCode::SUCCESS;
+    }
+    if failed {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
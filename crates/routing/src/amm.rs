@@ -0,0 +1,356 @@
+//! AMM pool math
+//!
+//! Exact integer implementations of the swap curves used by Soroban pools.
+//! Off-chain quotes must agree with the on-chain adapters to the stroop, so
+//! everything here is `i128` arithmetic with the same rounding as the contracts:
+//! outputs round down, required inputs round up.
+
+use crate::amount::{mul_div_ceil, mul_div_floor, BPS};
+use crate::error::{Result, RoutingError};
+
+/// Newton iteration cap for the StableSwap invariant
+const MAX_ITERATIONS: usize = 255;
+
+/// Number of coins in a StableSwap pool
+const N_COINS: i128 = 2;
+
+fn check_inputs(amount: i128, reserve_in: i128, reserve_out: i128, fee_bps: u32) -> Result<()> {
+    if amount < 0 {
+        return Err(RoutingError::InvalidAmount(format!(
+            "amount must not be negative, got {}",
+            amount
+        )));
+    }
+    if reserve_in <= 0 || reserve_out <= 0 {
+        return Err(RoutingError::InsufficientLiquidity(
+            "pool has no reserves".to_string(),
+        ));
+    }
+    if fee_bps as i128 >= BPS {
+        return Err(RoutingError::InvalidAmount(format!(
+            "fee of {} bps is not below 100%",
+            fee_bps
+        )));
+    }
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// Constant product (x * y = k)
+// ---------------------------------------------------------------------------
+
+/// Output for `amount_in`, matching `ConstantProductAdapter::adapter_quote`
+///
+/// `dy = (y * dx * (1 - fee)) / (x + dx * (1 - fee))`. With `fee_bps = 30` this
+/// is exactly the adapter's `997 / 1000` formula.
+pub fn constant_product_out(
+    amount_in: i128,
+    reserve_in: i128,
+    reserve_out: i128,
+    fee_bps: u32,
+) -> Result<i128> {
+    check_inputs(amount_in, reserve_in, reserve_out, fee_bps)?;
+
+    let fee_multiplier = BPS - fee_bps as i128;
+    let amount_with_fee = amount_in
+        .checked_mul(fee_multiplier)
+        .ok_or(RoutingError::Overflow)?;
+    let denominator = reserve_in
+        .checked_mul(BPS)
+        .and_then(|x| x.checked_add(amount_with_fee))
+        .ok_or(RoutingError::Overflow)?;
+    mul_div_floor(amount_with_fee, reserve_out, denominator)
+}
+
+/// Smallest input for which [`constant_product_out`] yields at least `amount_out`
+pub fn constant_product_in(
+    amount_out: i128,
+    reserve_in: i128,
+    reserve_out: i128,
+    fee_bps: u32,
+) -> Result<i128> {
+    check_inputs(amount_out, reserve_in, reserve_out, fee_bps)?;
+    if amount_out >= reserve_out {
+        return Err(RoutingError::InsufficientLiquidity(format!(
+            "pool holds {} but {} was requested",
+            reserve_out, amount_out
+        )));
+    }
+    if amount_out == 0 {
+        return Ok(0);
+    }
+
+    // dx = x * dy / ((y - dy) * (1 - fee)), rounded up
+    let fee_multiplier = BPS - fee_bps as i128;
+    let numerator = reserve_in.checked_mul(BPS).ok_or(RoutingError::Overflow)?;
+    let denominator = (reserve_out - amount_out)
+        .checked_mul(fee_multiplier)
+        .ok_or(RoutingError::Overflow)?;
+    mul_div_ceil(numerator, amount_out, denominator)
+}
+
+// ---------------------------------------------------------------------------
+// StableSwap (Curve invariant, two coins)
+// ---------------------------------------------------------------------------
+
+/// StableSwap invariant `D` for balances `x`, `y` and amplification `A`
+///
+/// Solves `A·n^n·(x + y) + D = A·n^n·D + D^(n+1) / (n^n·x·y)` by Newton's method,
+/// exactly as Curve's `get_D`.
+pub fn stable_invariant(x: i128, y: i128, amplification: u32) -> Result<i128> {
+    let sum = x.checked_add(y).ok_or(RoutingError::Overflow)?;
+    if sum == 0 {
+        return Ok(0);
+    }
+    let ann = amplification as i128 * N_COINS * N_COINS;
+
+    let mut d = sum;
+    for _ in 0..MAX_ITERATIONS {
+        let mut d_p = d;
+        for balance in [x, y] {
+            d_p = mul_div_floor(d_p, d, balance * N_COINS)?;
+        }
+        let prev = d;
+        let numerator = ann
+            .checked_mul(sum)
+            .and_then(|a| a.checked_add(d_p.checked_mul(N_COINS)?))
+            .ok_or(RoutingError::Overflow)?;
+        let denominator = (ann - 1)
+            .checked_mul(d)
+            .and_then(|a| a.checked_add(d_p.checked_mul(N_COINS + 1)?))
+            .ok_or(RoutingError::Overflow)?;
+        d = mul_div_floor(numerator, d, denominator)?;
+        if (d - prev).abs() <= 1 {
+            return Ok(d);
+        }
+    }
+    Err(RoutingError::InvalidAmount(
+        "StableSwap invariant did not converge".to_string(),
+    ))
+}
+
+/// Balance of the other coin that keeps the invariant at `d` when one coin's
+/// balance is `x_new` (Curve's `get_y`)
+fn stable_other_balance(x_new: i128, d: i128, amplification: u32) -> Result<i128> {
+    if x_new <= 0 {
+        return Err(RoutingError::InsufficientLiquidity(
+            "StableSwap balance must stay positive".to_string(),
+        ));
+    }
+    let ann = amplification as i128 * N_COINS * N_COINS;
+
+    let mut c = mul_div_floor(d, d, x_new * N_COINS)?;
+    c = mul_div_floor(c, d, ann * N_COINS)?;
+    let b = x_new + d / ann;
+
+    let mut y = d;
+    for _ in 0..MAX_ITERATIONS {
+        let prev = y;
+        let numerator = y
+            .checked_mul(y)
+            .and_then(|v| v.checked_add(c))
+            .ok_or(RoutingError::Overflow)?;
+        let denominator = 2 * y + b - d;
+        if denominator <= 0 {
+            return Err(RoutingError::InsufficientLiquidity(
+                "StableSwap curve has no solution for this trade".to_string(),
+            ));
+        }
+        y = numerator / denominator;
+        if (y - prev).abs() <= 1 {
+            return Ok(y);
+        }
+    }
+    Err(RoutingError::InvalidAmount(
+        "StableSwap balance did not converge".to_string(),
+    ))
+}
+
+/// Output for `amount_in` on a StableSwap pool
+///
+/// Like Curve, one stroop is held back from the curve output for rounding
+/// safety and the fee is taken from the output.
+pub fn stable_swap_out(
+    amount_in: i128,
+    reserve_in: i128,
+    reserve_out: i128,
+    amplification: u32,
+    fee_bps: u32,
+) -> Result<i128> {
+    check_inputs(amount_in, reserve_in, reserve_out, fee_bps)?;
+    check_amplification(amplification)?;
+    if amount_in == 0 {
+        return Ok(0);
+    }
+
+    let d = stable_invariant(reserve_in, reserve_out, amplification)?;
+    let x_new = reserve_in
+        .checked_add(amount_in)
+        .ok_or(RoutingError::Overflow)?;
+    let y_new = stable_other_balance(x_new, d, amplification)?;
+
+    let gross = (reserve_out - y_new - 1).max(0);
+    let fee = mul_div_floor(gross, fee_bps as i128, BPS)?;
+    Ok(gross - fee)
+}
+
+/// Smallest input for which [`stable_swap_out`] yields at least `amount_out`
+pub fn stable_swap_in(
+    amount_out: i128,
+    reserve_in: i128,
+    reserve_out: i128,
+    amplification: u32,
+    fee_bps: u32,
+) -> Result<i128> {
+    check_inputs(amount_out, reserve_in, reserve_out, fee_bps)?;
+    check_amplification(amplification)?;
+    if amount_out == 0 {
+        return Ok(0);
+    }
+
+    // Undo the output fee, then the rounding stroop
+    let fee_multiplier = BPS - fee_bps as i128;
+    let gross = mul_div_ceil(amount_out, BPS, fee_multiplier)?;
+    let y_new = reserve_out - gross - 1;
+    if y_new <= 0 {
+        return Err(RoutingError::InsufficientLiquidity(format!(
+            "pool holds {} but {} was requested",
+            reserve_out, amount_out
+        )));
+    }
+
+    let d = stable_invariant(reserve_in, reserve_out, amplification)?;
+    let x_new = stable_other_balance(y_new, d, amplification)?;
+    let mut amount_in = (x_new - reserve_in + 1).max(1);
+
+    // Newton's method lands within a stroop or two of the exact answer; settle
+    // on the smallest input the forward calculation accepts.
+    let out = |dx| stable_swap_out(dx, reserve_in, reserve_out, amplification, fee_bps);
+    for _ in 0..MAX_ITERATIONS {
+        if out(amount_in)? >= amount_out {
+            break;
+        }
+        amount_in += 1;
+    }
+    for _ in 0..MAX_ITERATIONS {
+        if amount_in <= 1 || out(amount_in - 1)? < amount_out {
+            break;
+        }
+        amount_in -= 1;
+    }
+    if out(amount_in)? < amount_out {
+        return Err(RoutingError::InsufficientLiquidity(format!(
+            "pool cannot deliver {}",
+            amount_out
+        )));
+    }
+    Ok(amount_in)
+}
+
+fn check_amplification(amplification: u32) -> Result<()> {
+    if amplification == 0 {
+        return Err(RoutingError::InvalidAmount(
+            "StableSwap amplification must be positive".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The adapter's formula, verbatim
+    fn adapter_quote(amount_in: i128, res_in: i128, res_out: i128) -> i128 {
+        let fee_multiplier = 997;
+        let amount_with_fee = amount_in * fee_multiplier;
+        let numerator = amount_with_fee * res_out;
+        let denominator = (res_in * 1000) + amount_with_fee;
+        numerator / denominator
+    }
+
+    #[test]
+    fn test_constant_product_matches_adapter_to_the_stroop() {
+        let reserves = [
+            (1_000_000, 2_000_000),
+            (10_000_000_000, 3_333_333_333),
+            (7, 1_000_000_000_000),
+            (123_456_789_012, 98_765_432_109),
+        ];
+        for (res_in, res_out) in reserves {
+            for amount_in in [1, 13, 999, 1_000_000, 54_321_987, 10_000_000_000] {
+                assert_eq!(
+                    constant_product_out(amount_in, res_in, res_out, 30).unwrap(),
+                    adapter_quote(amount_in, res_in, res_out),
+                    "amount_in={} reserves=({}, {})",
+                    amount_in,
+                    res_in,
+                    res_out
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_constant_product_in_is_minimal_input() {
+        let (res_in, res_out) = (10_000_000_000, 3_333_333_333);
+        for amount_out in [1, 77, 1_000_000, 500_000_000, 3_000_000_000] {
+            let amount_in = constant_product_in(amount_out, res_in, res_out, 30).unwrap();
+            assert!(constant_product_out(amount_in, res_in, res_out, 30).unwrap() >= amount_out);
+            assert!(constant_product_out(amount_in - 1, res_in, res_out, 30).unwrap() < amount_out);
+        }
+        assert!(matches!(
+            constant_product_in(res_out, res_in, res_out, 30),
+            Err(RoutingError::InsufficientLiquidity(_))
+        ));
+    }
+
+    #[test]
+    fn test_zero_fee_and_zero_amount() {
+        assert_eq!(constant_product_out(0, 100, 100, 30).unwrap(), 0);
+        // 50 into 100/100 with no fee: 100 * 50 / 150
+        assert_eq!(constant_product_out(50, 100, 100, 0).unwrap(), 33);
+        assert!(constant_product_out(1, 0, 100, 30).is_err());
+        assert!(constant_product_out(1, 100, 100, 10_000).is_err());
+    }
+
+    #[test]
+    fn test_stable_invariant_of_balanced_pool_is_sum() {
+        assert_eq!(
+            stable_invariant(1_000_000, 1_000_000, 100).unwrap(),
+            2_000_000
+        );
+        assert_eq!(stable_invariant(0, 0, 100).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_stable_swap_beats_constant_product_near_peg() {
+        let reserve = 1_000_000_000_000;
+        let amount_in = 10_000_000_000;
+        let stable = stable_swap_out(amount_in, reserve, reserve, 100, 4).unwrap();
+        let cp = constant_product_out(amount_in, reserve, reserve, 4).unwrap();
+
+        assert!(stable > cp);
+        // A balanced, highly amplified pool trades almost 1:1
+        assert!(stable <= amount_in);
+        assert!(stable > amount_in * 9_990 / 10_000);
+    }
+
+    #[test]
+    fn test_stable_swap_in_round_trips() {
+        let (res_in, res_out) = (800_000_000_000, 1_200_000_000_000);
+        for amount_out in [1, 1_000, 5_000_000_000, 400_000_000_000] {
+            let amount_in = stable_swap_in(amount_out, res_in, res_out, 200, 4).unwrap();
+            assert!(stable_swap_out(amount_in, res_in, res_out, 200, 4).unwrap() >= amount_out);
+            assert!(stable_swap_out(amount_in - 1, res_in, res_out, 200, 4).unwrap() < amount_out);
+        }
+    }
+
+    #[test]
+    fn test_stable_swap_requires_amplification() {
+        assert!(matches!(
+            stable_swap_out(1, 100, 100, 0, 4),
+            Err(RoutingError::InvalidAmount(_))
+        ));
+    }
+}
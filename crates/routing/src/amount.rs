@@ -0,0 +1,413 @@
+//! Fixed-point amounts and exact prices
+//!
+//! Stellar amounts have 7 decimal places and are carried on-chain as integer
+//! stroops. [`Amount`] wraps the stroop count, and [`Price`] keeps prices as
+//! reduced `n/d` rationals like Horizon's `price_r`, so quote math never goes
+//! through floating point. Both parse from and format to Horizon-style
+//! decimal strings.
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::cmp::Ordering;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::error::{Result, RoutingError};
+
+/// Decimal places in a Stellar amount
+pub const DECIMALS: u32 = 7;
+
+/// Stroops in one whole unit of an asset
+pub const STROOPS_PER_UNIT: i128 = 10_000_000;
+
+/// Basis points in one whole, as fees, slippage and price impact are counted
+pub const BPS: i128 = 10_000;
+
+/// Most fractional digits accepted when parsing a price
+const MAX_PRICE_DECIMALS: usize = 18;
+
+/// An asset amount with 7 decimal places, stored as stroops
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Amount(i128);
+
+impl Amount {
+    pub const ZERO: Amount = Amount(0);
+    pub const ONE: Amount = Amount(STROOPS_PER_UNIT);
+
+    pub const fn from_stroops(stroops: i128) -> Self {
+        Self(stroops)
+    }
+
+    pub const fn stroops(self) -> i128 {
+        self.0
+    }
+
+    pub fn is_positive(self) -> bool {
+        self.0 > 0
+    }
+
+    pub fn checked_add(self, other: Amount) -> Result<Amount> {
+        self.0
+            .checked_add(other.0)
+            .map(Amount)
+            .ok_or(RoutingError::Overflow)
+    }
+
+    pub fn checked_sub(self, other: Amount) -> Result<Amount> {
+        self.0
+            .checked_sub(other.0)
+            .map(Amount)
+            .ok_or(RoutingError::Overflow)
+    }
+
+    /// `self * price`, rounded down to the stroop
+    pub fn mul_price_floor(self, price: Price) -> Result<Amount> {
+        mul_div_floor(self.0, price.n, price.d).map(Amount)
+    }
+
+    /// `self * price`, rounded up to the stroop
+    pub fn mul_price_ceil(self, price: Price) -> Result<Amount> {
+        mul_div_ceil(self.0, price.n, price.d).map(Amount)
+    }
+}
+
+impl fmt::Display for Amount {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let sign = if self.0 < 0 { "-" } else { "" };
+        let abs = self.0.unsigned_abs();
+        let unit = STROOPS_PER_UNIT as u128;
+        write!(f, "{}{}.{:07}", sign, abs / unit, abs % unit)
+    }
+}
+
+impl FromStr for Amount {
+    type Err = RoutingError;
+
+    /// Parse a decimal string such as `"12.5"` or `"-0.0000001"`
+    ///
+    /// Digits beyond the seventh decimal place must be zero, so database
+    /// values with a wider scale still parse exactly.
+    fn from_str(s: &str) -> Result<Self> {
+        let invalid = || RoutingError::InvalidAmount(format!("invalid amount: {:?}", s));
+        let (negative, int_part, frac_part) = split_decimal(s).ok_or_else(invalid)?;
+
+        let (kept, dropped) = frac_part.split_at(frac_part.len().min(DECIMALS as usize));
+        if dropped.bytes().any(|b| b != b'0') {
+            return Err(RoutingError::InvalidAmount(format!(
+                "amount {:?} has more than {} decimal places",
+                s, DECIMALS
+            )));
+        }
+
+        let whole = parse_digits(int_part)?;
+        let frac = parse_digits(kept)? * 10i128.pow(DECIMALS - kept.len() as u32);
+        let stroops = whole
+            .checked_mul(STROOPS_PER_UNIT)
+            .and_then(|x| x.checked_add(frac))
+            .ok_or(RoutingError::Overflow)?;
+        Ok(Amount(if negative { -stroops } else { stroops }))
+    }
+}
+
+impl Serialize for Amount {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Amount {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+/// A non-negative price as a reduced `n/d` rational
+///
+/// Always stored in lowest terms with a positive denominator, so derived
+/// equality is exact equality.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "RawPrice")]
+pub struct Price {
+    n: i128,
+    d: i128,
+}
+
+/// Wire form of [`Price`], checked and reduced through [`Price::new`]
+#[derive(Deserialize)]
+struct RawPrice {
+    n: i128,
+    d: i128,
+}
+
+impl TryFrom<RawPrice> for Price {
+    type Error = RoutingError;
+
+    fn try_from(raw: RawPrice) -> Result<Self> {
+        Self::new(raw.n, raw.d)
+    }
+}
+
+impl Price {
+    pub fn new(n: i128, d: i128) -> Result<Self> {
+        if n < 0 || d <= 0 {
+            return Err(RoutingError::InvalidAmount(format!(
+                "invalid price {}/{}",
+                n, d
+            )));
+        }
+        let g = gcd(n, d);
+        Ok(Self { n: n / g, d: d / g })
+    }
+
+    pub fn numerator(&self) -> i128 {
+        self.n
+    }
+
+    pub fn denominator(&self) -> i128 {
+        self.d
+    }
+
+    /// `d/n`, e.g. USDC per XLM from XLM per USDC
+    pub fn invert(&self) -> Result<Self> {
+        Self::new(self.d, self.n)
+    }
+}
+
+impl Ord for Price {
+    /// Exact comparison by continued fractions, which cannot overflow
+    fn cmp(&self, other: &Self) -> Ordering {
+        let (mut a, mut b, mut c, mut d) = (self.n, self.d, other.n, other.d);
+        let mut flipped = false;
+        loop {
+            let (q1, r1) = (a / b, a % b);
+            let (q2, r2) = (c / d, c % d);
+            let ord = match q1.cmp(&q2) {
+                Ordering::Equal => match (r1 == 0, r2 == 0) {
+                    (true, true) => Ordering::Equal,
+                    (true, false) => Ordering::Less,
+                    (false, true) => Ordering::Greater,
+                    (false, false) => {
+                        // r1/b vs r2/d has the opposite order of b/r1 vs d/r2
+                        (a, b, c, d) = (b, r1, d, r2);
+                        flipped = !flipped;
+                        continue;
+                    }
+                },
+                ord => ord,
+            };
+            return if flipped { ord.reverse() } else { ord };
+        }
+    }
+}
+
+impl PartialOrd for Price {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl fmt::Display for Price {
+    /// Seven decimal places, rounded down
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let rem = self.n % self.d;
+        let frac = mul_div_floor(rem, STROOPS_PER_UNIT, self.d).unwrap_or_else(|_| {
+            // Only reachable for denominators above ~10^31; scaling both
+            // terms down first costs far less than a stroop of precision
+            let scale = self.d / 10i128.pow(20);
+            (rem / scale) * STROOPS_PER_UNIT / (self.d / scale)
+        });
+        write!(f, "{}.{:07}", self.n / self.d, frac)
+    }
+}
+
+impl FromStr for Price {
+    type Err = RoutingError;
+
+    /// Parse a decimal string exactly, e.g. `"0.1428571"` or a database
+    /// `numeric` rendered with extra scale
+    fn from_str(s: &str) -> Result<Self> {
+        let invalid = || RoutingError::InvalidAmount(format!("invalid price: {:?}", s));
+        let (negative, int_part, frac_part) = split_decimal(s).ok_or_else(invalid)?;
+        let frac_part = frac_part.trim_end_matches('0');
+        if negative || frac_part.len() > MAX_PRICE_DECIMALS {
+            return Err(invalid());
+        }
+
+        let scale = 10i128.pow(frac_part.len() as u32);
+        let frac = parse_digits(frac_part)?;
+        let n = parse_digits(int_part)?
+            .checked_mul(scale)
+            .and_then(|x| x.checked_add(frac))
+            .ok_or(RoutingError::Overflow)?;
+        Self::new(n, scale)
+    }
+}
+
+/// Split `[-]int[.frac]` into its sign and digit runs
+fn split_decimal(s: &str) -> Option<(bool, &str, &str)> {
+    let (negative, unsigned) = match s.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, s),
+    };
+    let (int_part, frac_part) = unsigned.split_once('.').unwrap_or((unsigned, ""));
+    let all_digits = |p: &str| p.bytes().all(|b| b.is_ascii_digit());
+    if int_part.is_empty() || !all_digits(int_part) || !all_digits(frac_part) {
+        return None;
+    }
+    Some((negative, int_part, frac_part))
+}
+
+fn parse_digits(digits: &str) -> Result<i128> {
+    if digits.is_empty() {
+        return Ok(0);
+    }
+    digits.parse().map_err(|_| RoutingError::Overflow)
+}
+
+fn gcd(mut a: i128, mut b: i128) -> i128 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a.max(1)
+}
+
+/// `a * b / c`, rounded toward negative infinity
+pub(crate) fn mul_div_floor(a: i128, b: i128, c: i128) -> Result<i128> {
+    let product = a.checked_mul(b).ok_or(RoutingError::Overflow)?;
+    let quotient = product.checked_div(c).ok_or(RoutingError::Overflow)?;
+    // `/` truncates toward zero, which rounds a negative quotient up
+    if product % c != 0 && (product < 0) != (c < 0) {
+        Ok(quotient - 1)
+    } else {
+        Ok(quotient)
+    }
+}
+
+/// `a * b / c`, rounded toward positive infinity
+pub(crate) fn mul_div_ceil(a: i128, b: i128, c: i128) -> Result<i128> {
+    let product = a.checked_mul(b).ok_or(RoutingError::Overflow)?;
+    let quotient = product.checked_div(c).ok_or(RoutingError::Overflow)?;
+    if product % c != 0 && (product < 0) == (c < 0) {
+        Ok(quotient + 1)
+    } else {
+        Ok(quotient)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_amount_parse_and_format() {
+        let cases = [
+            ("1", 10_000_000, "1.0000000"),
+            ("0.1", 1_000_000, "0.1000000"),
+            ("123.4567891", 1_234_567_891, "123.4567891"),
+            ("0.0000001", 1, "0.0000001"),
+            ("-2.5", -25_000_000, "-2.5000000"),
+            ("12.34000000000000", 123_400_000, "12.3400000"),
+        ];
+        for (input, stroops, formatted) in cases {
+            let amount: Amount = input.parse().unwrap();
+            assert_eq!(amount.stroops(), stroops, "{input}");
+            assert_eq!(amount.to_string(), formatted);
+        }
+
+        for bad in ["", ".5", "abc", "1.00000001", "1e7", "--1"] {
+            assert!(bad.parse::<Amount>().is_err(), "{bad}");
+        }
+    }
+
+    #[test]
+    fn test_amount_checked_arithmetic() {
+        let a = Amount::from_stroops(i128::MAX);
+        assert!(matches!(
+            a.checked_add(Amount::ONE),
+            Err(RoutingError::Overflow)
+        ));
+        assert_eq!(
+            Amount::ONE.checked_sub(Amount::from_stroops(1)).unwrap(),
+            Amount::from_stroops(9_999_999)
+        );
+    }
+
+    #[test]
+    fn test_amount_serializes_as_string() {
+        let json = serde_json::to_string(&Amount::from_stroops(15)).unwrap();
+        assert_eq!(json, "\"0.0000015\"");
+        let back: Amount = serde_json::from_str(&json).unwrap();
+        assert_eq!(back.stroops(), 15);
+    }
+
+    #[test]
+    fn test_price_is_exact_rational() {
+        let third = Price::new(2, 6).unwrap();
+        let huge = Price::new(1, i128::MAX).unwrap();
+        assert_eq!(huge.to_string(), "0.0000000");
+        assert_eq!((third.numerator(), third.denominator()), (1, 3));
+        assert_eq!(third.to_string(), "0.3333333");
+        assert_eq!(third.invert().unwrap(), Price::new(3, 1).unwrap());
+
+        // 0.1 + 0.2 style inputs stay exact
+        let p: Price = "0.30000000000000".parse().unwrap();
+        assert_eq!(p, Price::new(3, 10).unwrap());
+
+        assert!(Price::new(1, 0).is_err());
+        assert!(Price::new(-1, 2).is_err());
+        assert!(Price::new(0, 5).unwrap().invert().is_err());
+        assert!("-0.5".parse::<Price>().is_err());
+    }
+
+    #[test]
+    fn test_price_ordering_is_exact() {
+        let a = Price::new(1_000_000_000_000_000_001, 1_000_000_000_000_000_000).unwrap();
+        let b = Price::new(1_000_000_000_000_000_000, 999_999_999_999_999_999).unwrap();
+        assert!(a < b);
+        assert!(Price::new(1, 3).unwrap() < Price::new(1, 2).unwrap());
+        assert!(Price::new(7, 2).unwrap() > Price::new(10, 3).unwrap());
+        assert_eq!(
+            Price::new(22, 7).unwrap().cmp(&Price::new(44, 14).unwrap()),
+            Ordering::Equal
+        );
+    }
+
+    #[test]
+    fn test_amount_times_price_rounding() {
+        let amount: Amount = "10".parse().unwrap();
+        let price = Price::new(1, 3).unwrap();
+        assert_eq!(
+            amount.mul_price_floor(price).unwrap().to_string(),
+            "3.3333333"
+        );
+        assert_eq!(
+            amount.mul_price_ceil(price).unwrap().to_string(),
+            "3.3333334"
+        );
+
+        let debt: Amount = "-10".parse().unwrap();
+        assert_eq!(
+            debt.mul_price_floor(price).unwrap().to_string(),
+            "-3.3333334"
+        );
+        assert_eq!(
+            debt.mul_price_ceil(price).unwrap().to_string(),
+            "-3.3333333"
+        );
+        assert_eq!(mul_div_floor(7, 1, -2).unwrap(), -4);
+        assert_eq!(mul_div_ceil(7, 1, -2).unwrap(), -3);
+        assert_eq!(mul_div_floor(-6, 1, 3).unwrap(), -2);
+        assert_eq!(mul_div_ceil(-6, 1, 3).unwrap(), -2);
+    }
+
+    #[test]
+    fn test_price_deserializes_through_new() {
+        let p: Price = serde_json::from_str(r#"{"n":4,"d":6}"#).unwrap();
+        assert_eq!(p, Price::new(2, 3).unwrap());
+        assert_eq!(serde_json::to_string(&p).unwrap(), r#"{"n":2,"d":3}"#);
+
+        for bad in [r#"{"n":1,"d":0}"#, r#"{"n":-1,"d":2}"#, r#"{"n":1,"d":-2}"#] {
+            assert!(serde_json::from_str::<Price>(bad).is_err(), "{bad}");
+        }
+    }
+}
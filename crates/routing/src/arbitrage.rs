@@ -0,0 +1,376 @@
+//! Arbitrage cycle detection
+//!
+//! Each edge is weighted `-ln(rate)`, where `rate` is its spot output per unit
+//! of input after fees. A cycle whose rates multiply to more than one is then
+//! a negative cycle, which Bellman-Ford finds. Log weights are only used to
+//! find candidates: every cycle is re-simulated with exact integer amounts
+//! before it is reported, and its profit and sizes come from those
+//! simulations.
+//!
+//! Large cycles usually mean a venue's data is stale rather than a real
+//! opportunity, so the venues involved can be kept out of quotes.
+
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+use crate::error::Result;
+use crate::graph::{AssetId, EdgeRef, LiquidityGraph, VenueId};
+use crate::pathfinder::{simulate_route, Route, RouteHop};
+
+/// Default marginal profit above which a cycle is treated as stale data
+pub const DEFAULT_STALE_THRESHOLD_BPS: u32 = 500;
+
+/// Largest trade size probed when sizing a cycle, in stroops
+const MAX_PROBE: i128 = 1 << 62;
+
+/// Arbitrage detection configuration
+#[derive(Debug, Clone)]
+pub struct ArbitrageConfig {
+    /// Marginal profit at or above which a cycle is taken as evidence of
+    /// inconsistent data rather than a genuine opportunity
+    pub stale_threshold_bps: u32,
+}
+
+impl Default for ArbitrageConfig {
+    fn default() -> Self {
+        Self {
+            stale_threshold_bps: DEFAULT_STALE_THRESHOLD_BPS,
+        }
+    }
+}
+
+/// A profitable cycle
+///
+/// Amounts are in stroops of the cycle's start asset.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Arbitrage {
+    /// The cycle executed at `optimal_size`; starts and ends at the same asset
+    pub route: Route,
+    /// Profit of an infinitesimal trade around the cycle, in basis points
+    pub marginal_profit_bps: u32,
+    /// Input that maximises profit
+    pub optimal_size: i128,
+    /// Profit at `optimal_size`
+    pub expected_profit: i128,
+    /// Largest input that still returns more than it costs
+    pub max_profitable_size: i128,
+}
+
+impl Arbitrage {
+    pub fn start_asset(&self) -> &AssetId {
+        &self.route.hops[0].from
+    }
+
+    pub fn venues(&self) -> impl Iterator<Item = &VenueId> {
+        self.route.hops.iter().map(|h| &h.venue)
+    }
+}
+
+/// Finds profitable cycles in a [`LiquidityGraph`]
+#[derive(Debug, Clone, Default)]
+pub struct ArbitrageDetector {
+    config: ArbitrageConfig,
+}
+
+struct LogEdge<'a> {
+    from: usize,
+    to: usize,
+    weight: f64,
+    edge: EdgeRef<'a>,
+}
+
+impl ArbitrageDetector {
+    pub fn new(config: ArbitrageConfig) -> Self {
+        Self { config }
+    }
+
+    pub fn config(&self) -> &ArbitrageConfig {
+        &self.config
+    }
+
+    /// All profitable cycles Bellman-Ford can reach, most profitable first
+    pub fn find_cycles(&self, graph: &LiquidityGraph) -> Result<Vec<Arbitrage>> {
+        let mut assets: Vec<&AssetId> = graph.assets().collect();
+        assets.sort();
+        let index: HashMap<&AssetId, usize> =
+            assets.iter().enumerate().map(|(i, a)| (*a, i)).collect();
+
+        let mut edges = Vec::new();
+        for asset in &assets {
+            for edge in graph.edges_from(asset) {
+                let Ok(spot) = edge.spot_price() else {
+                    continue;
+                };
+                if spot.numerator() == 0 {
+                    continue;
+                }
+                let after_fee = 1.0 - edge.fee_bps() as f64 / 10_000.0;
+                let rate = spot.numerator() as f64 / spot.denominator() as f64 * after_fee;
+                edges.push(LogEdge {
+                    from: index[edge.from],
+                    to: index[edge.to],
+                    weight: -rate.ln(),
+                    edge,
+                });
+            }
+        }
+
+        let mut arbitrages = Vec::new();
+        for cycle in negative_cycles(assets.len(), &edges) {
+            let hops: Vec<&LogEdge> = cycle.iter().map(|&i| &edges[i]).collect();
+            let weight: f64 = hops.iter().map(|e| e.weight).sum();
+            if let Some(arbitrage) = size_cycle(graph, &hops, weight)? {
+                arbitrages.push(arbitrage);
+            }
+        }
+        arbitrages.sort_by_key(|a| Reverse(a.marginal_profit_bps));
+        Ok(arbitrages)
+    }
+
+    /// Venues in cycles profitable enough to indicate stale data
+    ///
+    /// Suitable for [`crate::constraints::RouteConstraints::denied_venues`].
+    pub fn suspect_venues(&self, graph: &LiquidityGraph) -> Result<HashSet<VenueId>> {
+        Ok(self
+            .find_cycles(graph)?
+            .iter()
+            .filter(|a| a.marginal_profit_bps >= self.config.stale_threshold_bps)
+            .flat_map(|a| a.venues().cloned())
+            .collect())
+    }
+}
+
+/// Distinct negative cycles as edge indices in path order
+fn negative_cycles(node_count: usize, edges: &[LogEdge<'_>]) -> Vec<Vec<usize>> {
+    // Ignore relaxations within float noise of zero
+    const EPSILON: f64 = 1e-12;
+
+    // Starting every node at zero acts as a virtual source joined to all
+    let mut dist = vec![0.0f64; node_count];
+    let mut pred: Vec<Option<usize>> = vec![None; node_count];
+    // Nodes still improving after `node_count` rounds sit on or behind a
+    // negative cycle
+    let mut relaxed = Vec::new();
+    for _ in 0..node_count {
+        relaxed.clear();
+        for (idx, e) in edges.iter().enumerate() {
+            if dist[e.from] + e.weight < dist[e.to] - EPSILON {
+                dist[e.to] = dist[e.from] + e.weight;
+                pred[e.to] = Some(idx);
+                relaxed.push(e.to);
+            }
+        }
+        if relaxed.is_empty() {
+            return Vec::new();
+        }
+    }
+
+    let mut seen: BTreeSet<Vec<usize>> = BTreeSet::new();
+    let mut cycles = Vec::new();
+    for &node in &relaxed {
+        // Stepping back `node_count` times is guaranteed to land on a cycle
+        let mut cursor = node;
+        for _ in 0..node_count {
+            match pred[cursor] {
+                Some(e) => cursor = edges[e].from,
+                None => break,
+            }
+        }
+        let mut cycle = Vec::new();
+        let start = cursor;
+        while let Some(e) = pred[cursor] {
+            cycle.push(e);
+            cursor = edges[e].from;
+            if cursor == start || cycle.len() > node_count {
+                break;
+            }
+        }
+        if cursor != start {
+            continue;
+        }
+        cycle.reverse();
+        let mut key = cycle.clone();
+        key.sort_unstable();
+        if seen.insert(key) {
+            cycles.push(cycle);
+        }
+    }
+    cycles
+}
+
+/// Exact profit figures for a candidate cycle, `None` if rounding leaves no
+/// profitable size
+fn size_cycle(
+    graph: &LiquidityGraph,
+    hops: &[&LogEdge<'_>],
+    weight: f64,
+) -> Result<Option<Arbitrage>> {
+    // Start from the native asset when the cycle has it, otherwise the
+    // smallest asset, so each cycle is reported the same way every time
+    let start = hops
+        .iter()
+        .position(|e| e.edge.from.is_native())
+        .unwrap_or_else(|| {
+            (0..hops.len())
+                .min_by_key(|&i| hops[i].edge.from)
+                .unwrap_or(0)
+        });
+    let template = Route {
+        hops: hops[start..]
+            .iter()
+            .chain(&hops[..start])
+            .map(|e| RouteHop {
+                venue: e.edge.venue.id(),
+                pool_type: e.edge.venue.pool_type(),
+                from: e.edge.from.clone(),
+                to: e.edge.to.clone(),
+                amount_in: 0,
+                amount_out: 0,
+            })
+            .collect(),
+        amount_in: 0,
+        amount_out: 0,
+    };
+    let profit = |size: i128| -> Option<i128> {
+        simulate_route(graph, &template, size)
+            .ok()
+            .map(|r| r.amount_out - size)
+    };
+
+    // Double the size until the cycle stops paying, keeping the best point
+    let mut best: Option<(i128, i128)> = None;
+    let mut size: i128 = 1;
+    let mut upper = MAX_PROBE;
+    while size <= MAX_PROBE {
+        match (profit(size).filter(|&p| p > 0), best) {
+            (Some(p), Some((_, best_profit))) if p <= best_profit => {}
+            (Some(p), _) => best = Some((size, p)),
+            (None, Some(_)) => {
+                upper = size;
+                break;
+            }
+            (None, None) => {}
+        }
+        size *= 2;
+    }
+    let Some((probe, _)) = best else {
+        return Ok(None);
+    };
+
+    // Largest profitable size lies between the best probe and the first
+    // unprofitable one
+    let (mut lo, mut hi) = (probe, upper);
+    while hi - lo > 1 {
+        let mid = lo + (hi - lo) / 2;
+        if profit(mid).is_some_and(|p| p > 0) {
+            lo = mid;
+        } else {
+            hi = mid;
+        }
+    }
+    let max_profitable_size = lo;
+
+    // Profit is unimodal in size; ternary search the profitable range
+    let (mut lo, mut hi) = (1, max_profitable_size);
+    while hi - lo > 2 {
+        let m1 = lo + (hi - lo) / 3;
+        let m2 = hi - (hi - lo) / 3;
+        if profit(m1).unwrap_or(i128::MIN) < profit(m2).unwrap_or(i128::MIN) {
+            lo = m1 + 1;
+        } else {
+            hi = m2;
+        }
+    }
+    let optimal_size = (lo..=hi)
+        .max_by_key(|&s| (profit(s).unwrap_or(i128::MIN), -s))
+        .unwrap_or(probe);
+
+    let route = simulate_route(graph, &template, optimal_size)?;
+    let marginal = ((-weight).exp() - 1.0) * 10_000.0;
+    Ok(Some(Arbitrage {
+        expected_profit: route.amount_out - optimal_size,
+        route,
+        marginal_profit_bps: marginal.clamp(0.0, u32::MAX as f64) as u32,
+        optimal_size,
+        max_profitable_size,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constraints::RouteConstraints;
+    use crate::pathfinder::Pathfinder;
+    use crate::test_support::{asset, graph, pool};
+
+    /// XLM -> USDC -> yXLM -> XLM pays 1.2x before fees: yXLM is overpriced
+    fn mispriced() -> LiquidityGraph {
+        graph(vec![
+            pool("C1", "XLM", "USDC", 10_000_000_000, 1_000_000_000),
+            pool("C2", "USDC", "YXLM", 1_000_000_000, 10_000_000_000),
+            pool("C3", "YXLM", "XLM", 10_000_000_000, 12_000_000_000),
+        ])
+    }
+
+    #[test]
+    fn test_consistent_prices_have_no_cycles() {
+        let graph = graph(vec![
+            pool("C1", "XLM", "USDC", 10_000_000_000, 1_000_000_000),
+            pool("C2", "USDC", "YXLM", 1_000_000_000, 10_000_000_000),
+            pool("C3", "YXLM", "XLM", 10_000_000_000, 10_000_000_000),
+        ]);
+        let detector = ArbitrageDetector::default();
+        assert!(detector.find_cycles(&graph).unwrap().is_empty());
+        assert!(detector.suspect_venues(&graph).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_finds_and_sizes_profitable_cycle() {
+        let graph = mispriced();
+        let cycles = ArbitrageDetector::default().find_cycles(&graph).unwrap();
+        assert_eq!(cycles.len(), 1);
+        let arb = &cycles[0];
+
+        assert!(arb.start_asset().is_native());
+        assert_eq!(arb.route.hops.last().unwrap().to, AssetId::native());
+        assert_eq!(arb.route.hop_count(), 3);
+        // 1.2 * 0.997^3 - 1
+        assert!((1_880..=1_900).contains(&arb.marginal_profit_bps));
+
+        assert!(arb.expected_profit > 0);
+        assert_eq!(
+            arb.route.amount_out - arb.route.amount_in,
+            arb.expected_profit
+        );
+        assert!(arb.optimal_size < arb.max_profitable_size);
+
+        let profit =
+            |size: i128| simulate_route(&graph, &arb.route, size).unwrap().amount_out - size;
+        assert!(profit(arb.optimal_size - 1_000) <= arb.expected_profit);
+        assert!(profit(arb.optimal_size + 1_000) <= arb.expected_profit);
+        assert!(profit(arb.max_profitable_size) > 0);
+        assert!(profit(arb.max_profitable_size + 1) <= 0);
+    }
+
+    #[test]
+    fn test_suspect_venues_keep_stale_prices_out_of_quotes() {
+        let graph = mispriced();
+        let suspects = ArbitrageDetector::default().suspect_venues(&graph).unwrap();
+        assert_eq!(suspects.len(), 3);
+
+        // Below a lenient threshold the cycle is reported but not blocked
+        let lenient = ArbitrageDetector::new(ArbitrageConfig {
+            stale_threshold_bps: 5_000,
+        });
+        assert!(lenient.suspect_venues(&graph).unwrap().is_empty());
+
+        let constraints = RouteConstraints {
+            denied_venues: suspects,
+            ..Default::default()
+        };
+        assert!(Pathfinder::default()
+            .find_constrained_route(&graph, &asset("XLM"), &asset("YXLM"), 1_000, &constraints)
+            .is_err());
+    }
+}
@@ -0,0 +1,311 @@
+//! Candidate path caching
+//!
+//! Searching the graph is the expensive part of quoting; re-running a known
+//! path for a new amount is a handful of venue formulas. The cache keeps the
+//! best few paths found for a pair and amount range, and later requests only
+//! re-simulate them. Entries are keyed by the graph's topology version, so a
+//! venue appearing or disappearing forces a fresh search while reserve and
+//! price-level changes do not.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+use crate::constraints::RouteConstraints;
+use crate::error::Result;
+use crate::graph::{AssetId, LiquidityGraph};
+use crate::pathfinder::{simulate_route, Pathfinder, Route};
+
+/// Path cache configuration
+#[derive(Debug, Clone)]
+pub struct PathCacheConfig {
+    /// Most entries held at once
+    pub capacity: usize,
+    /// Paths kept per entry and re-simulated on a hit
+    pub candidates: usize,
+}
+
+impl Default for PathCacheConfig {
+    fn default() -> Self {
+        Self {
+            capacity: 4_096,
+            candidates: 4,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct PathKey {
+    from: AssetId,
+    to: AssetId,
+    bucket: u32,
+    topology: u64,
+}
+
+/// Hit and miss counters since the cache was created
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PathCacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    pub entries: usize,
+}
+
+/// Amount range an input falls into: amounts within a factor of two of each
+/// other share candidate paths
+pub fn amount_bucket(amount: i128) -> u32 {
+    if amount <= 0 {
+        0
+    } else {
+        127 - amount.leading_zeros()
+    }
+}
+
+/// Candidate paths per pair, amount bucket and topology version
+#[derive(Debug, Default)]
+pub struct PathCache {
+    config: PathCacheConfig,
+    entries: Mutex<HashMap<PathKey, Vec<Route>>>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl PathCache {
+    pub fn new(config: PathCacheConfig) -> Self {
+        Self {
+            config,
+            ..Self::default()
+        }
+    }
+
+    pub fn config(&self) -> &PathCacheConfig {
+        &self.config
+    }
+
+    /// Best route for `amount_in` of `from`, re-simulating cached paths when
+    /// the graph's topology has not changed since they were found
+    ///
+    /// Falls back to a full search when nothing is cached, or when none of
+    /// the cached paths can carry the amount any more.
+    pub fn find_best_route(
+        &self,
+        pathfinder: &Pathfinder,
+        graph: &LiquidityGraph,
+        from: &AssetId,
+        to: &AssetId,
+        amount_in: i128,
+    ) -> Result<Route> {
+        let routes = self.find_routes(pathfinder, graph, from, to, amount_in)?;
+        Ok(routes
+            .into_iter()
+            .max_by(|a, b| {
+                a.amount_out
+                    .cmp(&b.amount_out)
+                    .then_with(|| b.hop_count().cmp(&a.hop_count()))
+            })
+            .expect("at least one candidate"))
+    }
+
+    /// Every candidate path for the pair that can carry `amount_in`,
+    /// simulated at that amount
+    ///
+    /// Never empty: when no cached path fits, the fresh search's routes are
+    /// returned, or its error.
+    pub fn find_routes(
+        &self,
+        pathfinder: &Pathfinder,
+        graph: &LiquidityGraph,
+        from: &AssetId,
+        to: &AssetId,
+        amount_in: i128,
+    ) -> Result<Vec<Route>> {
+        let key = PathKey {
+            from: from.clone(),
+            to: to.clone(),
+            bucket: amount_bucket(amount_in),
+            topology: graph.watermark().topology,
+        };
+
+        let cached = self.lock().get(&key).cloned();
+        if let Some(paths) = cached {
+            let routes: Vec<Route> = paths
+                .iter()
+                .filter_map(|path| simulate_route(graph, path, amount_in).ok())
+                .collect();
+            if !routes.is_empty() {
+                self.hits.fetch_add(1, Ordering::Relaxed);
+                return Ok(routes);
+            }
+        }
+
+        self.misses.fetch_add(1, Ordering::Relaxed);
+        let routes = pathfinder.find_top_routes(
+            graph,
+            from,
+            to,
+            amount_in,
+            self.config.candidates.max(1),
+            &RouteConstraints::default(),
+        )?;
+        self.insert(key, routes.clone());
+        Ok(routes)
+    }
+
+    /// Drop every entry
+    pub fn clear(&self) {
+        self.lock().clear();
+    }
+
+    pub fn stats(&self) -> PathCacheStats {
+        PathCacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            entries: self.lock().len(),
+        }
+    }
+
+    fn insert(&self, key: PathKey, routes: Vec<Route>) {
+        if self.config.capacity == 0 {
+            return;
+        }
+        let mut entries = self.lock();
+        // Paths found on an older topology can never be hit again
+        entries.retain(|k, _| k.topology >= key.topology);
+        if entries.len() >= self.config.capacity && !entries.contains_key(&key) {
+            if let Some(evicted) = entries.keys().next().cloned() {
+                entries.remove(&evicted);
+            }
+        }
+        entries.insert(key, routes);
+    }
+
+    fn lock(&self) -> MutexGuard<'_, HashMap<PathKey, Vec<Route>>> {
+        self.entries.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{self, asset, pool};
+
+    fn graph() -> LiquidityGraph {
+        test_support::graph([
+            pool("CDIRECT", "XLM", "USDC", 1_000_000_000, 100_000_000),
+            pool("C1", "XLM", "EURC", 1_000_000_000, 90_000_000),
+            pool("C2", "EURC", "USDC", 90_000_000, 100_000_000),
+        ])
+    }
+
+    #[test]
+    fn test_amount_bucket_groups_by_magnitude() {
+        assert_eq!(amount_bucket(0), 0);
+        assert_eq!(amount_bucket(1), 0);
+        assert_eq!(amount_bucket(1_024), 10);
+        assert_eq!(amount_bucket(2_047), 10);
+        assert_eq!(amount_bucket(2_048), 11);
+    }
+
+    #[test]
+    fn test_hit_resimulates_cached_paths() {
+        let mut graph = graph();
+        let finder = Pathfinder::default();
+        let cache = PathCache::default();
+        let (xlm, usdc) = (asset("XLM"), asset("USDC"));
+
+        let first = cache
+            .find_best_route(&finder, &graph, &xlm, &usdc, 10_000_000)
+            .unwrap();
+        let second = cache
+            .find_best_route(&finder, &graph, &xlm, &usdc, 12_000_000)
+            .unwrap();
+        assert_eq!(
+            first,
+            finder
+                .find_best_route(&graph, &xlm, &usdc, 10_000_000)
+                .unwrap()
+        );
+        assert_eq!(
+            second,
+            finder
+                .find_best_route(&graph, &xlm, &usdc, 12_000_000)
+                .unwrap()
+        );
+        assert_eq!(cache.stats().hits, 1);
+        assert_eq!(cache.stats().misses, 1);
+
+        // Reserve changes keep the topology, so the cached paths are re-run
+        // against the new reserves
+        graph
+            .set_pool_reserves("CDIRECT", 1_000_000_000, 50_000_000)
+            .unwrap();
+        let moved = cache
+            .find_best_route(&finder, &graph, &xlm, &usdc, 10_000_000)
+            .unwrap();
+        assert_eq!(
+            moved,
+            finder
+                .find_best_route(&graph, &xlm, &usdc, 10_000_000)
+                .unwrap()
+        );
+        assert_eq!(moved.hop_count(), 2);
+        assert_eq!(cache.stats().hits, 2);
+    }
+
+    #[test]
+    fn test_topology_change_forces_a_new_search() {
+        let mut graph = graph();
+        let finder = Pathfinder::default();
+        let cache = PathCache::default();
+        let (xlm, usdc) = (asset("XLM"), asset("USDC"));
+
+        cache
+            .find_best_route(&finder, &graph, &xlm, &usdc, 10_000_000)
+            .unwrap();
+        graph
+            .upsert_venue(pool("CDEEP", "XLM", "USDC", 10_000_000_000, 1_000_000_000))
+            .unwrap();
+
+        let route = cache
+            .find_best_route(&finder, &graph, &xlm, &usdc, 10_000_000)
+            .unwrap();
+        assert_eq!(route.hops[0].venue.to_string(), "amm:CDEEP");
+        // The entry for the old topology has been dropped
+        assert_eq!(cache.stats().misses, 2);
+        assert_eq!(cache.stats().entries, 1);
+    }
+
+    #[test]
+    fn test_amounts_in_other_buckets_miss() {
+        let graph = graph();
+        let finder = Pathfinder::default();
+        let cache = PathCache::default();
+        let (xlm, usdc) = (asset("XLM"), asset("USDC"));
+
+        cache
+            .find_best_route(&finder, &graph, &xlm, &usdc, 10_000_000)
+            .unwrap();
+        cache
+            .find_best_route(&finder, &graph, &xlm, &usdc, 100_000_000)
+            .unwrap();
+        assert_eq!(cache.stats().misses, 2);
+        assert_eq!(cache.stats().entries, 2);
+    }
+
+    #[test]
+    fn test_capacity_bounds_entries() {
+        let graph = graph();
+        let finder = Pathfinder::default();
+        let cache = PathCache::new(PathCacheConfig {
+            capacity: 1,
+            candidates: 2,
+        });
+        let (xlm, usdc) = (asset("XLM"), asset("USDC"));
+
+        for amount in [1_000_000, 10_000_000, 100_000_000] {
+            cache
+                .find_best_route(&finder, &graph, &xlm, &usdc, amount)
+                .unwrap();
+        }
+        assert_eq!(cache.stats().entries, 1);
+    }
+}
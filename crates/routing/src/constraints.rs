@@ -0,0 +1,134 @@
+//! Caller-supplied restrictions on which routes may be returned
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+use crate::graph::{AssetId, EdgeRef, PoolType, VenueId};
+
+/// Restrictions applied while searching for routes
+///
+/// The default places no restrictions. Denied venues, assets and issuers
+/// remove their edges from the search; `allowed_pool_types` keeps only
+/// venues of those kinds, e.g. `[Sdex]` for SDEX-only routing.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RouteConstraints {
+    pub denied_venues: HashSet<VenueId>,
+    pub denied_assets: HashSet<AssetId>,
+    /// Issuer account IDs whose assets may not be touched
+    pub denied_issuers: HashSet<String>,
+    /// Asset every route must pass through
+    pub required_asset: Option<AssetId>,
+    /// Venue kinds a route may use; `None` allows all
+    pub allowed_pool_types: Option<HashSet<PoolType>>,
+    /// Hop limit, applied on top of the pathfinder's own
+    pub max_hops: Option<usize>,
+    /// Least output-side depth an edge needs to be used, in stroops
+    pub min_liquidity: i128,
+}
+
+impl RouteConstraints {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn deny_venue(mut self, venue: VenueId) -> Self {
+        self.denied_venues.insert(venue);
+        self
+    }
+
+    pub fn deny_asset(mut self, asset: AssetId) -> Self {
+        self.denied_assets.insert(asset);
+        self
+    }
+
+    pub fn deny_issuer(mut self, issuer: impl Into<String>) -> Self {
+        self.denied_issuers.insert(issuer.into());
+        self
+    }
+
+    pub fn require_asset(mut self, asset: AssetId) -> Self {
+        self.required_asset = Some(asset);
+        self
+    }
+
+    pub fn allow_pool_types(mut self, pool_types: impl IntoIterator<Item = PoolType>) -> Self {
+        self.allowed_pool_types = Some(pool_types.into_iter().collect());
+        self
+    }
+
+    pub fn max_hops(mut self, max_hops: usize) -> Self {
+        self.max_hops = Some(max_hops);
+        self
+    }
+
+    pub fn min_liquidity(mut self, min_liquidity: i128) -> Self {
+        self.min_liquidity = min_liquidity;
+        self
+    }
+
+    /// Whether routes may touch `asset` at all
+    pub fn allows_asset(&self, asset: &AssetId) -> bool {
+        !self.denied_assets.contains(asset)
+            && !asset
+                .issuer()
+                .is_some_and(|issuer| self.denied_issuers.contains(issuer))
+    }
+
+    /// Whether a route may use `edge`
+    pub fn allows_edge(&self, edge: &EdgeRef<'_>) -> bool {
+        if !self.allows_asset(edge.from) || !self.allows_asset(edge.to) {
+            return false;
+        }
+        if let Some(allowed) = &self.allowed_pool_types {
+            if !allowed.contains(&edge.venue.pool_type()) {
+                return false;
+            }
+        }
+        if self.min_liquidity > 0 && edge.depth() < self.min_liquidity {
+            return false;
+        }
+        self.denied_venues.is_empty() || !self.denied_venues.contains(&edge.venue.id())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::LiquidityGraph;
+    use crate::test_support::{self, pool, usdc, ISSUER};
+
+    fn graph() -> LiquidityGraph {
+        test_support::graph([pool("CPOOL", "XLM", "USDC", 1_000, 100)])
+    }
+
+    #[test]
+    fn test_default_allows_everything() {
+        let graph = graph();
+        let edge = graph.edges_from(&AssetId::native()).next().unwrap();
+        assert!(RouteConstraints::default().allows_edge(&edge));
+    }
+
+    #[test]
+    fn test_each_restriction_blocks_the_edge() {
+        let graph = graph();
+        let edge = graph.edges_from(&AssetId::native()).next().unwrap();
+
+        let blocking = [
+            RouteConstraints::new().deny_venue(edge.venue.id()),
+            RouteConstraints::new().deny_asset(usdc()),
+            RouteConstraints::new().deny_issuer(ISSUER),
+            RouteConstraints::new().allow_pool_types([PoolType::Sdex]),
+            RouteConstraints::new().min_liquidity(101),
+        ];
+        for constraints in blocking {
+            assert!(!constraints.allows_edge(&edge), "{:?}", constraints);
+        }
+
+        assert!(RouteConstraints::new()
+            .allow_pool_types([PoolType::AmmConstProd, PoolType::AmmStable])
+            .min_liquidity(100)
+            .deny_issuer("GOTHER")
+            .allows_edge(&edge));
+    }
+}
@@ -0,0 +1,329 @@
+//! Incremental liquidity updates
+//!
+//! The indexer sees the market one change at a time: an offer created,
+//! updated or taken, a pool's reserves moved by a swap. A [`GraphUpdate`]
+//! carries the changes observed at one ledger so the graph can apply them as
+//! edge deltas instead of being rebuilt from the database.
+
+use serde::{Deserialize, Serialize};
+
+use crate::amount::Price;
+use crate::error::{Result, RoutingError};
+use crate::graph::{AmmPool, AssetId, VenueId};
+
+/// A single SDEX offer
+///
+/// The price is expressed the way Horizon does: `price_n / price_d` units of
+/// the buying asset per unit of the selling asset.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SdexOffer {
+    pub id: u64,
+    pub selling: AssetId,
+    pub buying: AssetId,
+    pub price_n: i64,
+    pub price_d: i64,
+    /// Amount of the selling asset still on offer, in stroops
+    pub amount: i128,
+}
+
+impl SdexOffer {
+    /// The orderbook side this offer belongs to
+    pub fn venue_id(&self) -> VenueId {
+        VenueId::Sdex {
+            selling: self.selling.clone(),
+            buying: self.buying.clone(),
+        }
+    }
+
+    /// The offer's price as an exact rational
+    pub fn price(&self) -> Result<Price> {
+        Price::new(self.price_n as i128, self.price_d as i128)
+    }
+
+    pub(crate) fn validate(&self) -> Result<()> {
+        if self.selling == self.buying {
+            return Err(RoutingError::InvalidPair(format!(
+                "offer {} sells and buys {}",
+                self.id, self.selling
+            )));
+        }
+        if self.price_n <= 0 || self.price_d <= 0 || self.amount < 0 {
+            return Err(RoutingError::InvalidAmount(format!(
+                "invalid price or amount for offer {}",
+                self.id
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// One change to the liquidity graph
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum GraphDelta {
+    /// Offer created or updated; an amount of zero removes it
+    UpsertOffer(SdexOffer),
+    /// Offer taken in full or cancelled
+    RemoveOffer {
+        id: u64,
+    },
+    /// Pool created, or replaced as a whole
+    UpsertPool(AmmPool),
+    /// New reserves for a pool already in the graph
+    PoolReserves {
+        address: String,
+        reserve_a: i128,
+        reserve_b: i128,
+    },
+    RemovePool {
+        address: String,
+    },
+}
+
+/// Changes observed at one ledger, applied in order
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GraphUpdate {
+    pub ledger: u64,
+    pub deltas: Vec<GraphDelta>,
+}
+
+impl GraphUpdate {
+    pub fn new(ledger: u64) -> Self {
+        Self {
+            ledger,
+            deltas: Vec::new(),
+        }
+    }
+
+    pub fn push(mut self, delta: GraphDelta) -> Self {
+        self.deltas.push(delta);
+        self
+    }
+}
+
+/// Where a graph stands relative to the ledger stream
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Watermark {
+    /// Bumped by every change to the graph, however it was made
+    pub version: u64,
+    /// Bumped only when venues, and so edges, are added or removed
+    pub topology: u64,
+    /// Latest ledger whose update has been fully applied
+    pub ledger: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::{LiquidityGraph, PoolType, PriceLevel, Venue};
+    use crate::test_support::{usdc, xlm};
+
+    fn offer(id: u64, price_n: i64, price_d: i64, amount: i128) -> SdexOffer {
+        SdexOffer {
+            id,
+            selling: xlm(),
+            buying: usdc(),
+            price_n,
+            price_d,
+            amount,
+        }
+    }
+
+    fn pool() -> AmmPool {
+        AmmPool {
+            address: "CPOOL".to_string(),
+            pool_type: PoolType::AmmConstProd,
+            asset_a: xlm(),
+            asset_b: usdc(),
+            reserve_a: 1_000_000,
+            reserve_b: 100_000,
+            fee_bps: 30,
+            amplification: None,
+        }
+    }
+
+    fn levels(graph: &LiquidityGraph) -> Vec<PriceLevel> {
+        match graph.venue(&offer(0, 1, 1, 0).venue_id()) {
+            Some(Venue::Sdex(side)) => side.levels.clone(),
+            Some(other) => panic!("unexpected venue: {:?}", other),
+            None => Vec::new(),
+        }
+    }
+
+    fn level(price_n: i64, price_d: i64, amount: i128) -> PriceLevel {
+        PriceLevel {
+            price_n,
+            price_d,
+            amount,
+        }
+    }
+
+    #[test]
+    fn test_offers_aggregate_into_sorted_levels() {
+        let mut graph = LiquidityGraph::new();
+        graph
+            .apply_update(
+                &GraphUpdate::new(10)
+                    .push(GraphDelta::UpsertOffer(offer(1, 2, 10, 500)))
+                    .push(GraphDelta::UpsertOffer(offer(2, 1, 10, 300)))
+                    // Same price as offer 1, written differently
+                    .push(GraphDelta::UpsertOffer(offer(3, 1, 5, 200))),
+            )
+            .unwrap();
+
+        assert_eq!(levels(&graph), vec![level(1, 10, 300), level(2, 10, 700)]);
+        assert_eq!(graph.edges_from(&usdc()).count(), 1);
+        assert_eq!(graph.watermark().ledger, 10);
+    }
+
+    #[test]
+    fn test_offer_updates_and_removals_adjust_levels() {
+        let mut graph = LiquidityGraph::new();
+        graph
+            .apply_update(
+                &GraphUpdate::new(10)
+                    .push(GraphDelta::UpsertOffer(offer(1, 1, 10, 500)))
+                    .push(GraphDelta::UpsertOffer(offer(2, 1, 10, 300))),
+            )
+            .unwrap();
+
+        // Partially taken, then re-priced
+        let taken = GraphUpdate::new(11).push(GraphDelta::UpsertOffer(offer(1, 1, 10, 100)));
+        graph.apply_update(&taken).unwrap();
+        assert_eq!(levels(&graph), vec![level(1, 10, 400)]);
+        let repriced = GraphUpdate::new(12).push(GraphDelta::UpsertOffer(offer(2, 3, 10, 300)));
+        graph.apply_update(&repriced).unwrap();
+        assert_eq!(levels(&graph), vec![level(1, 10, 100), level(3, 10, 300)]);
+
+        // Taken in full, and removed by id
+        graph
+            .apply_update(
+                &GraphUpdate::new(13)
+                    .push(GraphDelta::UpsertOffer(offer(1, 1, 10, 0)))
+                    .push(GraphDelta::RemoveOffer { id: 2 }),
+            )
+            .unwrap();
+        assert!(graph.is_empty());
+        assert_eq!(graph.asset_count(), 0);
+    }
+
+    #[test]
+    fn test_pool_reserves_update_in_place() {
+        let mut graph = LiquidityGraph::new();
+        graph
+            .apply_update(&GraphUpdate::new(5).push(GraphDelta::UpsertPool(pool())))
+            .unwrap();
+        let before = graph
+            .edges_from(&xlm())
+            .next()
+            .unwrap()
+            .amount_out(10_000)
+            .unwrap();
+
+        graph
+            .apply_update(&GraphUpdate::new(6).push(GraphDelta::PoolReserves {
+                address: "CPOOL".to_string(),
+                reserve_a: 2_000_000,
+                reserve_b: 50_000,
+            }))
+            .unwrap();
+        let edge = graph.edges_from(&xlm()).next().unwrap();
+        assert_eq!(edge.depth(), 50_000);
+        assert!(edge.amount_out(10_000).unwrap() < before);
+
+        let unknown = GraphUpdate::new(7).push(GraphDelta::PoolReserves {
+            address: "COTHER".to_string(),
+            reserve_a: 1,
+            reserve_b: 1,
+        });
+        assert!(matches!(
+            graph.apply_update(&unknown),
+            Err(RoutingError::InvalidPair(_))
+        ));
+
+        graph
+            .apply_update(&GraphUpdate::new(8).push(GraphDelta::RemovePool {
+                address: "CPOOL".to_string(),
+            }))
+            .unwrap();
+        assert!(graph.is_empty());
+    }
+
+    #[test]
+    fn test_watermark_tracks_version_and_ledger() {
+        let mut graph = LiquidityGraph::new();
+        assert_eq!(graph.watermark(), Watermark::default());
+
+        graph.upsert_venue(Venue::Amm(pool())).unwrap();
+        assert_eq!(graph.watermark().version, 1);
+
+        graph
+            .apply_update(
+                &GraphUpdate::new(20)
+                    .push(GraphDelta::UpsertOffer(offer(1, 1, 10, 500)))
+                    .push(GraphDelta::UpsertOffer(offer(2, 1, 10, 500))),
+            )
+            .unwrap();
+        assert_eq!(
+            graph.watermark(),
+            Watermark {
+                version: 3,
+                topology: 2,
+                ledger: 20
+            }
+        );
+
+        // Removing an unknown offer changes nothing
+        graph
+            .apply_update(&GraphUpdate::new(20).push(GraphDelta::RemoveOffer { id: 99 }))
+            .unwrap();
+        assert_eq!(graph.watermark().version, 3);
+
+        // Emptying the side removes its edge
+        graph.remove_offer(1).unwrap();
+        graph.remove_offer(2).unwrap();
+        assert_eq!(graph.watermark().topology, 3);
+
+        assert!(matches!(
+            graph.apply_update(&GraphUpdate::new(19)),
+            Err(RoutingError::StaleUpdate(19, 20))
+        ));
+    }
+
+    #[test]
+    fn test_failed_update_keeps_ledger_watermark() {
+        let mut graph = LiquidityGraph::new();
+        let update = GraphUpdate::new(30)
+            .push(GraphDelta::UpsertOffer(offer(1, 1, 10, 500)))
+            .push(GraphDelta::UpsertOffer(offer(2, 0, 10, 500)));
+
+        assert!(matches!(
+            graph.apply_update(&update),
+            Err(RoutingError::InvalidAmount(_))
+        ));
+        // Nothing is applied, not even the deltas before the failure
+        assert!(levels(&graph).is_empty());
+        assert_eq!(graph.watermark(), Watermark::default());
+    }
+
+    #[test]
+    fn test_replacing_a_side_forgets_its_offers() {
+        let mut graph = LiquidityGraph::new();
+        graph
+            .apply_update(&GraphUpdate::new(1).push(GraphDelta::UpsertOffer(offer(1, 1, 10, 500))))
+            .unwrap();
+        graph
+            .upsert_venue(Venue::Sdex(crate::graph::OrderbookSide {
+                selling: xlm(),
+                buying: usdc(),
+                levels: vec![level(2, 10, 100)],
+            }))
+            .unwrap();
+
+        // Offer 1 no longer backs any level, so removing it is a no-op
+        graph
+            .apply_update(&GraphUpdate::new(2).push(GraphDelta::RemoveOffer { id: 1 }))
+            .unwrap();
+        assert_eq!(levels(&graph), vec![level(2, 10, 100)]);
+    }
+}
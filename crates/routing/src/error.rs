@@ -15,6 +15,15 @@ pub enum RoutingError {
 
     #[error("Invalid asset pair: {0}")]
     InvalidPair(String),
+
+    #[error("Arithmetic overflow")]
+    Overflow,
+
+    #[error("Update for ledger {0} is older than graph ledger {1}")]
+    StaleUpdate(u64, u64),
+
+    #[error("Cannot encode route for the router contract: {0}")]
+    Encoding(String),
 }
 
 pub type Result<T> = std::result::Result<T, RoutingError>;
@@ -0,0 +1,494 @@
+//! Router contract route export
+//!
+//! Mirrors the router contract's `Asset`, `PoolType`, `RouteHop` and `Route`
+//! types, so a route found off-chain can be handed to `execute_swap` as is.
+//! [`ContractRoute::to_scval`] produces the value the contract's
+//! `#[contracttype]` derive decodes: structs as maps keyed by field name in
+//! sorted order, enums as a vector of the variant name and its fields.
+
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+use stellar_xdr::curr::{Limits, ScAddress, ScMap, ScMapEntry, ScSymbol, ScVal, ScVec, WriteXdr};
+
+use crate::amount::{mul_div_ceil, mul_div_floor, BPS};
+use crate::error::{Result, RoutingError};
+use crate::graph::{AssetId, PoolType, VenueId};
+use crate::pathfinder::{Route, DEFAULT_MAX_HOPS};
+
+/// Contract-side settings the export depends on
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExportConfig {
+    /// Adapter contract the router calls for SDEX hops
+    pub sdex_adapter: Option<String>,
+    /// Router fee taken from the final output, in basis points
+    pub protocol_fee_bps: u32,
+}
+
+/// The contract's `Asset`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ContractAsset {
+    Native,
+    Issued {
+        issuer: String,
+        code: String,
+    },
+    /// Soroban token contract
+    Soroban {
+        address: String,
+    },
+}
+
+impl ContractAsset {
+    /// Map a graph asset: "native", "CODE:ISSUER", or a token contract
+    /// address
+    pub fn from_asset_id(asset: &AssetId) -> Result<Self> {
+        if asset.is_native() {
+            return Ok(Self::Native);
+        }
+        let exported = match asset.as_str().split_once(':') {
+            Some((code, issuer)) => Self::Issued {
+                issuer: issuer.to_string(),
+                code: code.to_string(),
+            },
+            None => Self::Soroban {
+                address: asset.to_string(),
+            },
+        };
+        exported.to_scval()?;
+        Ok(exported)
+    }
+
+    pub fn to_scval(&self) -> Result<ScVal> {
+        match self {
+            Self::Native => variant("Native", vec![]),
+            Self::Issued { issuer, code } => {
+                variant("Issued", vec![address(issuer)?, symbol(code)?])
+            }
+            Self::Soroban { address: contract } => variant("Soroban", vec![address(contract)?]),
+        }
+    }
+}
+
+/// The contract's `RouteHop`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ContractRouteHop {
+    pub source: ContractAsset,
+    pub destination: ContractAsset,
+    /// Contract the router calls to swap
+    pub pool: String,
+    pub pool_type: PoolType,
+}
+
+impl ContractRouteHop {
+    pub fn to_scval(&self) -> Result<ScVal> {
+        let pool_type = match self.pool_type {
+            PoolType::Sdex => "Sdex",
+            PoolType::AmmConstProd => "AmmConstProd",
+            PoolType::AmmStable => "AmmStable",
+        };
+        fields(vec![
+            ("source", self.source.to_scval()?),
+            ("destination", self.destination.to_scval()?),
+            ("pool", address(&self.pool)?),
+            ("pool_type", variant(pool_type, vec![])?),
+        ])
+    }
+}
+
+/// The contract's `Route`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ContractRoute {
+    pub hops: Vec<ContractRouteHop>,
+    /// Output after the router fee, in stroops
+    pub estimated_output: i128,
+    /// Least output accepted, in stroops
+    pub min_output: i128,
+    /// Ledger sequence after which the route should not be executed
+    pub expires_at: u64,
+}
+
+impl ContractRoute {
+    pub fn to_scval(&self) -> Result<ScVal> {
+        let hops = self
+            .hops
+            .iter()
+            .map(ContractRouteHop::to_scval)
+            .collect::<Result<Vec<_>>>()?;
+        fields(vec![
+            (
+                "hops",
+                ScVal::Vec(Some(ScVec::try_from(hops).map_err(xdr)?)),
+            ),
+            ("estimated_output", ScVal::from(self.estimated_output)),
+            ("min_output", ScVal::from(self.min_output)),
+            ("expires_at", ScVal::from(self.expires_at)),
+        ])
+    }
+
+    /// Base64 XDR of [`Self::to_scval`], as passed to contract invocations
+    pub fn to_xdr_base64(&self) -> Result<String> {
+        self.to_scval()?.to_xdr_base64(Limits::none()).map_err(xdr)
+    }
+}
+
+/// Least output within `slippage_bps` of `estimated_output`, rounded down
+pub fn min_output(estimated_output: i128, slippage_bps: u32) -> Result<i128> {
+    let slippage = checked_bps(slippage_bps, "slippage")?;
+    mul_div_floor(estimated_output, BPS - slippage, BPS)
+}
+
+/// Most input within `slippage_bps` of `estimated_input`, rounded up
+pub fn max_input(estimated_input: i128, slippage_bps: u32) -> Result<i128> {
+    let slippage = checked_bps(slippage_bps, "slippage")?;
+    mul_div_ceil(estimated_input, BPS + slippage, BPS)
+}
+
+/// Fee the router takes from a swap's final output, rounded down as the
+/// contract does
+pub fn protocol_fee(amount_out: i128, protocol_fee_bps: u32) -> Result<i128> {
+    let fee = checked_bps(protocol_fee_bps, "router fee")?;
+    mul_div_floor(amount_out, fee, BPS)
+}
+
+/// Least output a route must reach for the router to pay out at least
+/// `amount_out` after its fee
+pub fn output_before_fee(amount_out: i128, protocol_fee_bps: u32) -> Result<i128> {
+    let fee = checked_bps(protocol_fee_bps, "router fee")?;
+    if fee == BPS {
+        return Err(RoutingError::InvalidAmount(
+            "a router fee of 100% leaves no output".to_string(),
+        ));
+    }
+    mul_div_ceil(amount_out, BPS, BPS - fee)
+}
+
+fn checked_bps(bps: u32, what: &str) -> Result<i128> {
+    if bps as i128 > BPS {
+        return Err(RoutingError::InvalidAmount(format!(
+            "{} of {} bps exceeds 100%",
+            what, bps
+        )));
+    }
+    Ok(bps as i128)
+}
+
+/// Convert `route` into the contract's `Route`
+///
+/// The estimated output is what the contract pays out after its fee, which
+/// is also what it compares against `min_output`.
+pub fn export_route(
+    route: &Route,
+    slippage_bps: u32,
+    expires_at: u64,
+    config: &ExportConfig,
+) -> Result<ContractRoute> {
+    let hops = export_hops(route, config)?;
+    let estimated_output =
+        route.amount_out - protocol_fee(route.amount_out, config.protocol_fee_bps)?;
+    Ok(ContractRoute {
+        hops,
+        estimated_output,
+        min_output: min_output(estimated_output, slippage_bps)?,
+        expires_at,
+    })
+}
+
+/// Convert a route found for an exact output into the contract's `Route`,
+/// requiring that the router pays out at least `amount_out` after its fee
+///
+/// Slippage is taken on the input side instead, by sending up to
+/// [`max_input`] of the route's input; any extra output goes to the
+/// recipient.
+pub fn export_route_exact_out(
+    route: &Route,
+    amount_out: i128,
+    expires_at: u64,
+    config: &ExportConfig,
+) -> Result<ContractRoute> {
+    let hops = export_hops(route, config)?;
+    let estimated_output =
+        route.amount_out - protocol_fee(route.amount_out, config.protocol_fee_bps)?;
+    if estimated_output < amount_out {
+        return Err(RoutingError::InvalidAmount(format!(
+            "route pays out {} after the router fee, short of {}",
+            estimated_output, amount_out
+        )));
+    }
+    Ok(ContractRoute {
+        hops,
+        estimated_output,
+        min_output: amount_out,
+        expires_at,
+    })
+}
+
+fn export_hops(route: &Route, config: &ExportConfig) -> Result<Vec<ContractRouteHop>> {
+    if route.hops.is_empty() || route.hop_count() > DEFAULT_MAX_HOPS {
+        return Err(RoutingError::Encoding(format!(
+            "the router executes 1 to {} hops, route has {}",
+            DEFAULT_MAX_HOPS,
+            route.hop_count()
+        )));
+    }
+    route
+        .hops
+        .iter()
+        .map(|hop| {
+            let pool = match &hop.venue {
+                VenueId::Amm { address } => address.clone(),
+                VenueId::Sdex { .. } => config.sdex_adapter.clone().ok_or_else(|| {
+                    RoutingError::Encoding("no SDEX adapter configured".to_string())
+                })?,
+            };
+            address(&pool)?;
+            Ok(ContractRouteHop {
+                source: ContractAsset::from_asset_id(&hop.from)?,
+                destination: ContractAsset::from_asset_id(&hop.to)?,
+                pool,
+                pool_type: hop.pool_type,
+            })
+        })
+        .collect()
+}
+
+fn xdr(err: stellar_xdr::curr::Error) -> RoutingError {
+    RoutingError::Encoding(err.to_string())
+}
+
+fn address(strkey: &str) -> Result<ScVal> {
+    ScAddress::from_str(strkey)
+        .map(ScVal::Address)
+        .map_err(|_| RoutingError::Encoding(format!("{} is not an account or contract", strkey)))
+}
+
+fn symbol(name: &str) -> Result<ScVal> {
+    ScSymbol::try_from(name)
+        .map(ScVal::Symbol)
+        .map_err(|_| RoutingError::Encoding(format!("{} is not a valid symbol", name)))
+}
+
+/// An enum variant: its name, then its fields
+fn variant(name: &str, values: Vec<ScVal>) -> Result<ScVal> {
+    let mut items = vec![symbol(name)?];
+    items.extend(values);
+    Ok(ScVal::Vec(Some(ScVec::try_from(items).map_err(xdr)?)))
+}
+
+/// A struct: a map from field name to value, sorted by name
+fn fields(mut entries: Vec<(&str, ScVal)>) -> Result<ScVal> {
+    entries.sort_by_key(|(name, _)| *name);
+    let entries = entries
+        .into_iter()
+        .map(|(name, val)| {
+            Ok(ScMapEntry {
+                key: symbol(name)?,
+                val,
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+    Ok(ScVal::Map(Some(ScMap::try_from(entries).map_err(xdr)?)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pathfinder::RouteHop;
+    use crate::test_support::{usdc, ISSUER};
+    use stellar_xdr::curr::{Hash, ReadXdr};
+
+    fn contract(byte: u8) -> String {
+        ScAddress::Contract(Hash([byte; 32])).to_string()
+    }
+
+    fn hop(venue: VenueId, pool_type: PoolType, from: AssetId, to: AssetId) -> RouteHop {
+        RouteHop {
+            venue,
+            pool_type,
+            from,
+            to,
+            amount_in: 10_000_000,
+            amount_out: 1_000_000,
+        }
+    }
+
+    fn route(hops: Vec<RouteHop>) -> Route {
+        Route {
+            hops,
+            amount_in: 10_000_000,
+            amount_out: 1_000_000,
+        }
+    }
+
+    fn amm_route() -> Route {
+        route(vec![hop(
+            VenueId::Amm {
+                address: contract(1),
+            },
+            PoolType::AmmConstProd,
+            AssetId::native(),
+            usdc(),
+        )])
+    }
+
+    #[test]
+    fn test_assets_map_to_contract_variants() {
+        assert_eq!(
+            ContractAsset::from_asset_id(&AssetId::native()).unwrap(),
+            ContractAsset::Native
+        );
+        assert_eq!(
+            ContractAsset::from_asset_id(&usdc()).unwrap(),
+            ContractAsset::Issued {
+                issuer: ISSUER.to_string(),
+                code: "USDC".to_string()
+            }
+        );
+        assert_eq!(
+            ContractAsset::from_asset_id(&AssetId::new(contract(7))).unwrap(),
+            ContractAsset::Soroban {
+                address: contract(7)
+            }
+        );
+        assert!(matches!(
+            ContractAsset::from_asset_id(&AssetId::credit("USDC", "GNOTANADDRESS")),
+            Err(RoutingError::Encoding(_))
+        ));
+    }
+
+    #[test]
+    fn test_min_output_applies_slippage_after_router_fee() {
+        let config = ExportConfig {
+            protocol_fee_bps: 30,
+            ..ExportConfig::default()
+        };
+        let exported = export_route(&amm_route(), 50, 1_234, &config).unwrap();
+
+        // 1,000,000 less 0.3%, then less 0.5%, rounded down
+        assert_eq!(exported.estimated_output, 997_000);
+        assert_eq!(exported.min_output, 992_015);
+        assert_eq!(exported.expires_at, 1_234);
+        assert_eq!(exported.hops[0].pool, contract(1));
+        assert_eq!(exported.hops[0].source, ContractAsset::Native);
+
+        assert_eq!(min_output(997_000, 0).unwrap(), 997_000);
+        assert!(matches!(
+            min_output(997_000, 10_001),
+            Err(RoutingError::InvalidAmount(_))
+        ));
+    }
+
+    #[test]
+    fn test_exact_out_routes_require_the_amount_after_router_fee() {
+        let config = ExportConfig {
+            protocol_fee_bps: 30,
+            ..ExportConfig::default()
+        };
+        // The route reaches 1,000,000 before the fee, 997,000 after it
+        let exported = export_route_exact_out(&amm_route(), 997_000, 1_234, &config).unwrap();
+        assert_eq!(exported.estimated_output, 997_000);
+        assert_eq!(exported.min_output, 997_000);
+        assert!(matches!(
+            export_route_exact_out(&amm_route(), 997_001, 1_234, &config),
+            Err(RoutingError::InvalidAmount(_))
+        ));
+
+        assert_eq!(output_before_fee(997_000, 30).unwrap(), 1_000_000);
+        // Rounded up, so the fee taken from it still leaves the amount
+        let gross = output_before_fee(997_001, 30).unwrap();
+        assert_eq!(gross, 1_000_002);
+        assert!(gross - protocol_fee(gross, 30).unwrap() >= 997_001);
+        assert_eq!(output_before_fee(500, 0).unwrap(), 500);
+        assert!(output_before_fee(500, 10_000).is_err());
+
+        assert_eq!(max_input(1_000_000, 50).unwrap(), 1_005_000);
+        assert_eq!(max_input(999, 50).unwrap(), 1_004);
+    }
+
+    #[test]
+    fn test_sdex_hops_need_an_adapter() {
+        let sdex = route(vec![hop(
+            VenueId::Sdex {
+                selling: usdc(),
+                buying: AssetId::native(),
+            },
+            PoolType::Sdex,
+            AssetId::native(),
+            usdc(),
+        )]);
+        assert!(matches!(
+            export_route(&sdex, 50, 0, &ExportConfig::default()),
+            Err(RoutingError::Encoding(_))
+        ));
+
+        let config = ExportConfig {
+            sdex_adapter: Some(contract(9)),
+            ..ExportConfig::default()
+        };
+        let exported = export_route(&sdex, 50, 0, &config).unwrap();
+        assert_eq!(exported.hops[0].pool, contract(9));
+        assert_eq!(exported.hops[0].pool_type, PoolType::Sdex);
+    }
+
+    #[test]
+    fn test_routes_over_the_hop_limit_are_rejected() {
+        let hops = (0..=DEFAULT_MAX_HOPS as u8)
+            .map(|i| {
+                hop(
+                    VenueId::Amm {
+                        address: contract(i),
+                    },
+                    PoolType::AmmConstProd,
+                    AssetId::native(),
+                    usdc(),
+                )
+            })
+            .collect();
+        assert!(matches!(
+            export_route(&route(hops), 50, 0, &ExportConfig::default()),
+            Err(RoutingError::Encoding(_))
+        ));
+    }
+
+    #[test]
+    fn test_scval_matches_contracttype_layout() {
+        let exported = export_route(&amm_route(), 50, 99, &ExportConfig::default()).unwrap();
+        let ScVal::Map(Some(route)) = exported.to_scval().unwrap() else {
+            panic!("route is not a map");
+        };
+        let keys: Vec<String> = route.iter().map(|e| format!("{:?}", e.key)).collect();
+        let expected: Vec<String> = ["estimated_output", "expires_at", "hops", "min_output"]
+            .iter()
+            .map(|k| format!("{:?}", symbol(k).unwrap()))
+            .collect();
+        assert_eq!(keys, expected);
+        assert_eq!(route[1].val, ScVal::U64(99));
+
+        let ScVal::Vec(Some(hops)) = &route[2].val else {
+            panic!("hops is not a vector");
+        };
+        let ScVal::Map(Some(hop)) = &hops[0] else {
+            panic!("hop is not a map");
+        };
+        // destination, pool, pool_type, source
+        assert_eq!(hop[2].val, variant("AmmConstProd", vec![]).unwrap());
+        assert_eq!(
+            hop[0].val,
+            variant(
+                "Issued",
+                vec![address(ISSUER).unwrap(), symbol("USDC").unwrap()]
+            )
+            .unwrap()
+        );
+        assert_eq!(hop[3].val, variant("Native", vec![]).unwrap());
+    }
+
+    #[test]
+    fn test_xdr_round_trips() {
+        let exported = export_route(&amm_route(), 50, 99, &ExportConfig::default()).unwrap();
+        let encoded = exported.to_xdr_base64().unwrap();
+        let decoded = ScVal::from_xdr_base64(encoded, Limits::none()).unwrap();
+        assert_eq!(decoded, exported.to_scval().unwrap());
+    }
+}
@@ -0,0 +1,916 @@
+//! In-memory liquidity graph
+//!
+//! Assets are nodes. Every venue — one side of an SDEX orderbook or a Soroban
+//! AMM pool — contributes directed edges between the assets it trades:
+//!
+//! - An [`OrderbookSide`] of offers selling `S` for `B` is a single edge
+//!   `B -> S` (the taker pays `B` and receives `S`).
+//! - An [`AmmPool`] holding `A`/`B` reserves is two edges, `A -> B` and `B -> A`.
+//!
+//! Venues are inserted, updated and removed as a whole, so the adjacency index
+//! always agrees with the venue table. Changes streamed from the indexer are
+//! applied as [`GraphDelta`]s: single offers are folded into their side's
+//! price levels and pool reserves are updated in place, so edges are only
+//! touched when a venue appears or disappears.
+
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::{BTreeSet, HashMap};
+use std::fmt;
+
+use crate::amount::{Price, BPS};
+use crate::delta::{GraphDelta, GraphUpdate, SdexOffer, Watermark};
+use crate::error::{Result, RoutingError};
+use crate::{amm, impact};
+
+/// Canonical Stellar asset identifier: "native" or "CODE:ISSUER"
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct AssetId(String);
+
+impl AssetId {
+    /// Create an asset identifier from its canonical string form
+    pub fn new(id: impl Into<String>) -> Self {
+        Self(id.into())
+    }
+
+    /// The native XLM asset
+    pub fn native() -> Self {
+        Self("native".to_string())
+    }
+
+    /// Credit asset identified by code and issuer
+    pub fn credit(code: &str, issuer: &str) -> Self {
+        Self(format!("{}:{}", code, issuer))
+    }
+
+    /// Canonical string form
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Whether this is the native XLM asset
+    pub fn is_native(&self) -> bool {
+        self.0 == "native"
+    }
+
+    /// Issuer account of a credit asset
+    pub fn issuer(&self) -> Option<&str> {
+        self.0.split_once(':').map(|(_, issuer)| issuer)
+    }
+}
+
+impl fmt::Display for AssetId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl From<&str> for AssetId {
+    fn from(s: &str) -> Self {
+        Self::new(s)
+    }
+}
+
+/// Liquidity source type, mirroring the router contract's `PoolType`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PoolType {
+    Sdex,
+    AmmConstProd,
+    AmmStable,
+}
+
+/// One price level of an SDEX orderbook side
+///
+/// The price is expressed the way Horizon does: `price_n / price_d` units of
+/// the buying asset per unit of the selling asset.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PriceLevel {
+    pub price_n: i64,
+    pub price_d: i64,
+    /// Amount of the selling asset offered at this price, in stroops
+    pub amount: i128,
+}
+
+impl PriceLevel {
+    /// The level's price as an exact rational
+    pub fn price(&self) -> Result<Price> {
+        Price::new(self.price_n as i128, self.price_d as i128)
+    }
+}
+
+/// All offers selling one asset for another, best price first
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OrderbookSide {
+    /// Asset the makers are selling (what the taker receives)
+    pub selling: AssetId,
+    /// Asset the makers are buying (what the taker pays)
+    pub buying: AssetId,
+    pub levels: Vec<PriceLevel>,
+}
+
+impl OrderbookSide {
+    /// Total amount of the selling asset on offer, in stroops
+    pub fn depth(&self) -> i128 {
+        self.levels.iter().map(|l| l.amount).sum()
+    }
+
+    /// Amount of the selling asset received for `amount_in` of the buying
+    /// asset, taking price levels in order
+    pub fn amount_out(&self, amount_in: i128) -> Result<i128> {
+        impact::fill_exact_in(self, amount_in).map(|fill| fill.amount_out)
+    }
+
+    /// Least amount of the buying asset that receives at least `amount_out`
+    /// of the selling asset
+    pub fn amount_in(&self, amount_out: i128) -> Result<i128> {
+        impact::fill_exact_out(self, amount_out).map(|fill| fill.amount_in)
+    }
+}
+
+/// A Soroban AMM pool
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AmmPool {
+    /// Pool (adapter) contract address
+    pub address: String,
+    /// `AmmConstProd` or `AmmStable`
+    pub pool_type: PoolType,
+    pub asset_a: AssetId,
+    pub asset_b: AssetId,
+    pub reserve_a: i128,
+    pub reserve_b: i128,
+    /// Swap fee in basis points (30 = 0.3%)
+    pub fee_bps: u32,
+    /// StableSwap amplification coefficient `A`; required for `AmmStable`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub amplification: Option<u32>,
+}
+
+impl AmmPool {
+    /// Reserves ordered as `(input, output)` for a swap starting at `from`
+    pub fn reserves_for(&self, from: &AssetId) -> Option<(i128, i128)> {
+        if *from == self.asset_a {
+            Some((self.reserve_a, self.reserve_b))
+        } else if *from == self.asset_b {
+            Some((self.reserve_b, self.reserve_a))
+        } else {
+            None
+        }
+    }
+
+    fn directed_reserves(&self, from: &AssetId) -> Result<(i128, i128)> {
+        self.reserves_for(from).ok_or_else(|| {
+            RoutingError::InvalidPair(format!("{} is not traded by pool {}", from, self.address))
+        })
+    }
+
+    /// Output for swapping `amount_in` of `from` through the pool
+    pub fn amount_out(&self, from: &AssetId, amount_in: i128) -> Result<i128> {
+        let (reserve_in, reserve_out) = self.directed_reserves(from)?;
+        match self.pool_type {
+            PoolType::AmmStable => amm::stable_swap_out(
+                amount_in,
+                reserve_in,
+                reserve_out,
+                self.amplification.unwrap_or(0),
+                self.fee_bps,
+            ),
+            _ => amm::constant_product_out(amount_in, reserve_in, reserve_out, self.fee_bps),
+        }
+    }
+
+    /// Input of `from` needed to receive at least `amount_out` of the other asset
+    pub fn amount_in(&self, from: &AssetId, amount_out: i128) -> Result<i128> {
+        let (reserve_in, reserve_out) = self.directed_reserves(from)?;
+        match self.pool_type {
+            PoolType::AmmStable => amm::stable_swap_in(
+                amount_out,
+                reserve_in,
+                reserve_out,
+                self.amplification.unwrap_or(0),
+                self.fee_bps,
+            ),
+            _ => amm::constant_product_in(amount_out, reserve_in, reserve_out, self.fee_bps),
+        }
+    }
+}
+
+/// Stable identifier of a venue in the graph
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum VenueId {
+    /// The SDEX orderbook side selling `selling` for `buying`
+    Sdex { selling: AssetId, buying: AssetId },
+    /// An AMM pool by contract address
+    Amm { address: String },
+}
+
+impl fmt::Display for VenueId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VenueId::Sdex { .. } => f.write_str("sdex"),
+            VenueId::Amm { address } => write!(f, "amm:{}", address),
+        }
+    }
+}
+
+/// A source of liquidity
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Venue {
+    Sdex(OrderbookSide),
+    Amm(AmmPool),
+}
+
+impl Venue {
+    pub fn id(&self) -> VenueId {
+        match self {
+            Venue::Sdex(side) => VenueId::Sdex {
+                selling: side.selling.clone(),
+                buying: side.buying.clone(),
+            },
+            Venue::Amm(pool) => VenueId::Amm {
+                address: pool.address.clone(),
+            },
+        }
+    }
+
+    pub fn pool_type(&self) -> PoolType {
+        match self {
+            Venue::Sdex(_) => PoolType::Sdex,
+            Venue::Amm(pool) => pool.pool_type,
+        }
+    }
+
+    /// Directed `(from, to)` edges this venue contributes to the graph
+    pub fn edges(&self) -> Vec<(&AssetId, &AssetId)> {
+        match self {
+            Venue::Sdex(side) => vec![(&side.buying, &side.selling)],
+            Venue::Amm(pool) => vec![
+                (&pool.asset_a, &pool.asset_b),
+                (&pool.asset_b, &pool.asset_a),
+            ],
+        }
+    }
+
+    /// Check the venue is well-formed before it is added to the graph
+    fn validate(&self) -> Result<()> {
+        match self {
+            Venue::Sdex(side) => {
+                if side.selling == side.buying {
+                    return Err(RoutingError::InvalidPair(format!(
+                        "orderbook side sells and buys {}",
+                        side.selling
+                    )));
+                }
+                if side
+                    .levels
+                    .iter()
+                    .any(|l| l.price_n <= 0 || l.price_d <= 0 || l.amount < 0)
+                {
+                    return Err(RoutingError::InvalidAmount(format!(
+                        "invalid price level in {}/{} orderbook",
+                        side.selling, side.buying
+                    )));
+                }
+            }
+            Venue::Amm(pool) => {
+                if pool.asset_a == pool.asset_b {
+                    return Err(RoutingError::InvalidPair(format!(
+                        "pool {} has identical assets",
+                        pool.address
+                    )));
+                }
+                if pool.pool_type == PoolType::Sdex {
+                    return Err(RoutingError::InvalidPair(format!(
+                        "pool {} cannot have pool type sdex",
+                        pool.address
+                    )));
+                }
+                if pool.reserve_a < 0 || pool.reserve_b < 0 || pool.fee_bps as i128 >= BPS {
+                    return Err(RoutingError::InvalidAmount(format!(
+                        "invalid reserves or fee for pool {}",
+                        pool.address
+                    )));
+                }
+                if pool.pool_type == PoolType::AmmStable && pool.amplification.unwrap_or(0) == 0 {
+                    return Err(RoutingError::InvalidAmount(format!(
+                        "stable pool {} needs an amplification coefficient",
+                        pool.address
+                    )));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A directed edge borrowed from the graph
+#[derive(Debug, Clone, Copy)]
+pub struct EdgeRef<'a> {
+    pub venue: &'a Venue,
+    pub from: &'a AssetId,
+    pub to: &'a AssetId,
+}
+
+impl EdgeRef<'_> {
+    /// Liquidity available on the output side of this edge, in stroops
+    pub fn depth(&self) -> i128 {
+        match self.venue {
+            Venue::Sdex(side) => side.depth(),
+            Venue::Amm(pool) => pool
+                .reserves_for(self.from)
+                .map(|(_, out)| out)
+                .unwrap_or(0),
+        }
+    }
+
+    /// Output received for `amount_in` of the input asset, in stroops
+    pub fn amount_out(&self, amount_in: i128) -> Result<i128> {
+        match self.venue {
+            Venue::Sdex(side) => side.amount_out(amount_in),
+            Venue::Amm(pool) => pool.amount_out(self.from, amount_in),
+        }
+    }
+
+    /// Swap fee charged by the venue, in basis points
+    pub fn fee_bps(&self) -> u32 {
+        match self.venue {
+            Venue::Sdex(_) => 0,
+            Venue::Amm(pool) => pool.fee_bps,
+        }
+    }
+
+    /// Output for `amount_in` with the venue's swap fee waived
+    pub fn amount_out_before_fees(&self, amount_in: i128) -> Result<i128> {
+        match self.venue {
+            Venue::Sdex(side) => side.amount_out(amount_in),
+            Venue::Amm(pool) => AmmPool {
+                fee_bps: 0,
+                ..pool.clone()
+            }
+            .amount_out(self.from, amount_in),
+        }
+    }
+
+    /// Marginal rate for an infinitesimal trade, in output per unit of input
+    /// and before fees
+    pub fn spot_price(&self) -> Result<Price> {
+        let empty = || {
+            RoutingError::InsufficientLiquidity(format!(
+                "no liquidity from {} to {}",
+                self.from, self.to
+            ))
+        };
+        match self.venue {
+            Venue::Sdex(side) => side
+                .levels
+                .iter()
+                .find(|l| l.amount > 0)
+                .ok_or_else(empty)?
+                .price()?
+                .invert(),
+            Venue::Amm(pool) => {
+                let (reserve_in, reserve_out) = pool.directed_reserves(self.from)?;
+                if reserve_in <= 0 || reserve_out <= 0 {
+                    return Err(empty());
+                }
+                match pool.pool_type {
+                    // No closed form; quote a trade too small to move the curve
+                    PoolType::AmmStable => {
+                        let probe = (reserve_in / 1_000_000).max(1);
+                        Price::new(self.amount_out_before_fees(probe)?, probe)
+                    }
+                    _ => Price::new(reserve_out, reserve_in),
+                }
+            }
+        }
+    }
+
+    /// Least input needed to receive at least `amount_out`, in stroops
+    pub fn amount_in(&self, amount_out: i128) -> Result<i128> {
+        match self.venue {
+            Venue::Sdex(side) => side.amount_in(amount_out),
+            Venue::Amm(pool) => pool.amount_in(self.from, amount_out),
+        }
+    }
+}
+
+/// Liquidity graph keyed by asset
+#[derive(Debug, Clone, Default)]
+pub struct LiquidityGraph {
+    venues: HashMap<VenueId, Venue>,
+    /// Outgoing venues per input asset; ordered so traversal is deterministic
+    adjacency: HashMap<AssetId, BTreeSet<VenueId>>,
+    /// Incoming venues per output asset, for searches run backwards
+    incoming: HashMap<AssetId, BTreeSet<VenueId>>,
+    /// Number of venues touching each asset; an asset is a node while > 0
+    asset_refs: HashMap<AssetId, usize>,
+    /// Offers folded into SDEX price levels, so later deltas can be undone
+    offers: HashMap<u64, SdexOffer>,
+    watermark: Watermark,
+}
+
+impl LiquidityGraph {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Insert a venue, or replace the existing venue with the same id
+    ///
+    /// Returns the previous venue, if any. Invalid venues are rejected without
+    /// modifying the graph. Replacing an orderbook side discards the offers
+    /// previously folded into it.
+    pub fn upsert_venue(&mut self, venue: Venue) -> Result<Option<Venue>> {
+        venue.validate()?;
+
+        let id = venue.id();
+        let previous = self.detach_venue(&id);
+        if previous.as_ref().is_none_or(|p| p.edges() != venue.edges()) {
+            self.watermark.topology += 1;
+        }
+        self.forget_offers(&id);
+        self.attach_venue(id, venue);
+        self.watermark.version += 1;
+
+        Ok(previous)
+    }
+
+    /// Remove a venue and all of its edges
+    pub fn remove_venue(&mut self, id: &VenueId) -> Option<Venue> {
+        let venue = self.detach_venue(id)?;
+        self.forget_offers(id);
+        self.watermark.version += 1;
+        self.watermark.topology += 1;
+        Some(venue)
+    }
+
+    /// Insert or update a single SDEX offer
+    ///
+    /// The offer's previous amount is taken out of its price level and the
+    /// new amount added to the level at its current price. An amount of zero
+    /// removes the offer.
+    pub fn upsert_offer(&mut self, offer: SdexOffer) -> Result<()> {
+        offer.validate()?;
+        let price = offer.price()?;
+
+        let withdrawn = self.withdraw_offer(offer.id)?.is_some();
+        if offer.amount == 0 {
+            if withdrawn {
+                self.watermark.version += 1;
+            }
+            return Ok(());
+        }
+
+        let id = offer.venue_id();
+        if !self.venues.contains_key(&id) {
+            let side = OrderbookSide {
+                selling: offer.selling.clone(),
+                buying: offer.buying.clone(),
+                levels: Vec::new(),
+            };
+            self.attach_venue(id.clone(), Venue::Sdex(side));
+            self.watermark.topology += 1;
+        }
+        let Some(Venue::Sdex(side)) = self.venues.get_mut(&id) else {
+            unreachable!("offer side was just attached")
+        };
+
+        // Levels are validated, so their prices always parse
+        let position = side.levels.binary_search_by(|level| {
+            level
+                .price()
+                .map_or(Ordering::Less, |level_price| level_price.cmp(&price))
+        });
+        match position {
+            Ok(idx) => {
+                let level = &mut side.levels[idx];
+                level.amount = level
+                    .amount
+                    .checked_add(offer.amount)
+                    .ok_or(RoutingError::Overflow)?;
+            }
+            Err(idx) => side.levels.insert(
+                idx,
+                PriceLevel {
+                    price_n: offer.price_n,
+                    price_d: offer.price_d,
+                    amount: offer.amount,
+                },
+            ),
+        }
+
+        self.offers.insert(offer.id, offer);
+        self.watermark.version += 1;
+        Ok(())
+    }
+
+    /// Remove a single SDEX offer, returning it if it was known
+    pub fn remove_offer(&mut self, id: u64) -> Result<Option<SdexOffer>> {
+        let offer = self.withdraw_offer(id)?;
+        if offer.is_some() {
+            self.watermark.version += 1;
+        }
+        Ok(offer)
+    }
+
+    /// Replace the reserves of a pool already in the graph
+    pub fn set_pool_reserves(
+        &mut self,
+        address: &str,
+        reserve_a: i128,
+        reserve_b: i128,
+    ) -> Result<()> {
+        let id = VenueId::Amm {
+            address: address.to_string(),
+        };
+        let Some(Venue::Amm(pool)) = self.venues.get_mut(&id) else {
+            return Err(RoutingError::InvalidPair(format!(
+                "unknown pool {}",
+                address
+            )));
+        };
+        if reserve_a < 0 || reserve_b < 0 {
+            return Err(RoutingError::InvalidAmount(format!(
+                "invalid reserves for pool {}",
+                address
+            )));
+        }
+        pool.reserve_a = reserve_a;
+        pool.reserve_b = reserve_b;
+        self.watermark.version += 1;
+        Ok(())
+    }
+
+    /// Apply one delta
+    pub fn apply_delta(&mut self, delta: &GraphDelta) -> Result<()> {
+        match delta {
+            GraphDelta::UpsertOffer(offer) => self.upsert_offer(offer.clone()),
+            GraphDelta::RemoveOffer { id } => self.remove_offer(*id).map(|_| ()),
+            GraphDelta::UpsertPool(pool) => self.upsert_venue(Venue::Amm(pool.clone())).map(|_| ()),
+            GraphDelta::PoolReserves {
+                address,
+                reserve_a,
+                reserve_b,
+            } => self.set_pool_reserves(address, *reserve_a, *reserve_b),
+            GraphDelta::RemovePool { address } => {
+                self.remove_venue(&VenueId::Amm {
+                    address: address.clone(),
+                });
+                Ok(())
+            }
+        }
+    }
+
+    /// Apply the deltas observed at one ledger, in order
+    ///
+    /// The update is all or nothing: if any delta fails the graph, and its
+    /// watermark, are left as they were, so the update can be retried once
+    /// the bad delta is fixed. Updates for a ledger older than the watermark
+    /// are rejected.
+    pub fn apply_update(&mut self, update: &GraphUpdate) -> Result<()> {
+        let mut next = self.clone();
+        next.apply_update_partial(update)?;
+        *self = next;
+        Ok(())
+    }
+
+    /// [`Self::apply_update`] without the rollback: deltas before a failing
+    /// one stay applied, so callers must discard the graph on error
+    pub(crate) fn apply_update_partial(&mut self, update: &GraphUpdate) -> Result<()> {
+        if update.ledger < self.watermark.ledger {
+            return Err(RoutingError::StaleUpdate(
+                update.ledger,
+                self.watermark.ledger,
+            ));
+        }
+        for delta in &update.deltas {
+            self.apply_delta(delta)?;
+        }
+        self.watermark.ledger = update.ledger;
+        Ok(())
+    }
+
+    /// Version and ledger the graph has reached
+    pub fn watermark(&self) -> Watermark {
+        self.watermark
+    }
+
+    /// Add a venue's edges; any venue with the same id must be detached first
+    fn attach_venue(&mut self, id: VenueId, venue: Venue) {
+        for (from, to) in venue.edges() {
+            self.adjacency
+                .entry(from.clone())
+                .or_default()
+                .insert(id.clone());
+            self.incoming
+                .entry(to.clone())
+                .or_default()
+                .insert(id.clone());
+            // Each venue contributes exactly one edge per input asset, so
+            // counting sources counts every asset once per venue.
+            *self.asset_refs.entry(from.clone()).or_default() += 1;
+            if !venue.edges().iter().any(|(f, _)| *f == to) {
+                *self.asset_refs.entry(to.clone()).or_default() += 1;
+            }
+        }
+        self.venues.insert(id, venue);
+    }
+
+    fn detach_venue(&mut self, id: &VenueId) -> Option<Venue> {
+        let venue = self.venues.remove(id)?;
+        for (from, to) in venue.edges() {
+            if let Some(out) = self.adjacency.get_mut(from) {
+                out.remove(id);
+                if out.is_empty() {
+                    self.adjacency.remove(from);
+                }
+            }
+            if let Some(into) = self.incoming.get_mut(to) {
+                into.remove(id);
+                if into.is_empty() {
+                    self.incoming.remove(to);
+                }
+            }
+            self.release_asset(from);
+            if !venue.edges().iter().any(|(f, _)| *f == to) {
+                self.release_asset(to);
+            }
+        }
+        Some(venue)
+    }
+
+    fn forget_offers(&mut self, id: &VenueId) {
+        if let VenueId::Sdex { selling, buying } = id {
+            if !self.offers.is_empty() {
+                self.offers
+                    .retain(|_, o| o.selling != *selling || o.buying != *buying);
+            }
+        }
+    }
+
+    /// Take a known offer's amount back out of its price level, dropping the
+    /// level and then the side once they are empty
+    fn withdraw_offer(&mut self, offer_id: u64) -> Result<Option<SdexOffer>> {
+        let Some(offer) = self.offers.get(&offer_id) else {
+            return Ok(None);
+        };
+        let id = offer.venue_id();
+        let price = offer.price()?;
+
+        if let Some(Venue::Sdex(side)) = self.venues.get_mut(&id) {
+            let mut emptied = None;
+            for (idx, level) in side.levels.iter_mut().enumerate() {
+                if level.price()? == price {
+                    level.amount -= offer.amount;
+                    if level.amount <= 0 {
+                        emptied = Some(idx);
+                    }
+                    break;
+                }
+            }
+            if let Some(idx) = emptied {
+                side.levels.remove(idx);
+            }
+            if side.levels.is_empty() {
+                self.detach_venue(&id);
+                self.watermark.topology += 1;
+            }
+        }
+        Ok(self.offers.remove(&offer_id))
+    }
+
+    fn release_asset(&mut self, asset: &AssetId) {
+        if let Some(count) = self.asset_refs.get_mut(asset) {
+            *count -= 1;
+            if *count == 0 {
+                self.asset_refs.remove(asset);
+            }
+        }
+    }
+
+    pub fn venue(&self, id: &VenueId) -> Option<&Venue> {
+        self.venues.get(id)
+    }
+
+    pub fn venues(&self) -> impl Iterator<Item = &Venue> {
+        self.venues.values()
+    }
+
+    pub fn venue_count(&self) -> usize {
+        self.venues.len()
+    }
+
+    pub fn contains_asset(&self, asset: &AssetId) -> bool {
+        self.asset_refs.contains_key(asset)
+    }
+
+    pub fn assets(&self) -> impl Iterator<Item = &AssetId> {
+        self.asset_refs.keys()
+    }
+
+    pub fn asset_count(&self) -> usize {
+        self.asset_refs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.venues.is_empty()
+    }
+
+    /// All edges leaving `from`
+    pub fn edges_from<'a>(&'a self, from: &AssetId) -> impl Iterator<Item = EdgeRef<'a>> + 'a {
+        let ids = self.adjacency.get_key_value(from);
+        ids.into_iter().flat_map(move |(from, ids)| {
+            ids.iter().filter_map(move |id| {
+                let venue = self.venues.get(id)?;
+                let to = venue
+                    .edges()
+                    .into_iter()
+                    .find(|(f, _)| *f == from)
+                    .map(|(_, t)| t)?;
+                Some(EdgeRef { venue, from, to })
+            })
+        })
+    }
+
+    /// All edges arriving at `to`
+    pub fn edges_into<'a>(&'a self, to: &AssetId) -> impl Iterator<Item = EdgeRef<'a>> + 'a {
+        let ids = self.incoming.get_key_value(to);
+        ids.into_iter().flat_map(move |(to, ids)| {
+            ids.iter().filter_map(move |id| {
+                let venue = self.venues.get(id)?;
+                let from = venue
+                    .edges()
+                    .into_iter()
+                    .find(|(_, t)| *t == to)
+                    .map(|(f, _)| f)?;
+                Some(EdgeRef { venue, from, to })
+            })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{usdc, xlm};
+
+    fn book(selling: AssetId, buying: AssetId) -> Venue {
+        Venue::Sdex(OrderbookSide {
+            selling,
+            buying,
+            levels: vec![PriceLevel {
+                price_n: 1,
+                price_d: 10,
+                amount: 10_000_000_000,
+            }],
+        })
+    }
+
+    fn pool(address: &str, a: AssetId, b: AssetId) -> Venue {
+        Venue::Amm(AmmPool {
+            address: address.to_string(),
+            pool_type: PoolType::AmmConstProd,
+            asset_a: a,
+            asset_b: b,
+            reserve_a: 1_000_000,
+            reserve_b: 2_000_000,
+            fee_bps: 30,
+            amplification: None,
+        })
+    }
+
+    #[test]
+    fn test_sdex_side_is_one_directed_edge() {
+        let mut graph = LiquidityGraph::new();
+        graph.upsert_venue(book(xlm(), usdc())).unwrap();
+
+        assert_eq!(graph.asset_count(), 2);
+        let from_usdc: Vec<_> = graph.edges_from(&usdc()).collect();
+        assert_eq!(from_usdc.len(), 1);
+        assert_eq!(from_usdc[0].to, &xlm());
+        assert_eq!(graph.edges_from(&xlm()).count(), 0);
+
+        let into_xlm: Vec<_> = graph.edges_into(&xlm()).collect();
+        assert_eq!(into_xlm.len(), 1);
+        assert_eq!(into_xlm[0].from, &usdc());
+        assert_eq!(graph.edges_into(&usdc()).count(), 0);
+    }
+
+    #[test]
+    fn test_amm_pool_is_two_directed_edges() {
+        let mut graph = LiquidityGraph::new();
+        graph.upsert_venue(pool("CPOOL", xlm(), usdc())).unwrap();
+
+        assert_eq!(graph.edges_from(&xlm()).count(), 1);
+        assert_eq!(graph.edges_from(&usdc()).count(), 1);
+        let edge = graph.edges_from(&usdc()).next().unwrap();
+        assert_eq!(edge.depth(), 1_000_000);
+    }
+
+    #[test]
+    fn test_upsert_replaces_existing_venue() {
+        let mut graph = LiquidityGraph::new();
+        graph.upsert_venue(pool("CPOOL", xlm(), usdc())).unwrap();
+
+        let mut updated = pool("CPOOL", xlm(), usdc());
+        if let Venue::Amm(p) = &mut updated {
+            p.reserve_a = 5;
+        }
+        let previous = graph.upsert_venue(updated).unwrap();
+
+        assert!(previous.is_some());
+        assert_eq!(graph.venue_count(), 1);
+        assert_eq!(graph.edges_from(&xlm()).count(), 1);
+        let id = VenueId::Amm {
+            address: "CPOOL".to_string(),
+        };
+        match graph.venue(&id) {
+            Some(Venue::Amm(p)) => assert_eq!(p.reserve_a, 5),
+            other => panic!("unexpected venue: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_remove_venue_prunes_orphan_assets() {
+        let mut graph = LiquidityGraph::new();
+        graph.upsert_venue(book(xlm(), usdc())).unwrap();
+        graph.upsert_venue(pool("CPOOL", xlm(), usdc())).unwrap();
+
+        let pool_id = VenueId::Amm {
+            address: "CPOOL".to_string(),
+        };
+        assert!(graph.remove_venue(&pool_id).is_some());
+        assert_eq!(graph.asset_count(), 2);
+        assert_eq!(graph.edges_into(&xlm()).count(), 1);
+        assert_eq!(graph.edges_into(&usdc()).count(), 0);
+
+        let book_id = book(xlm(), usdc()).id();
+        assert!(graph.remove_venue(&book_id).is_some());
+        assert!(graph.is_empty());
+        assert_eq!(graph.asset_count(), 0);
+    }
+
+    #[test]
+    fn test_invalid_venue_leaves_graph_untouched() {
+        let mut graph = LiquidityGraph::new();
+        graph.upsert_venue(pool("CPOOL", xlm(), usdc())).unwrap();
+
+        let mut bad = pool("CPOOL", xlm(), usdc());
+        if let Venue::Amm(p) = &mut bad {
+            p.reserve_b = -1;
+        }
+        assert!(graph.upsert_venue(bad).is_err());
+
+        assert!(graph.upsert_venue(book(xlm(), xlm())).is_err());
+        assert_eq!(graph.venue_count(), 1);
+        assert_eq!(graph.asset_count(), 2);
+    }
+
+    #[test]
+    fn test_orderbook_amount_out_walks_levels() {
+        // 10 XLM at 0.1 USDC, then 10 XLM at 0.2 USDC
+        let side = OrderbookSide {
+            selling: xlm(),
+            buying: usdc(),
+            levels: vec![
+                PriceLevel {
+                    price_n: 1,
+                    price_d: 10,
+                    amount: 100_000_000,
+                },
+                PriceLevel {
+                    price_n: 2,
+                    price_d: 10,
+                    amount: 100_000_000,
+                },
+            ],
+        };
+
+        // 1 USDC buys the whole first level; 1 more USDC buys 5 XLM at 0.2
+        assert_eq!(side.amount_out(10_000_000).unwrap(), 100_000_000);
+        assert_eq!(side.amount_out(20_000_000).unwrap(), 150_000_000);
+        assert!(matches!(
+            side.amount_out(40_000_000),
+            Err(RoutingError::InsufficientLiquidity(_))
+        ));
+    }
+
+    #[test]
+    fn test_amm_amount_out_matches_adapter_formula() {
+        let venue = pool("CPOOL", xlm(), usdc());
+        let Venue::Amm(pool) = &venue else {
+            unreachable!()
+        };
+        let amount_in = 10_000;
+        let expected = (amount_in * 997 * 2_000_000) / (1_000_000 * 1000 + amount_in * 997);
+        assert_eq!(pool.amount_out(&xlm(), amount_in).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_venue_id_display_matches_path_source() {
+        assert_eq!(book(xlm(), usdc()).id().to_string(), "sdex");
+        assert_eq!(pool("CPOOL", xlm(), usdc()).id().to_string(), "amm:CPOOL");
+    }
+}
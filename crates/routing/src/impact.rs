@@ -0,0 +1,311 @@
+//! SDEX price impact calculation
+//!
+//! Simulates a taker filling against an orderbook side, consuming price levels
+//! best-first, and reports the execution price against the top of the book.
+
+use serde::{Deserialize, Serialize};
+
+use crate::amount::{mul_div_ceil, mul_div_floor, Price, BPS};
+use crate::error::{Result, RoutingError};
+use crate::graph::{OrderbookSide, PriceLevel};
+
+/// Outcome of walking an orderbook side with a given input amount
+///
+/// Prices are quoted like Horizon's: units of the buying asset paid per unit
+/// of the selling asset received.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FillSimulation {
+    /// Input actually consumed, in stroops of the buying asset
+    pub amount_in: i128,
+    /// Output received, in stroops of the selling asset
+    pub amount_out: i128,
+    /// Input left over once the book was exhausted
+    pub unfilled: i128,
+    /// Number of price levels touched
+    pub levels_consumed: usize,
+    /// Price of the best level
+    pub best_price: Price,
+    /// Volume-weighted price actually paid
+    pub average_price: Price,
+    /// Price of the last level touched
+    pub marginal_price: Price,
+    /// Average price relative to the best price, in basis points
+    pub price_impact_bps: u32,
+}
+
+impl FillSimulation {
+    pub fn is_complete(&self) -> bool {
+        self.unfilled == 0
+    }
+}
+
+/// Walk `side` with `amount_in` of its buying asset
+///
+/// A book that cannot absorb the whole amount yields a partial fill with the
+/// remainder in [`FillSimulation::unfilled`]. An empty book is an
+/// [`RoutingError::InsufficientLiquidity`] error.
+pub fn simulate_fill(side: &OrderbookSide, amount_in: i128) -> Result<FillSimulation> {
+    if amount_in <= 0 {
+        return Err(RoutingError::InvalidAmount(format!(
+            "amount must be positive, got {}",
+            amount_in
+        )));
+    }
+    let best = side.levels.iter().find(|l| l.amount > 0).ok_or_else(|| {
+        RoutingError::InsufficientLiquidity(format!(
+            "{}/{} orderbook is empty",
+            side.selling, side.buying
+        ))
+    })?;
+
+    let mut remaining = amount_in;
+    let mut amount_out: i128 = 0;
+    let mut levels_consumed = 0;
+    let mut marginal = best;
+
+    for level in side.levels.iter().filter(|l| l.amount > 0) {
+        if remaining == 0 {
+            break;
+        }
+        let (n, d) = (level.price_n as i128, level.price_d as i128);
+        // Cost of the whole level, rounded up so the maker is never short
+        let level_cost = mul_div_ceil(level.amount, n, d)?;
+        let received = if remaining >= level_cost {
+            remaining -= level_cost;
+            level.amount
+        } else {
+            let received = mul_div_floor(remaining, d, n)?;
+            remaining = 0;
+            received
+        };
+        amount_out += received;
+        levels_consumed += 1;
+        marginal = level;
+    }
+
+    summarize(
+        best,
+        marginal,
+        amount_in - remaining,
+        amount_out,
+        remaining,
+        levels_consumed,
+    )
+}
+
+/// Like [`simulate_fill`], but a partial fill is an error
+pub fn fill_exact_in(side: &OrderbookSide, amount_in: i128) -> Result<FillSimulation> {
+    let fill = simulate_fill(side, amount_in)?;
+    if !fill.is_complete() {
+        return Err(RoutingError::InsufficientLiquidity(format!(
+            "{}/{} orderbook can absorb {} of {} stroops",
+            side.selling, side.buying, fill.amount_in, amount_in
+        )));
+    }
+    Ok(fill)
+}
+
+/// Walk `side` for exactly `amount_out` of its selling asset
+///
+/// [`FillSimulation::amount_in`] is the least input that makes
+/// [`simulate_fill`] deliver at least `amount_out`. A book too thin to
+/// deliver the amount is an [`RoutingError::InsufficientLiquidity`] error.
+pub fn fill_exact_out(side: &OrderbookSide, amount_out: i128) -> Result<FillSimulation> {
+    if amount_out <= 0 {
+        return Err(RoutingError::InvalidAmount(format!(
+            "amount must be positive, got {}",
+            amount_out
+        )));
+    }
+    let insufficient = |available: i128| {
+        RoutingError::InsufficientLiquidity(format!(
+            "{}/{} orderbook holds {} of {} stroops",
+            side.selling, side.buying, available, amount_out
+        ))
+    };
+    let best = side
+        .levels
+        .iter()
+        .find(|l| l.amount > 0)
+        .ok_or_else(|| insufficient(0))?;
+
+    let mut needed = amount_out;
+    let mut amount_in: i128 = 0;
+    let mut levels_consumed = 0;
+    let mut marginal = best;
+
+    for level in side.levels.iter().filter(|l| l.amount > 0) {
+        if needed == 0 {
+            break;
+        }
+        let (n, d) = (level.price_n as i128, level.price_d as i128);
+        let taken = needed.min(level.amount);
+        // Same rounding as the forward walk: a partial take of `taken` needs
+        // `ceil(taken * n / d)`, which the forward walk floors back to at
+        // least `taken`
+        amount_in = amount_in
+            .checked_add(mul_div_ceil(taken, n, d)?)
+            .ok_or(RoutingError::Overflow)?;
+        needed -= taken;
+        levels_consumed += 1;
+        marginal = level;
+    }
+    if needed > 0 {
+        return Err(insufficient(amount_out - needed));
+    }
+
+    summarize(best, marginal, amount_in, amount_out, 0, levels_consumed)
+}
+
+fn summarize(
+    best: &PriceLevel,
+    marginal: &PriceLevel,
+    consumed: i128,
+    amount_out: i128,
+    unfilled: i128,
+    levels_consumed: usize,
+) -> Result<FillSimulation> {
+    Ok(FillSimulation {
+        amount_in: consumed,
+        amount_out,
+        unfilled,
+        levels_consumed,
+        best_price: best.price()?,
+        average_price: if amount_out > 0 {
+            Price::new(consumed, amount_out)?
+        } else {
+            best.price()?
+        },
+        marginal_price: marginal.price()?,
+        price_impact_bps: impact_bps(best, consumed, amount_out)?,
+    })
+}
+
+/// `(consumed / out) / (n / d) - 1`, in basis points, computed exactly
+fn impact_bps(best: &PriceLevel, consumed: i128, out: i128) -> Result<u32> {
+    if out == 0 {
+        return Ok(0);
+    }
+    let paid = consumed
+        .checked_mul(best.price_d as i128)
+        .ok_or(RoutingError::Overflow)?;
+    let at_best = out
+        .checked_mul(best.price_n as i128)
+        .ok_or(RoutingError::Overflow)?;
+    if paid <= at_best {
+        return Ok(0);
+    }
+    let bps = mul_div_floor(paid - at_best, BPS, at_best)?;
+    Ok(bps.min(u32::MAX as i128) as u32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::AssetId;
+
+    fn level(price_n: i64, price_d: i64, amount: i128) -> PriceLevel {
+        PriceLevel {
+            price_n,
+            price_d,
+            amount,
+        }
+    }
+
+    /// Selling XLM for USDC: 10 XLM @ 0.10, 10 XLM @ 0.11, 20 XLM @ 0.125
+    fn ladder() -> OrderbookSide {
+        OrderbookSide {
+            selling: AssetId::native(),
+            buying: AssetId::credit("USDC", "GISSUER"),
+            levels: vec![
+                level(1, 10, 100_000_000),
+                level(11, 100, 100_000_000),
+                level(1, 8, 200_000_000),
+            ],
+        }
+    }
+
+    #[test]
+    fn test_fill_within_best_level_has_no_impact() {
+        let fill = simulate_fill(&ladder(), 5_000_000).unwrap();
+        assert_eq!(fill.amount_out, 50_000_000);
+        assert_eq!(fill.levels_consumed, 1);
+        assert_eq!(fill.price_impact_bps, 0);
+        assert_eq!(fill.marginal_price, Price::new(1, 10).unwrap());
+        assert!(fill.is_complete());
+    }
+
+    #[test]
+    fn test_fill_walks_multiple_levels() {
+        // 1.0 USDC clears level 1, 1.1 USDC clears level 2, 0.5 USDC buys 4 XLM
+        let fill = simulate_fill(&ladder(), 26_000_000).unwrap();
+        assert_eq!(fill.amount_out, 240_000_000);
+        assert_eq!(fill.levels_consumed, 3);
+        assert_eq!(fill.marginal_price, Price::new(1, 8).unwrap());
+        assert_eq!(fill.average_price, Price::new(13, 120).unwrap());
+        // average 2.6 / 24 = 0.108333.. => 833 bps above 0.10
+        assert_eq!(fill.price_impact_bps, 833);
+        assert!(fill.average_price > fill.best_price);
+        assert!(fill.average_price < fill.marginal_price);
+    }
+
+    #[test]
+    fn test_thin_book_reports_unfilled_remainder() {
+        // Whole book costs 1.0 + 1.1 + 2.5 = 4.6 USDC
+        let fill = simulate_fill(&ladder(), 50_000_000).unwrap();
+        assert_eq!(fill.amount_in, 46_000_000);
+        assert_eq!(fill.unfilled, 4_000_000);
+        assert_eq!(fill.amount_out, 400_000_000);
+        assert!(!fill.is_complete());
+
+        assert!(matches!(
+            fill_exact_in(&ladder(), 50_000_000),
+            Err(RoutingError::InsufficientLiquidity(_))
+        ));
+    }
+
+    #[test]
+    fn test_exact_out_is_minimal_input() {
+        let side = ladder();
+        for amount_out in [1, 7, 50_000_000, 100_000_000, 150_000_003, 400_000_000] {
+            let fill = fill_exact_out(&side, amount_out).unwrap();
+            assert_eq!(fill.amount_out, amount_out);
+            assert!(simulate_fill(&side, fill.amount_in).unwrap().amount_out >= amount_out);
+            if fill.amount_in > 1 {
+                let short = simulate_fill(&side, fill.amount_in - 1).unwrap();
+                assert!(short.amount_out < amount_out);
+            }
+        }
+
+        let fill = fill_exact_out(&side, 240_000_000).unwrap();
+        assert_eq!(fill.amount_in, 26_000_000);
+        assert_eq!(fill.levels_consumed, 3);
+        assert_eq!(fill.price_impact_bps, 833);
+
+        assert!(matches!(
+            fill_exact_out(&side, 400_000_001),
+            Err(RoutingError::InsufficientLiquidity(_))
+        ));
+    }
+
+    #[test]
+    fn test_empty_book_is_insufficient_liquidity() {
+        let mut side = ladder();
+        side.levels.clear();
+        assert!(matches!(
+            simulate_fill(&side, 1),
+            Err(RoutingError::InsufficientLiquidity(_))
+        ));
+    }
+
+    #[test]
+    fn test_larger_fills_never_have_less_impact() {
+        let side = ladder();
+        let mut last = 0;
+        for amount in (1..=46).map(|x| x * 1_000_000) {
+            let fill = simulate_fill(&side, amount).unwrap();
+            assert!(fill.price_impact_bps >= last);
+            last = fill.price_impact_bps;
+        }
+    }
+}
@@ -2,18 +2,314 @@
 //!
 //! Provides pathfinding algorithms for optimal swap routing across SDEX and Soroban AMM pools.
 
+pub mod amm;
+pub mod amount;
+pub mod arbitrage;
+pub mod cache;
+pub mod constraints;
+pub mod delta;
 pub mod error;
+pub mod export;
+pub mod graph;
+pub mod impact;
 pub mod pathfinder;
+pub mod score;
+pub mod simulate;
+pub mod split;
+#[cfg(test)]
+pub(crate) mod test_support;
+
+use std::sync::{Arc, PoisonError, RwLock};
+
+use arbitrage::{Arbitrage, ArbitrageConfig, ArbitrageDetector};
+use cache::{PathCache, PathCacheConfig};
+use constraints::RouteConstraints;
+use delta::{GraphUpdate, Watermark};
+use error::Result;
+use export::{ContractRoute, ExportConfig};
+use graph::{AssetId, LiquidityGraph, Venue, VenueId};
+use pathfinder::{Pathfinder, Route};
+use score::{RankedRoute, ResourcePricing};
+use split::{SplitConfig, SplitRoute, SplitRouter};
 
 /// Routing engine
+///
+/// Owns the liquidity graph. Writers replace venues under a write lock, and
+/// readers take cheap [`Arc`] snapshots, so a route is always computed against
+/// a single consistent view of the market.
 pub struct RoutingEngine {
-    // TODO: Implement routing engine
+    graph: RwLock<Arc<LiquidityGraph>>,
+    pathfinder: Pathfinder,
+    split: SplitConfig,
+    arbitrage: ArbitrageConfig,
+    paths: PathCache,
+    pricing: ResourcePricing,
+    export: ExportConfig,
 }
 
 impl RoutingEngine {
     /// Create a new routing engine instance
     pub fn new() -> Self {
-        Self {}
+        Self::with_graph(LiquidityGraph::new())
+    }
+
+    /// Create a routing engine over an existing graph
+    pub fn with_graph(graph: LiquidityGraph) -> Self {
+        Self {
+            graph: RwLock::new(Arc::new(graph)),
+            pathfinder: Pathfinder::default(),
+            split: SplitConfig::default(),
+            arbitrage: ArbitrageConfig::default(),
+            paths: PathCache::default(),
+            pricing: ResourcePricing::default(),
+            export: ExportConfig::default(),
+        }
+    }
+
+    /// Use a custom pathfinder configuration
+    pub fn with_pathfinder(mut self, pathfinder: Pathfinder) -> Self {
+        self.pathfinder = pathfinder;
+        self
+    }
+
+    /// Use a custom split optimizer configuration
+    pub fn with_split_config(mut self, config: SplitConfig) -> Self {
+        self.split = config;
+        self
+    }
+
+    /// Use a custom arbitrage detection configuration
+    pub fn with_arbitrage_config(mut self, config: ArbitrageConfig) -> Self {
+        self.arbitrage = config;
+        self
+    }
+
+    /// Use a custom path cache configuration
+    pub fn with_path_cache_config(mut self, config: PathCacheConfig) -> Self {
+        self.paths = PathCache::new(config);
+        self
+    }
+
+    /// Use custom resource fees when weighing routes by net output
+    pub fn with_resource_pricing(mut self, pricing: ResourcePricing) -> Self {
+        self.pricing = pricing;
+        self
+    }
+
+    /// Use custom router contract settings for [`Self::export_route`]
+    pub fn with_export_config(mut self, config: ExportConfig) -> Self {
+        self.export = config;
+        self
+    }
+
+    /// Candidate path cache used by [`Self::find_route`]
+    pub fn path_cache(&self) -> &PathCache {
+        &self.paths
+    }
+
+    /// Snapshot of the current liquidity graph
+    pub fn snapshot(&self) -> Arc<LiquidityGraph> {
+        self.graph
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    /// Insert or replace a venue
+    pub fn upsert_venue(&self, venue: Venue) -> Result<Option<Venue>> {
+        let mut guard = self.graph.write().unwrap_or_else(PoisonError::into_inner);
+        Arc::make_mut(&mut guard).upsert_venue(venue)
+    }
+
+    /// Remove a venue and its edges
+    pub fn remove_venue(&self, id: &VenueId) -> Option<Venue> {
+        let mut guard = self.graph.write().unwrap_or_else(PoisonError::into_inner);
+        // Avoid a copy-on-write clone when there is nothing to remove
+        guard.venue(id)?;
+        Arc::make_mut(&mut guard).remove_venue(id)
+    }
+
+    /// Apply the deltas observed at one ledger
+    ///
+    /// See [`LiquidityGraph::apply_update`]. The update is built on a copy
+    /// of the graph that replaces the current one only once every delta has
+    /// applied, so a failing update leaves the engine untouched. Snapshots
+    /// taken before the call keep the previous view, and its watermark.
+    pub fn apply_update(&self, update: &GraphUpdate) -> Result<()> {
+        let mut guard = self.graph.write().unwrap_or_else(PoisonError::into_inner);
+        let mut next = LiquidityGraph::clone(&guard);
+        next.apply_update_partial(update)?;
+        *guard = Arc::new(next);
+        Ok(())
+    }
+
+    /// Version and ledger of the current graph
+    ///
+    /// To report the ledger a route was computed at, read the watermark from
+    /// the same [`Self::snapshot`] the route was found on.
+    pub fn watermark(&self) -> Watermark {
+        self.snapshot().watermark()
+    }
+
+    /// Best route for selling `amount_in` of `from` for `to`
+    ///
+    /// The best few paths by output are weighed by output net of their
+    /// execution cost. Paths found for the pair at a similar amount are
+    /// re-simulated rather than searched for again, until the graph's
+    /// topology changes.
+    pub fn find_route(&self, from: &AssetId, to: &AssetId, amount_in: i128) -> Result<Route> {
+        self.find_route_on(&self.snapshot(), from, to, amount_in)
+    }
+
+    /// [`Self::find_route`] against a snapshot the caller already holds, so
+    /// several routes can be computed on one view of the market
+    pub fn find_route_on(
+        &self,
+        graph: &LiquidityGraph,
+        from: &AssetId,
+        to: &AssetId,
+        amount_in: i128,
+    ) -> Result<Route> {
+        let routes = self
+            .paths
+            .find_routes(&self.pathfinder, graph, from, to, amount_in)?;
+        Ok(score::best_net_route(graph, routes, &self.pricing).expect("at least one candidate"))
+    }
+
+    /// Best route for selling `amount_in` of `from` for `to` within `constraints`
+    pub fn find_constrained_route(
+        &self,
+        from: &AssetId,
+        to: &AssetId,
+        amount_in: i128,
+        constraints: &RouteConstraints,
+    ) -> Result<Route> {
+        let graph = self.snapshot();
+        self.best_net_route(&graph, from, to, amount_in, constraints)
+    }
+
+    /// [`Self::find_constrained_route`], also avoiding venues whose prices
+    /// form a cycle too profitable to be real
+    pub fn find_consistent_route(
+        &self,
+        from: &AssetId,
+        to: &AssetId,
+        amount_in: i128,
+        constraints: &RouteConstraints,
+    ) -> Result<Route> {
+        let graph = self.snapshot();
+        let mut constraints = constraints.clone();
+        constraints
+            .denied_venues
+            .extend(ArbitrageDetector::new(self.arbitrage.clone()).suspect_venues(&graph)?);
+        self.best_net_route(&graph, from, to, amount_in, &constraints)
+    }
+
+    /// Profitable cycles in the current graph, most profitable first
+    pub fn find_arbitrage(&self) -> Result<Vec<Arbitrage>> {
+        let graph = self.snapshot();
+        ArbitrageDetector::new(self.arbitrage.clone()).find_cycles(&graph)
+    }
+
+    /// Up to `k` distinct routes, ranked with a score breakdown for each
+    pub fn find_ranked_routes(
+        &self,
+        from: &AssetId,
+        to: &AssetId,
+        amount_in: i128,
+        k: usize,
+        constraints: &RouteConstraints,
+    ) -> Result<Vec<RankedRoute>> {
+        let graph = self.snapshot();
+        let routes =
+            self.pathfinder
+                .find_top_routes(&graph, from, to, amount_in, k, constraints)?;
+        score::rank_routes(&graph, routes, &self.pricing)
+    }
+
+    /// Cheapest route for buying exactly `amount_out` of `to` with `from`
+    pub fn find_route_exact_out(
+        &self,
+        from: &AssetId,
+        to: &AssetId,
+        amount_out: i128,
+    ) -> Result<Route> {
+        self.find_route_exact_out_on(&self.snapshot(), from, to, amount_out)
+    }
+
+    /// [`Self::find_route_exact_out`] against a snapshot the caller already
+    /// holds
+    pub fn find_route_exact_out_on(
+        &self,
+        graph: &LiquidityGraph,
+        from: &AssetId,
+        to: &AssetId,
+        amount_out: i128,
+    ) -> Result<Route> {
+        self.pathfinder
+            .find_best_route_exact_out(graph, from, to, amount_out)
+    }
+
+    /// Best allocation of `amount_in` of `from` across parallel routes to `to`
+    pub fn find_split_route(
+        &self,
+        from: &AssetId,
+        to: &AssetId,
+        amount_in: i128,
+        constraints: &RouteConstraints,
+    ) -> Result<SplitRoute> {
+        let graph = self.snapshot();
+        SplitRouter::new(self.pathfinder.clone(), self.split.clone()).find_split_route(
+            &graph,
+            from,
+            to,
+            amount_in,
+            constraints,
+        )
+    }
+
+    /// `route` in the router contract's format, accepting up to
+    /// `slippage_bps` less than the estimated output until ledger
+    /// `expires_at`
+    pub fn export_route(
+        &self,
+        route: &Route,
+        slippage_bps: u32,
+        expires_at: u64,
+    ) -> Result<ContractRoute> {
+        export::export_route(route, slippage_bps, expires_at, &self.export)
+    }
+
+    /// `route`, found for an exact output, in the router contract's format,
+    /// requiring at least `amount_out` after the router's fee until ledger
+    /// `expires_at`
+    pub fn export_route_exact_out(
+        &self,
+        route: &Route,
+        amount_out: i128,
+        expires_at: u64,
+    ) -> Result<ContractRoute> {
+        export::export_route_exact_out(route, amount_out, expires_at, &self.export)
+    }
+
+    /// The best of the top few routes by output, net of execution cost
+    fn best_net_route(
+        &self,
+        graph: &LiquidityGraph,
+        from: &AssetId,
+        to: &AssetId,
+        amount_in: i128,
+        constraints: &RouteConstraints,
+    ) -> Result<Route> {
+        let routes = self.pathfinder.find_top_routes(
+            graph,
+            from,
+            to,
+            amount_in,
+            self.paths.config().candidates.max(1),
+            constraints,
+        )?;
+        Ok(score::best_net_route(graph, routes, &self.pricing).expect("at least one candidate"))
     }
 }
 
@@ -22,3 +318,155 @@ impl Default for RoutingEngine {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use graph::{AmmPool, AssetId, PoolType};
+    use test_support::{asset, pool, usdc, xlm};
+
+    #[test]
+    fn test_snapshot_is_isolated_from_later_updates() {
+        let engine = RoutingEngine::new();
+        engine
+            .upsert_venue(pool("CPOOL", "XLM", "USDC", 1_000, 1_000))
+            .unwrap();
+
+        let before = engine.snapshot();
+        engine
+            .upsert_venue(pool("CPOOL", "XLM", "USDC", 2_000, 1_000))
+            .unwrap();
+        engine.remove_venue(&pool("CPOOL", "XLM", "USDC", 0, 1_000).id());
+
+        assert_eq!(before.venue_count(), 1);
+        assert!(engine.snapshot().is_empty());
+    }
+
+    #[test]
+    fn test_routes_on_a_held_snapshot_ignore_later_updates() {
+        let engine = RoutingEngine::new();
+        engine
+            .upsert_venue(pool("CPOOL", "XLM", "USDC", 1_000_000, 1_000))
+            .unwrap();
+        let (xlm, usdc) = (xlm(), usdc());
+
+        let before = engine.snapshot();
+        let expected = engine.find_route(&xlm, &usdc, 10_000).unwrap();
+        engine
+            .upsert_venue(pool("CPOOL", "XLM", "USDC", 4_000_000, 1_000))
+            .unwrap();
+
+        let held = engine.find_route_on(&before, &xlm, &usdc, 10_000).unwrap();
+        assert_eq!(held, expected);
+        assert!(engine.find_route(&xlm, &usdc, 10_000).unwrap().amount_out < held.amount_out);
+    }
+
+    #[test]
+    fn test_updates_advance_watermark_without_touching_snapshots() {
+        let engine = RoutingEngine::new();
+        engine
+            .upsert_venue(pool("CPOOL", "XLM", "USDC", 1_000, 1_000))
+            .unwrap();
+        let before = engine.snapshot();
+
+        let update = GraphUpdate::new(42).push(delta::GraphDelta::PoolReserves {
+            address: "CPOOL".to_string(),
+            reserve_a: 4_000,
+            reserve_b: 250,
+        });
+        engine.apply_update(&update).unwrap();
+
+        assert_eq!(
+            before.watermark(),
+            Watermark {
+                version: 1,
+                topology: 1,
+                ledger: 0
+            }
+        );
+        assert_eq!(
+            engine.watermark(),
+            Watermark {
+                version: 2,
+                topology: 1,
+                ledger: 42
+            }
+        );
+        let depth = |graph: &LiquidityGraph| {
+            let edge = graph.edges_from(&AssetId::native()).next().unwrap();
+            edge.depth()
+        };
+        assert_eq!(depth(&engine.snapshot()), 250);
+        assert_eq!(depth(&before), 1_000);
+    }
+
+    #[test]
+    fn test_failed_update_leaves_engine_untouched() {
+        let engine = RoutingEngine::new();
+        engine
+            .upsert_venue(pool("CPOOL", "XLM", "USDC", 1_000, 1_000))
+            .unwrap();
+        let before = engine.watermark();
+
+        let update = GraphUpdate::new(42)
+            .push(delta::GraphDelta::PoolReserves {
+                address: "CPOOL".to_string(),
+                reserve_a: 4_000,
+                reserve_b: 250,
+            })
+            .push(delta::GraphDelta::PoolReserves {
+                address: "CMISSING".to_string(),
+                reserve_a: 1,
+                reserve_b: 1,
+            });
+        assert!(engine.apply_update(&update).is_err());
+
+        assert_eq!(engine.watermark(), before);
+        let graph = engine.snapshot();
+        assert_eq!(graph.edges_from(&xlm()).next().unwrap().depth(), 1_000);
+    }
+
+    #[test]
+    fn test_routes_are_chosen_by_net_output() {
+        // A direct pool at 30bps, and a two-hop detour at 14bps per hop that
+        // pays slightly more before execution costs
+        let (xlm, eurc, usdc) = (xlm(), asset("EURC"), usdc());
+        let pool = |address: &str, a: &AssetId, b: &AssetId, reserve_b: i128, fee_bps: u32| {
+            Venue::Amm(AmmPool {
+                address: address.to_string(),
+                pool_type: PoolType::AmmConstProd,
+                asset_a: a.clone(),
+                asset_b: b.clone(),
+                reserve_a: 1_000_000_000_000_000,
+                reserve_b,
+                fee_bps,
+                amplification: None,
+            })
+        };
+        let venues = [
+            pool("CDIRECT", &xlm, &usdc, 100_000_000_000_000, 30),
+            pool("C1", &xlm, &eurc, 1_000_000_000_000_000, 14),
+            pool("C2", &eurc, &usdc, 100_000_000_000_000, 14),
+        ];
+        let (priced, free) = (
+            RoutingEngine::new(),
+            RoutingEngine::new().with_resource_pricing(ResourcePricing::free()),
+        );
+        for venue in venues {
+            priced.upsert_venue(venue.clone()).unwrap();
+            free.upsert_venue(venue).unwrap();
+        }
+
+        let constraints = RouteConstraints::default();
+        let amount = 100_000_000;
+        assert_eq!(free.find_route(&xlm, &usdc, amount).unwrap().hop_count(), 2);
+        assert_eq!(
+            priced.find_route(&xlm, &usdc, amount).unwrap().hop_count(),
+            1
+        );
+        let constrained = priced
+            .find_constrained_route(&xlm, &usdc, amount, &constraints)
+            .unwrap();
+        assert_eq!(constrained.hop_count(), 1);
+    }
+}
@@ -1,11 +1,1133 @@
 //! Pathfinding algorithms for swap routing
 //!
-// Placeholder for pathfinding implementation
-// TODO: Implement:
-// - Single-hop routing
-// - Multi-hop routing
-// - Price impact calculation
-// - Slippage computation
-
-// Temporary empty type to satisfy the compiler until implementation is added.
-pub struct Pathfinder;
+//! Routes are found with a hop-bounded Bellman-Ford over the liquidity graph.
+//! Edge outputs depend on the amount flowing through them, so instead of
+//! relaxing static weights each layer propagates the actual simulated amount
+//! reaching every asset and keeps the best one. Weaker paths are remembered,
+//! and followed across an orderbook only when it is too thin for the best.
+
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+use crate::constraints::RouteConstraints;
+use crate::error::{Result, RoutingError};
+use crate::graph::{AssetId, EdgeRef, LiquidityGraph, PoolType, Venue, VenueId};
+
+/// Default hop limit, matching the router contract's `MAX_HOPS`
+pub const DEFAULT_MAX_HOPS: usize = 4;
+
+/// Pathfinder configuration
+#[derive(Debug, Clone)]
+pub struct PathfinderConfig {
+    /// Maximum number of hops in a route
+    pub max_hops: usize,
+}
+
+impl Default for PathfinderConfig {
+    fn default() -> Self {
+        Self {
+            max_hops: DEFAULT_MAX_HOPS,
+        }
+    }
+}
+
+/// One swap within a route
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RouteHop {
+    pub venue: VenueId,
+    pub pool_type: PoolType,
+    pub from: AssetId,
+    pub to: AssetId,
+    /// Amount of `from` paid into the venue, in stroops
+    pub amount_in: i128,
+    /// Amount of `to` received from the venue, in stroops
+    pub amount_out: i128,
+}
+
+/// A complete route from source to destination asset
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Route {
+    pub hops: Vec<RouteHop>,
+    pub amount_in: i128,
+    pub amount_out: i128,
+}
+
+impl Route {
+    pub fn hop_count(&self) -> usize {
+        self.hops.len()
+    }
+
+    /// Assets visited by the route, source first
+    pub fn assets(&self) -> Vec<&AssetId> {
+        let mut assets: Vec<&AssetId> = self.hops.iter().map(|h| &h.from).collect();
+        if let Some(last) = self.hops.last() {
+            assets.push(&last.to);
+        }
+        assets
+    }
+}
+
+/// Partial path reaching `asset` with `amount`, linked to its predecessor
+#[derive(Debug)]
+struct Label {
+    asset: AssetId,
+    amount: i128,
+    /// Hops from the search's starting asset
+    hops: usize,
+    /// Whether the path has visited the constraints' required asset
+    via_required: bool,
+    parent: Option<usize>,
+    venue: Option<(VenueId, PoolType)>,
+}
+
+/// State of one layered search
+struct Layers<'a> {
+    labels: Vec<Label>,
+    /// Per asset, and per whether the path has visited the required asset
+    seen: HashMap<(AssetId, bool), Seen<'a>>,
+    goal: &'a AssetId,
+    keep: usize,
+    max_hops: usize,
+    direction: Direction,
+    /// Whether dominated paths are kept; only forward searches carry
+    /// amounts an orderbook can refuse for want of depth
+    spares: bool,
+    /// Labels created in the current layer
+    next: Vec<usize>,
+    /// Labels at `goal`
+    goals: Vec<usize>,
+}
+
+/// Search state at one asset
+#[derive(Debug, Default)]
+struct Seen<'a> {
+    /// The `keep` best amounts that have reached the asset, best first
+    best: Vec<i128>,
+    /// Dominated paths from earlier layers, best first
+    spares: Vec<Spare<'a>>,
+    /// Dominated paths from the current layer
+    fresh: Vec<Spare<'a>>,
+    /// Venues that refused every path tried so far
+    refused: Vec<Refusal<'a>>,
+}
+
+/// A path pruned by dominance
+///
+/// Orderbooks refuse amounts beyond their depth, so the best path to an
+/// asset can be too large for the venue after it while a weaker one fits.
+/// Spares are only followed across a venue that refused the better paths.
+#[derive(Debug, Clone, Copy)]
+struct Spare<'a> {
+    amount: i128,
+    origin: SpareOrigin<'a>,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum SpareOrigin<'a> {
+    /// A label dropped after it was created
+    Label(usize),
+    /// A crossing from the given label that was never turned into one
+    Crossing(usize, EdgeRef<'a>),
+}
+
+/// A venue that refused every path tried so far, retried when spares
+/// arrive in a later layer
+#[derive(Debug, Clone, Copy)]
+struct Refusal<'a> {
+    edge: EdgeRef<'a>,
+    /// Amounts from here up are known not to fit
+    limit: i128,
+    /// Whether the crossing would have visited the required asset
+    via_required: bool,
+}
+
+impl<'a> Layers<'a> {
+    /// Record `amount` reaching the far side of `edge` from label `parent`
+    fn reach(&mut self, parent: usize, edge: EdgeRef<'a>, amount: i128, via_required: bool) {
+        let reached = self.direction.reached(&edge);
+        let at = self
+            .seen
+            .entry((reached.clone(), via_required))
+            .or_default();
+        if at.best.len() == self.keep && !self.direction.improves(amount, at.best[self.keep - 1]) {
+            if self.spares && reached != self.goal {
+                at.fresh.push(Spare {
+                    amount,
+                    origin: SpareOrigin::Crossing(parent, edge),
+                });
+            }
+            return;
+        }
+        let pos = at
+            .best
+            .iter()
+            .position(|&b| self.direction.improves(amount, b))
+            .unwrap_or(at.best.len());
+        at.best.insert(pos, amount);
+        at.best.truncate(self.keep);
+
+        self.labels.push(Label {
+            asset: reached.clone(),
+            amount,
+            hops: self.labels[parent].hops + 1,
+            via_required,
+            parent: Some(parent),
+            venue: Some((edge.venue.id(), edge.venue.pool_type())),
+        });
+        let idx = self.labels.len() - 1;
+        if reached == self.goal {
+            self.goals.push(idx);
+        } else {
+            self.next.push(idx);
+        }
+    }
+
+    /// Close the current layer and return the labels to expand next
+    fn settle(&mut self) -> Vec<usize> {
+        let (keep, direction, spares) = (self.keep, self.direction, self.spares);
+
+        // Labels overtaken later in the same layer become spares
+        let mut next = std::mem::take(&mut self.next);
+        next.retain(|&idx| {
+            let label = &self.labels[idx];
+            let at = self
+                .seen
+                .get_mut(&(label.asset.clone(), label.via_required))
+                .expect("label asset is seen");
+            let leading =
+                at.best.len() < keep || !direction.improves(at.best[keep - 1], label.amount);
+            if !leading && spares {
+                at.fresh.push(Spare {
+                    amount: label.amount,
+                    origin: SpareOrigin::Label(idx),
+                });
+            }
+            leading
+        });
+
+        // Venues refused earlier get the best of this layer's spares that
+        // fits, then those spares join the ones from earlier layers
+        let mut retries = Vec::new();
+        for (group, at) in self.seen.iter_mut() {
+            if at.fresh.is_empty() {
+                continue;
+            }
+            at.fresh.sort_by(|a, b| direction.rank(a.amount, b.amount));
+            let fresh = &mut at.fresh;
+            at.refused.retain(|refusal| {
+                let venue = refusal.edge.venue.id();
+                match take_spare(
+                    &mut self.labels,
+                    fresh,
+                    group,
+                    &refusal.edge,
+                    refusal.limit,
+                    &venue,
+                ) {
+                    Some((spare, value)) => {
+                        retries.push((spare, *refusal, value));
+                        false
+                    }
+                    None => true,
+                }
+            });
+            at.spares.append(&mut at.fresh);
+            at.spares.sort_by(|a, b| direction.rank(a.amount, b.amount));
+        }
+        // In a stable order so results do not depend on hashing
+        retries.sort_by_key(|&(spare, _, _)| spare);
+        for (spare, refusal, value) in retries {
+            if self.labels[spare].hops < self.max_hops {
+                self.reach(spare, refusal.edge, value, refusal.via_required);
+            }
+        }
+
+        next.append(&mut self.next);
+        // Expand in a stable order so results do not depend on hashing
+        next.sort_by(|a, b| {
+            let (a, b) = (&self.labels[*a], &self.labels[*b]);
+            a.asset
+                .cmp(&b.asset)
+                .then_with(|| direction.rank(a.amount, b.amount))
+        });
+        next
+    }
+}
+
+/// Route finder over a [`LiquidityGraph`]
+#[derive(Debug, Clone, Default)]
+pub struct Pathfinder {
+    config: PathfinderConfig,
+}
+
+impl Pathfinder {
+    pub fn new(config: PathfinderConfig) -> Self {
+        Self { config }
+    }
+
+    pub fn config(&self) -> &PathfinderConfig {
+        &self.config
+    }
+
+    /// Find the route that delivers the most `to` for `amount_in` of `from`
+    ///
+    /// Ties are broken in favour of fewer hops.
+    pub fn find_best_route(
+        &self,
+        graph: &LiquidityGraph,
+        from: &AssetId,
+        to: &AssetId,
+        amount_in: i128,
+    ) -> Result<Route> {
+        self.find_constrained_route(graph, from, to, amount_in, &RouteConstraints::default())
+    }
+
+    /// [`Self::find_best_route`], keeping to `constraints`
+    pub fn find_constrained_route(
+        &self,
+        graph: &LiquidityGraph,
+        from: &AssetId,
+        to: &AssetId,
+        amount_in: i128,
+        constraints: &RouteConstraints,
+    ) -> Result<Route> {
+        let (labels, goals) = self.search(
+            graph,
+            from,
+            to,
+            amount_in,
+            constraints,
+            Direction::ExactIn,
+            1,
+        )?;
+        Ok(build_route(&labels, goals[0]))
+    }
+
+    /// The `k` best distinct routes for `amount_in` of `from`, best first
+    ///
+    /// Each asset keeps its `k` best partial paths rather than one, so the
+    /// result is exact for the hop limit except where the simple-path rule
+    /// forces a route through an asset's `k+1`-th best prefix.
+    pub fn find_top_routes(
+        &self,
+        graph: &LiquidityGraph,
+        from: &AssetId,
+        to: &AssetId,
+        amount_in: i128,
+        k: usize,
+        constraints: &RouteConstraints,
+    ) -> Result<Vec<Route>> {
+        let (labels, goals) = self.search(
+            graph,
+            from,
+            to,
+            amount_in,
+            constraints,
+            Direction::ExactIn,
+            k,
+        )?;
+        Ok(goals
+            .into_iter()
+            .map(|end| build_route(&labels, end))
+            .collect())
+    }
+
+    /// Find the route that delivers `amount_out` of `to` for the least `from`
+    ///
+    /// The search runs backwards from `to`, pricing each hop with the venue's
+    /// exact-output formula. The chosen path is then replayed forwards, so the
+    /// returned hop amounts are what execution yields; the final output is at
+    /// least `amount_out`.
+    pub fn find_best_route_exact_out(
+        &self,
+        graph: &LiquidityGraph,
+        from: &AssetId,
+        to: &AssetId,
+        amount_out: i128,
+    ) -> Result<Route> {
+        self.find_constrained_route_exact_out(
+            graph,
+            from,
+            to,
+            amount_out,
+            &RouteConstraints::default(),
+        )
+    }
+
+    /// [`Self::find_best_route_exact_out`], keeping to `constraints`
+    pub fn find_constrained_route_exact_out(
+        &self,
+        graph: &LiquidityGraph,
+        from: &AssetId,
+        to: &AssetId,
+        amount_out: i128,
+        constraints: &RouteConstraints,
+    ) -> Result<Route> {
+        let (labels, goals) = self.search(
+            graph,
+            to,
+            from,
+            amount_out,
+            constraints,
+            Direction::ExactOut,
+            1,
+        )?;
+        let path = build_reverse_route(&labels, goals[0]);
+        simulate_route(graph, &path, path.amount_in)
+    }
+
+    /// Layered search from `start` to `goal`
+    ///
+    /// Up to `keep` labels survive at each asset, so `keep > 1` yields that
+    /// many distinct paths to `goal`. Returns the labels and the indices of
+    /// the labels at `goal`, best first.
+    #[allow(clippy::too_many_arguments)]
+    fn search(
+        &self,
+        graph: &LiquidityGraph,
+        start: &AssetId,
+        goal: &AssetId,
+        amount: i128,
+        constraints: &RouteConstraints,
+        direction: Direction,
+        keep: usize,
+    ) -> Result<(Vec<Label>, Vec<usize>)> {
+        if amount <= 0 {
+            return Err(RoutingError::InvalidAmount(format!(
+                "amount must be positive, got {}",
+                amount
+            )));
+        }
+        if start == goal {
+            return Err(RoutingError::InvalidPair(format!(
+                "source and destination are both {}",
+                start
+            )));
+        }
+        let no_route = || match direction {
+            Direction::ExactIn => RoutingError::NoRoute(start.to_string(), goal.to_string()),
+            Direction::ExactOut => RoutingError::NoRoute(goal.to_string(), start.to_string()),
+        };
+        for asset in [start, goal] {
+            if !constraints.allows_asset(asset) {
+                return Err(RoutingError::InvalidPair(format!(
+                    "{} is excluded by the route constraints",
+                    asset
+                )));
+            }
+        }
+        if !graph.contains_asset(start) || !graph.contains_asset(goal) {
+            return Err(no_route());
+        }
+        let keep = keep.max(1);
+        let max_hops = constraints
+            .max_hops
+            .map_or(self.config.max_hops, |m| m.min(self.config.max_hops));
+        let required = constraints.required_asset.as_ref();
+
+        let mut layers = Layers {
+            labels: vec![Label {
+                asset: start.clone(),
+                amount,
+                hops: 0,
+                via_required: required.is_none_or(|r| r == start),
+                parent: None,
+                venue: None,
+            }],
+            seen: HashMap::new(),
+            goal,
+            keep,
+            max_hops,
+            direction,
+            spares: matches!(direction, Direction::ExactIn),
+            next: Vec::new(),
+            goals: Vec::new(),
+        };
+        layers
+            .seen
+            .entry((start.clone(), layers.labels[0].via_required))
+            .or_default()
+            .best
+            .push(amount);
+
+        let mut frontier = vec![0usize];
+        for _ in 0..max_hops {
+            for &label_idx in &frontier {
+                let label = &layers.labels[label_idx];
+                if label.hops >= max_hops {
+                    continue;
+                }
+                let (asset, amount) = (label.asset.clone(), label.amount);
+                let group = (asset.clone(), label.via_required);
+                for edge in direction.edges(graph, &asset) {
+                    if !constraints.allows_edge(&edge) {
+                        continue;
+                    }
+                    let venue_id = edge.venue.id();
+                    let reached = direction.reached(&edge);
+                    if path_contains(&layers.labels, label_idx, reached, &venue_id) {
+                        continue;
+                    }
+                    let via_required = group.1 || required == Some(reached);
+                    if reached == goal && !via_required {
+                        continue;
+                    }
+                    match direction.price(&edge, amount) {
+                        Ok(value) if value > 0 => {
+                            layers.reach(label_idx, edge, value, via_required)
+                        }
+                        Err(RoutingError::InsufficientLiquidity(_)) if layers.spares => {
+                            let at = layers.seen.get_mut(&group).expect("label asset is seen");
+                            let limit = refusal_limit(&edge, amount);
+                            match take_spare(
+                                &mut layers.labels,
+                                &mut at.spares,
+                                &group,
+                                &edge,
+                                limit,
+                                &venue_id,
+                            ) {
+                                Some((spare, value)) => {
+                                    layers.reach(spare, edge, value, via_required)
+                                }
+                                None => at.refused.push(Refusal {
+                                    edge,
+                                    limit,
+                                    via_required,
+                                }),
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+            }
+
+            frontier = layers.settle();
+            if frontier.is_empty() {
+                break;
+            }
+        }
+
+        let Layers {
+            labels, mut goals, ..
+        } = layers;
+        if goals.is_empty() {
+            return Err(no_route());
+        }
+        // Best amount first; ties go to fewer hops
+        goals.sort_by(|a, b| {
+            let (a, b) = (&labels[*a], &labels[*b]);
+            direction
+                .rank(a.amount, b.amount)
+                .then_with(|| a.hops.cmp(&b.hops))
+        });
+        goals.truncate(keep);
+        Ok((labels, goals))
+    }
+}
+
+/// Which way a search walks the graph
+#[derive(Debug, Clone, Copy)]
+enum Direction {
+    /// From the source, maximising the amount reaching each asset
+    ExactIn,
+    /// From the destination, minimising the amount needed at each asset
+    ExactOut,
+}
+
+impl Direction {
+    fn edges<'a>(self, graph: &'a LiquidityGraph, asset: &AssetId) -> Vec<EdgeRef<'a>> {
+        match self {
+            Direction::ExactIn => graph.edges_from(asset).collect(),
+            Direction::ExactOut => graph.edges_into(asset).collect(),
+        }
+    }
+
+    /// Asset a label moves to when following `edge`
+    fn reached<'a>(self, edge: &EdgeRef<'a>) -> &'a AssetId {
+        match self {
+            Direction::ExactIn => edge.to,
+            Direction::ExactOut => edge.from,
+        }
+    }
+
+    /// Amount at the reached asset given `amount` at the current one
+    fn price(self, edge: &EdgeRef<'_>, amount: i128) -> Result<i128> {
+        match self {
+            Direction::ExactIn => edge.amount_out(amount),
+            Direction::ExactOut => edge.amount_in(amount),
+        }
+    }
+
+    fn improves(self, candidate: i128, current: i128) -> bool {
+        match self {
+            Direction::ExactIn => candidate > current,
+            Direction::ExactOut => candidate < current,
+        }
+    }
+
+    /// Orders amounts best first
+    fn rank(self, a: i128, b: i128) -> Ordering {
+        match self {
+            Direction::ExactIn => b.cmp(&a),
+            Direction::ExactOut => a.cmp(&b),
+        }
+    }
+}
+
+/// Re-run the hops of `route` with a new input amount
+///
+/// Fails if a venue has disappeared from the graph or can no longer absorb
+/// the amount.
+pub fn simulate_route(graph: &LiquidityGraph, route: &Route, amount_in: i128) -> Result<Route> {
+    if amount_in <= 0 {
+        return Err(RoutingError::InvalidAmount(format!(
+            "amount must be positive, got {}",
+            amount_in
+        )));
+    }
+    let mut hops = Vec::with_capacity(route.hops.len());
+    let mut amount = amount_in;
+    for hop in &route.hops {
+        let edge = hop_edge(graph, hop)?;
+        let amount_out = edge.amount_out(amount)?;
+        hops.push(RouteHop {
+            amount_in: amount,
+            amount_out,
+            ..hop.clone()
+        });
+        amount = amount_out;
+    }
+    Ok(Route {
+        hops,
+        amount_in,
+        amount_out: amount,
+    })
+}
+
+/// The graph edge a hop runs over
+pub(crate) fn hop_edge<'a>(graph: &'a LiquidityGraph, hop: &RouteHop) -> Result<EdgeRef<'a>> {
+    graph
+        .edges_from(&hop.from)
+        .find(|e| e.to == &hop.to && e.venue.id() == hop.venue)
+        .ok_or_else(|| RoutingError::NoRoute(hop.from.to_string(), hop.to.to_string()))
+}
+
+/// Amounts below which `edge` may absorb an input, once it refused `refused`
+///
+/// An orderbook takes no more than buys out its depth. Pools have no such
+/// bound in closed form, so only amounts from `refused` up are ruled out.
+fn refusal_limit(edge: &EdgeRef<'_>, refused: i128) -> i128 {
+    match edge.venue {
+        Venue::Sdex(_) => edge
+            .amount_in(edge.depth())
+            .map_or(refused, |max| refused.min(max + 1)),
+        Venue::Amm(_) => refused,
+    }
+}
+
+/// The best of `spares`, sorted best first, below `limit` that `edge` can
+/// absorb
+///
+/// Returns the spare's label, created on first use, and the amount it
+/// delivers across the edge.
+fn take_spare(
+    labels: &mut Vec<Label>,
+    spares: &mut [Spare<'_>],
+    (asset, via_required): &(AssetId, bool),
+    edge: &EdgeRef<'_>,
+    limit: i128,
+    venue: &VenueId,
+) -> Option<(usize, i128)> {
+    let below = spares.partition_point(|s| s.amount >= limit);
+    for spare in &mut spares[below..] {
+        let value = match edge.amount_out(spare.amount) {
+            Ok(value) if value > 0 => value,
+            Err(RoutingError::InsufficientLiquidity(_)) => continue,
+            _ => return None,
+        };
+        let idx = match spare.origin {
+            SpareOrigin::Label(idx) => idx,
+            SpareOrigin::Crossing(parent, via) => {
+                labels.push(Label {
+                    asset: asset.clone(),
+                    amount: spare.amount,
+                    hops: labels[parent].hops + 1,
+                    via_required: *via_required,
+                    parent: Some(parent),
+                    venue: Some((via.venue.id(), via.venue.pool_type())),
+                });
+                spare.origin = SpareOrigin::Label(labels.len() - 1);
+                labels.len() - 1
+            }
+        };
+        if !path_contains(labels, idx, edge.to, venue) {
+            return Some((idx, value));
+        }
+    }
+    None
+}
+
+/// Whether the path ending at `label_idx` already visits `asset` or `venue`
+fn path_contains(labels: &[Label], label_idx: usize, asset: &AssetId, venue: &VenueId) -> bool {
+    let mut cursor = Some(label_idx);
+    while let Some(idx) = cursor {
+        let label = &labels[idx];
+        if label.asset == *asset {
+            return true;
+        }
+        if label.venue.as_ref().is_some_and(|(v, _)| v == venue) {
+            return true;
+        }
+        cursor = label.parent;
+    }
+    false
+}
+
+/// Route from a backward search, where `end` is the label at the source and
+/// each parent is one hop closer to the destination
+fn build_reverse_route(labels: &[Label], end: usize) -> Route {
+    let mut hops = Vec::new();
+    let mut cursor = end;
+    while let Some(parent) = labels[cursor].parent {
+        let label = &labels[cursor];
+        let next = &labels[parent];
+        let (venue, pool_type) = label.venue.clone().expect("non-root label has a venue");
+        hops.push(RouteHop {
+            venue,
+            pool_type,
+            from: label.asset.clone(),
+            to: next.asset.clone(),
+            amount_in: label.amount,
+            amount_out: next.amount,
+        });
+        cursor = parent;
+    }
+
+    Route {
+        amount_in: labels[end].amount,
+        amount_out: labels[cursor].amount,
+        hops,
+    }
+}
+
+fn build_route(labels: &[Label], end: usize) -> Route {
+    let mut hops = Vec::new();
+    let mut cursor = end;
+    while let Some(parent) = labels[cursor].parent {
+        let label = &labels[cursor];
+        let prev = &labels[parent];
+        let (venue, pool_type) = label.venue.clone().expect("non-root label has a venue");
+        hops.push(RouteHop {
+            venue,
+            pool_type,
+            from: prev.asset.clone(),
+            to: label.asset.clone(),
+            amount_in: prev.amount,
+            amount_out: label.amount,
+        });
+        cursor = parent;
+    }
+    hops.reverse();
+
+    Route {
+        amount_in: labels[cursor].amount,
+        amount_out: labels[end].amount,
+        hops,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::{OrderbookSide, PriceLevel};
+    use crate::test_support::{asset, graph, pool, ISSUER};
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_routes_through_intermediate_asset() {
+        // No direct EXO/USDC market; EXO -> XLM -> USDC
+        let graph = graph(vec![
+            pool("C1", "EXO", "XLM", 1_000_000_000, 1_000_000_000),
+            pool("C2", "XLM", "USDC", 1_000_000_000, 100_000_000),
+        ]);
+
+        let route = Pathfinder::default()
+            .find_best_route(&graph, &asset("EXO"), &asset("USDC"), 1_000_000)
+            .unwrap();
+
+        assert_eq!(route.hop_count(), 2);
+        assert_eq!(
+            route.assets(),
+            vec![&asset("EXO"), &asset("XLM"), &asset("USDC")]
+        );
+        assert_eq!(route.hops[0].amount_in, 1_000_000);
+        assert_eq!(route.hops[0].amount_out, route.hops[1].amount_in);
+        assert_eq!(route.hops[1].amount_out, route.amount_out);
+        assert!(route.amount_out > 0);
+    }
+
+    #[test]
+    fn test_prefers_better_output_over_fewer_hops() {
+        let graph = graph(vec![
+            // Thin direct pool
+            pool("CDIRECT", "XLM", "USDC", 10_000_000, 1_000_000),
+            // Deep two-hop path
+            pool("C1", "XLM", "EURC", 10_000_000_000, 10_000_000_000),
+            pool("C2", "EURC", "USDC", 10_000_000_000, 1_000_000_000),
+        ]);
+
+        let route = Pathfinder::default()
+            .find_best_route(&graph, &asset("XLM"), &asset("USDC"), 10_000_000)
+            .unwrap();
+
+        assert_eq!(route.hop_count(), 2);
+        assert_eq!(route.hops[0].venue.to_string(), "amm:C1");
+    }
+
+    #[test]
+    fn test_hop_limit_is_respected() {
+        let graph = graph(vec![
+            pool("C1", "A", "B", 1_000_000, 1_000_000),
+            pool("C2", "B", "C", 1_000_000, 1_000_000),
+            pool("C3", "C", "D", 1_000_000, 1_000_000),
+        ]);
+
+        let two_hops = Pathfinder::new(PathfinderConfig { max_hops: 2 });
+        assert!(matches!(
+            two_hops.find_best_route(&graph, &asset("A"), &asset("D"), 1_000),
+            Err(RoutingError::NoRoute(_, _))
+        ));
+
+        let route = Pathfinder::default()
+            .find_best_route(&graph, &asset("A"), &asset("D"), 1_000)
+            .unwrap();
+        assert_eq!(route.hop_count(), 3);
+    }
+
+    #[test]
+    fn test_sdex_orderbook_hop() {
+        let book = Venue::Sdex(OrderbookSide {
+            selling: asset("USDC"),
+            buying: asset("XLM"),
+            levels: vec![PriceLevel {
+                price_n: 10,
+                price_d: 1,
+                amount: 1_000_000_000,
+            }],
+        });
+        let graph = graph(vec![book]);
+
+        let route = Pathfinder::default()
+            .find_best_route(&graph, &asset("XLM"), &asset("USDC"), 100_000_000)
+            .unwrap();
+        assert_eq!(route.hops[0].pool_type, PoolType::Sdex);
+        assert_eq!(route.amount_out, 10_000_000);
+
+        // Books are one-directional
+        assert!(Pathfinder::default()
+            .find_best_route(&graph, &asset("USDC"), &asset("XLM"), 1_000)
+            .is_err());
+    }
+
+    #[test]
+    fn test_weaker_prefix_used_when_best_overflows_book() {
+        // The direct pool brings more XLM to the book than it can fill; the
+        // detour through BTC brings less, and fits
+        let book = Venue::Sdex(OrderbookSide {
+            selling: asset("USDC"),
+            buying: asset("XLM"),
+            levels: vec![PriceLevel {
+                price_n: 10,
+                price_d: 1,
+                amount: 8_500_000,
+            }],
+        });
+        let graph = graph(vec![
+            pool("C1", "EXO", "XLM", 1_000_000_000, 1_000_000_000),
+            pool("C2", "EXO", "BTC", 1_000_000_000, 1_000_000_000),
+            pool("C3", "BTC", "XLM", 1_000_000_000, 1_000_000_000),
+            book,
+        ]);
+
+        let route = Pathfinder::default()
+            .find_best_route(&graph, &asset("EXO"), &asset("USDC"), 100_000_000)
+            .unwrap();
+        let venues: Vec<String> = route.hops.iter().map(|h| h.venue.to_string()).collect();
+        assert_eq!(venues, vec!["amm:C2", "amm:C3", "sdex"]);
+    }
+
+    #[test]
+    fn test_exact_out_finds_minimum_input() {
+        let graph = graph(vec![
+            pool("C1", "EXO", "XLM", 1_000_000_000, 1_000_000_000),
+            pool("C2", "XLM", "USDC", 1_000_000_000, 100_000_000),
+            pool("C3", "EXO", "USDC", 100_000_000, 5_000_000),
+        ]);
+        let finder = Pathfinder::default();
+        let want = 1_000_000;
+
+        let route = finder
+            .find_best_route_exact_out(&graph, &asset("EXO"), &asset("USDC"), want)
+            .unwrap();
+        assert_eq!(
+            route.assets(),
+            vec![&asset("EXO"), &asset("XLM"), &asset("USDC")]
+        );
+        assert!(route.amount_out >= want);
+        assert_eq!(route.hops[0].amount_out, route.hops[1].amount_in);
+
+        // One stroop less no longer delivers the target
+        let short = simulate_route(&graph, &route, route.amount_in - 1).unwrap();
+        assert!(short.amount_out < want);
+
+        // And selling that input forward finds the same path
+        let forward = finder
+            .find_best_route(&graph, &asset("EXO"), &asset("USDC"), route.amount_in)
+            .unwrap();
+        assert_eq!(forward, route);
+    }
+
+    #[test]
+    fn test_exact_out_through_orderbook() {
+        let book = Venue::Sdex(OrderbookSide {
+            selling: asset("USDC"),
+            buying: asset("XLM"),
+            levels: vec![
+                PriceLevel {
+                    price_n: 10,
+                    price_d: 1,
+                    amount: 10_000_000,
+                },
+                PriceLevel {
+                    price_n: 11,
+                    price_d: 1,
+                    amount: 10_000_000,
+                },
+            ],
+        });
+        let graph = graph(vec![book]);
+        let finder = Pathfinder::default();
+
+        let route = finder
+            .find_best_route_exact_out(&graph, &asset("XLM"), &asset("USDC"), 15_000_000)
+            .unwrap();
+        assert_eq!(route.amount_in, 155_000_000);
+        assert_eq!(route.amount_out, 15_000_000);
+
+        assert!(matches!(
+            finder.find_best_route_exact_out(&graph, &asset("XLM"), &asset("USDC"), 20_000_001),
+            Err(RoutingError::NoRoute(_, _))
+        ));
+        assert!(matches!(
+            finder.find_best_route_exact_out(&graph, &asset("USDC"), &asset("XLM"), 1_000),
+            Err(RoutingError::NoRoute(from, to)) if from == asset("USDC").to_string() && to == "native"
+        ));
+    }
+
+    #[test]
+    fn test_top_routes_are_distinct_and_ordered() {
+        let graph = graph(vec![
+            pool("C1", "XLM", "USDC", 1_000_000_000, 100_000_000),
+            pool("C2", "XLM", "USDC", 500_000_000, 50_000_000),
+            pool("C3", "XLM", "EXO", 1_000_000_000, 1_000_000_000),
+            pool("C4", "EXO", "USDC", 1_000_000_000, 100_000_000),
+        ]);
+        let finder = Pathfinder::default();
+
+        let routes = finder
+            .find_top_routes(
+                &graph,
+                &asset("XLM"),
+                &asset("USDC"),
+                1_000_000,
+                10,
+                &RouteConstraints::default(),
+            )
+            .unwrap();
+        assert_eq!(routes.len(), 3);
+        assert!(routes
+            .windows(2)
+            .all(|w| w[0].amount_out >= w[1].amount_out));
+        let best = finder
+            .find_best_route(&graph, &asset("XLM"), &asset("USDC"), 1_000_000)
+            .unwrap();
+        assert_eq!(routes[0], best);
+
+        let paths: HashSet<Vec<VenueId>> = routes
+            .iter()
+            .map(|r| r.hops.iter().map(|h| h.venue.clone()).collect())
+            .collect();
+        assert_eq!(paths.len(), 3);
+
+        let top_two = finder
+            .find_top_routes(
+                &graph,
+                &asset("XLM"),
+                &asset("USDC"),
+                1_000_000,
+                2,
+                &RouteConstraints::default(),
+            )
+            .unwrap();
+        assert_eq!(top_two, routes[..2]);
+    }
+
+    #[test]
+    fn test_simulate_route_requotes_same_path() {
+        let graph = graph(vec![
+            pool("C1", "EXO", "XLM", 1_000_000_000, 1_000_000_000),
+            pool("C2", "XLM", "USDC", 1_000_000_000, 100_000_000),
+        ]);
+        let finder = Pathfinder::default();
+        let small = finder
+            .find_best_route(&graph, &asset("EXO"), &asset("USDC"), 1_000)
+            .unwrap();
+
+        let large = simulate_route(&graph, &small, 1_000_000).unwrap();
+        let direct = finder
+            .find_best_route(&graph, &asset("EXO"), &asset("USDC"), 1_000_000)
+            .unwrap();
+        assert_eq!(large, direct);
+    }
+
+    #[test]
+    fn test_constraints_deny_venues_and_assets() {
+        let graph = graph(vec![
+            pool("C1", "XLM", "USDC", 1_000_000_000, 100_000_000),
+            pool("C2", "XLM", "USDC", 1_000_000, 50_000),
+            pool("C3", "XLM", "EXO", 1_000_000_000, 1_000_000_000),
+            pool("C4", "EXO", "USDC", 1_000_000_000, 100_000_000),
+        ]);
+        let finder = Pathfinder::default();
+        let route = |constraints: RouteConstraints| {
+            finder
+                .find_constrained_route(&graph, &asset("XLM"), &asset("USDC"), 1_000, &constraints)
+                .map(|r| {
+                    r.hops
+                        .iter()
+                        .map(|h| h.venue.to_string())
+                        .collect::<Vec<_>>()
+                })
+        };
+        let c1 = VenueId::Amm {
+            address: "C1".to_string(),
+        };
+
+        assert_eq!(route(RouteConstraints::new()).unwrap(), vec!["amm:C1"]);
+        assert_eq!(
+            route(RouteConstraints::new().deny_venue(c1.clone())).unwrap(),
+            vec!["amm:C3", "amm:C4"]
+        );
+        assert_eq!(
+            route(
+                RouteConstraints::new()
+                    .deny_venue(c1)
+                    .deny_asset(asset("EXO"))
+            )
+            .unwrap(),
+            vec!["amm:C2"]
+        );
+        assert!(matches!(
+            route(RouteConstraints::new().deny_issuer(ISSUER)),
+            Err(RoutingError::InvalidPair(_))
+        ));
+    }
+
+    #[test]
+    fn test_constraints_require_asset_and_limit_hops() {
+        let graph = graph(vec![
+            pool("C1", "XLM", "USDC", 1_000_000_000, 100_000_000),
+            pool("C2", "XLM", "EXO", 1_000_000_000, 1_000_000_000),
+            pool("C3", "EXO", "BTC", 1_000_000_000, 1_000_000_000),
+            pool("C4", "BTC", "USDC", 1_000_000_000, 100_000_000),
+        ]);
+        let finder = Pathfinder::default();
+        let via_btc = RouteConstraints::new().require_asset(asset("BTC"));
+
+        let route = finder
+            .find_constrained_route(&graph, &asset("XLM"), &asset("USDC"), 1_000, &via_btc)
+            .unwrap();
+        assert_eq!(route.hop_count(), 3);
+        assert!(route.assets().contains(&&asset("BTC")));
+
+        let too_short = via_btc.max_hops(2);
+        assert!(matches!(
+            finder.find_constrained_route(&graph, &asset("XLM"), &asset("USDC"), 1_000, &too_short),
+            Err(RoutingError::NoRoute(_, _))
+        ));
+
+        let exact_out = finder
+            .find_constrained_route_exact_out(
+                &graph,
+                &asset("XLM"),
+                &asset("USDC"),
+                1_000,
+                &RouteConstraints::new().require_asset(asset("EXO")),
+            )
+            .unwrap();
+        assert_eq!(exact_out.hop_count(), 3);
+    }
+
+    #[test]
+    fn test_constraints_pool_types_and_min_liquidity() {
+        let book = Venue::Sdex(OrderbookSide {
+            selling: asset("USDC"),
+            buying: asset("XLM"),
+            levels: vec![PriceLevel {
+                price_n: 9,
+                price_d: 1,
+                amount: 1_000_000,
+            }],
+        });
+        let graph = graph(vec![
+            book,
+            pool("C1", "XLM", "USDC", 1_000_000_000, 100_000_000),
+        ]);
+        let finder = Pathfinder::default();
+        let pool_type = |constraints: RouteConstraints| {
+            finder
+                .find_constrained_route(&graph, &asset("XLM"), &asset("USDC"), 1_000, &constraints)
+                .unwrap()
+                .hops[0]
+                .pool_type
+        };
+
+        assert_eq!(pool_type(RouteConstraints::new()), PoolType::Sdex);
+        assert_eq!(
+            pool_type(RouteConstraints::new().allow_pool_types([PoolType::AmmConstProd])),
+            PoolType::AmmConstProd
+        );
+        assert_eq!(
+            pool_type(RouteConstraints::new().min_liquidity(10_000_000)),
+            PoolType::AmmConstProd
+        );
+    }
+
+    #[test]
+    fn test_no_route_and_invalid_input() {
+        let graph = graph(vec![
+            pool("C1", "A", "B", 1_000_000, 1_000_000),
+            pool("C2", "C", "D", 1_000_000, 1_000_000),
+        ]);
+        let finder = Pathfinder::default();
+
+        assert!(matches!(
+            finder.find_best_route(&graph, &asset("A"), &asset("D"), 1_000),
+            Err(RoutingError::NoRoute(_, _))
+        ));
+        assert!(matches!(
+            finder.find_best_route(&graph, &asset("A"), &asset("Z"), 1_000),
+            Err(RoutingError::NoRoute(_, _))
+        ));
+        assert!(matches!(
+            finder.find_best_route(&graph, &asset("A"), &asset("B"), 0),
+            Err(RoutingError::InvalidAmount(_))
+        ));
+        assert!(matches!(
+            finder.find_best_route(&graph, &asset("A"), &asset("A"), 1_000),
+            Err(RoutingError::InvalidPair(_))
+        ));
+    }
+}
@@ -0,0 +1,444 @@
+//! Route scoring and ranking
+//!
+//! Breaks a route's result down into the parts a user can reason about: what
+//! the venues would pay before fees, what the fees took, how far the trade
+//! moved prices, and what the router contract will spend executing it.
+//!
+//! Routes are ranked by output net of that execution cost, so an extra hop
+//! has to earn more than the network charges for running it.
+
+use serde::{Deserialize, Serialize};
+
+use crate::amount::{mul_div_floor, Price, BPS};
+use crate::error::Result;
+use crate::graph::{AssetId, LiquidityGraph};
+use crate::pathfinder::{hop_edge, Route};
+
+/// CPU instructions per hop, matching the router contract
+pub const BASE_CPU_PER_HOP: u64 = 5_000_000;
+
+/// Cross-contract call overhead per hop, matching the router contract
+pub const CCI_OVERHEAD: u64 = 2_000_000;
+
+/// Instruction budget a transaction must stay under
+pub const CPU_LIMIT: u64 = 100_000_000;
+
+/// Soroban resources needed to execute a route, as the router contract's
+/// `estimate_resources` computes them
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ResourceEstimate {
+    pub estimated_cpu: u64,
+    pub storage_reads: u32,
+    pub storage_writes: u32,
+    pub events: u32,
+    pub will_succeed: bool,
+}
+
+impl ResourceEstimate {
+    pub fn for_hops(hops: usize) -> Self {
+        let hops = hops as u64;
+        let estimated_cpu = BASE_CPU_PER_HOP * hops + CCI_OVERHEAD * hops;
+        Self {
+            estimated_cpu,
+            // Instance config, one pool check per hop, and the nonce
+            storage_reads: 1 + hops as u32 + 1,
+            storage_writes: 1,
+            events: 1,
+            will_succeed: estimated_cpu < CPU_LIMIT,
+        }
+    }
+
+    /// Resource fee for executing the route, in stroops of XLM
+    pub fn fee(&self, pricing: &ResourcePricing) -> i128 {
+        (self.estimated_cpu.div_ceil(10_000) as i128)
+            .saturating_mul(pricing.per_10k_instructions)
+            .saturating_add(self.storage_reads as i128 * pricing.per_read)
+            .saturating_add(self.storage_writes as i128 * pricing.per_write)
+            .saturating_add(self.events as i128 * pricing.per_event)
+    }
+}
+
+/// What the network charges for Soroban resources, in stroops of XLM
+///
+/// Defaults follow the mainnet fee schedule. The inclusion fee is the same
+/// for every route and is left out.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ResourcePricing {
+    pub per_10k_instructions: i128,
+    /// Per ledger entry read
+    pub per_read: i128,
+    /// Per ledger entry written
+    pub per_write: i128,
+    pub per_event: i128,
+}
+
+impl Default for ResourcePricing {
+    fn default() -> Self {
+        Self {
+            per_10k_instructions: 25,
+            per_read: 6_250,
+            per_write: 10_000,
+            per_event: 10_000,
+        }
+    }
+}
+
+impl ResourcePricing {
+    /// Resources cost nothing, so routes rank by output alone
+    pub fn free() -> Self {
+        Self {
+            per_10k_instructions: 0,
+            per_read: 0,
+            per_write: 0,
+            per_event: 0,
+        }
+    }
+}
+
+/// Spot rate from XLM into `to`, taken from the best venue trading the two
+/// directly
+pub fn xlm_rate(graph: &LiquidityGraph, to: &AssetId) -> Option<Price> {
+    if to.is_native() {
+        return Price::new(1, 1).ok();
+    }
+    graph
+        .edges_into(to)
+        .filter(|edge| edge.from.is_native())
+        .filter_map(|edge| edge.spot_price().ok())
+        .max()
+}
+
+/// Execution cost of a route with `hops` hops, in stroops of `to`
+///
+/// Zero when no venue quotes XLM against `to` directly, in which case routes
+/// rank by output alone.
+pub fn execution_cost(
+    graph: &LiquidityGraph,
+    to: &AssetId,
+    hops: usize,
+    pricing: &ResourcePricing,
+) -> i128 {
+    let fee = ResourceEstimate::for_hops(hops).fee(pricing);
+    xlm_rate(graph, to)
+        .and_then(|rate| mul_div_floor(fee, rate.numerator(), rate.denominator()).ok())
+        .unwrap_or(0)
+}
+
+/// Route with the most output net of execution cost, then the fewest hops
+pub fn best_net_route(
+    graph: &LiquidityGraph,
+    routes: Vec<Route>,
+    pricing: &ResourcePricing,
+) -> Option<Route> {
+    let to = routes.first()?.hops.last()?.to.clone();
+    routes.into_iter().max_by(|a, b| {
+        let net = |r: &Route| r.amount_out - execution_cost(graph, &to, r.hop_count(), pricing);
+        net(a)
+            .cmp(&net(b))
+            .then_with(|| b.hop_count().cmp(&a.hop_count()))
+    })
+}
+
+/// Score breakdown for one route
+///
+/// Amounts are in stroops of the destination asset.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RouteScore {
+    /// Output with the swap fees added back
+    pub gross_output: i128,
+    /// Output lost to swap fees, valuing each hop's fee at the rate the rest
+    /// of the route achieved
+    pub fees: i128,
+    /// Gross output relative to trading at every hop's spot price, in basis
+    /// points
+    pub price_impact_bps: u32,
+    pub hop_count: usize,
+    pub resources: ResourceEstimate,
+    /// Resource fee converted into the destination asset
+    pub execution_cost: i128,
+    /// Output less the execution cost
+    pub net_output: i128,
+}
+
+/// A route with its place among the alternatives
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RankedRoute {
+    /// Position in the ranking, starting at 1
+    pub rank: usize,
+    pub route: Route,
+    pub score: RouteScore,
+    /// Net output given up against the top-ranked route
+    pub shortfall: i128,
+}
+
+/// Score `route` against the venues in `graph`
+pub fn score_route(
+    graph: &LiquidityGraph,
+    route: &Route,
+    pricing: &ResourcePricing,
+) -> Result<RouteScore> {
+    let mut fees = 0;
+    let mut at_spot = route.amount_in;
+    for hop in &route.hops {
+        let edge = hop_edge(graph, hop)?;
+        // Priced on the hop's own input: carrying fee-free amounts forward
+        // could overrun a later orderbook that the route itself fits
+        let lost = (edge.amount_out_before_fees(hop.amount_in)? - hop.amount_out).max(0);
+        if hop.amount_out > 0 {
+            fees += mul_div_floor(lost, route.amount_out, hop.amount_out)?;
+        }
+        let spot = edge.spot_price()?;
+        at_spot = mul_div_floor(at_spot, spot.numerator(), spot.denominator())?;
+    }
+    let gross = route.amount_out + fees;
+
+    let price_impact_bps = if at_spot > gross {
+        mul_div_floor(at_spot - gross, BPS, at_spot)?.min(u32::MAX as i128) as u32
+    } else {
+        0
+    };
+
+    let execution_cost = route.hops.last().map_or(0, |last| {
+        execution_cost(graph, &last.to, route.hop_count(), pricing)
+    });
+    Ok(RouteScore {
+        gross_output: gross,
+        fees,
+        price_impact_bps,
+        hop_count: route.hop_count(),
+        resources: ResourceEstimate::for_hops(route.hop_count()),
+        execution_cost,
+        net_output: route.amount_out - execution_cost,
+    })
+}
+
+/// Score and rank `routes`, most net output first, then fewest hops
+pub fn rank_routes(
+    graph: &LiquidityGraph,
+    routes: Vec<Route>,
+    pricing: &ResourcePricing,
+) -> Result<Vec<RankedRoute>> {
+    let mut scored = routes
+        .into_iter()
+        .map(|route| Ok((score_route(graph, &route, pricing)?, route)))
+        .collect::<Result<Vec<_>>>()?;
+    scored.sort_by(|(a, _), (b, _)| {
+        b.net_output
+            .cmp(&a.net_output)
+            .then_with(|| a.hop_count.cmp(&b.hop_count))
+    });
+    let best = scored.first().map_or(0, |(score, _)| score.net_output);
+
+    Ok(scored
+        .into_iter()
+        .enumerate()
+        .map(|(idx, (score, route))| RankedRoute {
+            rank: idx + 1,
+            shortfall: best - score.net_output,
+            score,
+            route,
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constraints::RouteConstraints;
+    use crate::graph::{AmmPool, AssetId, OrderbookSide, PoolType, PriceLevel, Venue};
+    use crate::pathfinder::Pathfinder;
+    use crate::test_support::{graph, usdc, xlm};
+
+    fn pool(address: &str, reserve_xlm: i128, reserve_usdc: i128, fee_bps: u32) -> Venue {
+        Venue::Amm(AmmPool {
+            address: address.to_string(),
+            pool_type: PoolType::AmmConstProd,
+            asset_a: xlm(),
+            asset_b: usdc(),
+            reserve_a: reserve_xlm,
+            reserve_b: reserve_usdc,
+            fee_bps,
+            amplification: None,
+        })
+    }
+
+    #[test]
+    fn test_resources_match_contract_estimate() {
+        let one = ResourceEstimate::for_hops(1);
+        assert_eq!(one.estimated_cpu, 7_000_000);
+        assert_eq!(one.storage_reads, 3);
+        assert_eq!(one.storage_writes, 1);
+        assert_eq!(one.events, 1);
+        assert!(one.will_succeed);
+
+        let four = ResourceEstimate::for_hops(4);
+        assert_eq!(four.estimated_cpu, 28_000_000);
+        assert_eq!(four.storage_reads, 6);
+    }
+
+    /// A direct XLM/USDC pool charging 30bps, and a four-hop detour through
+    /// equally deep pools charging 7bps each
+    fn detour_graph() -> LiquidityGraph {
+        let hop = |address: &str, a: AssetId, b: AssetId, reserve_b: i128| {
+            Venue::Amm(AmmPool {
+                address: address.to_string(),
+                pool_type: PoolType::AmmConstProd,
+                asset_a: a,
+                asset_b: b,
+                reserve_a: 1_000_000_000_000_000,
+                reserve_b,
+                fee_bps: 7,
+                amplification: None,
+            })
+        };
+        let (a, b, c) = (
+            AssetId::credit("A", "GISSUER"),
+            AssetId::credit("B", "GISSUER"),
+            AssetId::credit("C", "GISSUER"),
+        );
+        graph(vec![
+            pool("CDIRECT", 1_000_000_000_000_000, 100_000_000_000_000, 30),
+            hop("C1", xlm(), a.clone(), 1_000_000_000_000_000),
+            hop("C2", a, b.clone(), 1_000_000_000_000_000),
+            hop("C3", b, c.clone(), 1_000_000_000_000_000),
+            hop("C4", c, usdc(), 100_000_000_000_000),
+        ])
+    }
+
+    #[test]
+    fn test_fee_prices_each_resource() {
+        let pricing = ResourcePricing::default();
+        // 700 x 25 for CPU, 3 reads, 1 write and 1 event
+        assert_eq!(ResourceEstimate::for_hops(1).fee(&pricing), 56_250);
+        assert_eq!(ResourceEstimate::for_hops(4).fee(&pricing), 127_500);
+        assert_eq!(
+            ResourceEstimate::for_hops(4).fee(&ResourcePricing::free()),
+            0
+        );
+    }
+
+    #[test]
+    fn test_marginal_gain_does_not_pay_for_extra_hops() {
+        let graph = detour_graph();
+        let routes = Pathfinder::default()
+            .find_top_routes(
+                &graph,
+                &xlm(),
+                &usdc(),
+                100_000_000,
+                2,
+                &RouteConstraints::default(),
+            )
+            .unwrap();
+        // The detour pays ~0.02% more before execution costs
+        assert_eq!(routes[0].hop_count(), 4);
+        assert!(routes[0].amount_out - routes[1].amount_out < routes[1].amount_out / 2_000);
+
+        let free = best_net_route(&graph, routes.clone(), &ResourcePricing::free()).unwrap();
+        assert_eq!(free.hop_count(), 4);
+        let priced = best_net_route(&graph, routes.clone(), &ResourcePricing::default()).unwrap();
+        assert_eq!(priced.hop_count(), 1);
+
+        let ranked = rank_routes(&graph, routes, &ResourcePricing::default()).unwrap();
+        assert_eq!(ranked[0].route.hop_count(), 1);
+        // 56,250 stroops of XLM at 0.1 USDC each
+        assert_eq!(ranked[0].score.execution_cost, 5_625);
+        assert_eq!(ranked[1].score.execution_cost, 12_750);
+        assert_eq!(
+            ranked[1].shortfall,
+            ranked[0].score.net_output - ranked[1].score.net_output
+        );
+        assert!(ranked[1].shortfall > 0);
+    }
+
+    #[test]
+    fn test_execution_cost_is_zero_without_an_xlm_market() {
+        let eurc = AssetId::credit("EURC", "GISSUER");
+        let graph = graph(vec![Venue::Amm(AmmPool {
+            address: "CPOOL".to_string(),
+            pool_type: PoolType::AmmConstProd,
+            asset_a: usdc(),
+            asset_b: eurc.clone(),
+            reserve_a: 1_000_000_000,
+            reserve_b: 900_000_000,
+            fee_bps: 30,
+            amplification: None,
+        })]);
+        let pricing = ResourcePricing::default();
+        assert_eq!(execution_cost(&graph, &eurc, 1, &pricing), 0);
+        assert_eq!(execution_cost(&graph, &xlm(), 1, &pricing), 56_250);
+    }
+
+    #[test]
+    fn test_score_splits_fees_from_price_impact() {
+        let graph = graph(vec![pool("C1", 1_000_000_000, 100_000_000, 30)]);
+        let route = Pathfinder::default()
+            .find_best_route(&graph, &xlm(), &usdc(), 10_000_000)
+            .unwrap();
+        let score = score_route(&graph, &route, &ResourcePricing::free()).unwrap();
+
+        // Fee-free: 100M * 10M / 1010M
+        assert_eq!(score.gross_output, 990_099);
+        assert_eq!(score.fees, score.gross_output - route.amount_out);
+        assert!(score.fees > 0);
+        // 1% of the pool moves the price by ~1%
+        assert_eq!(score.price_impact_bps, 99);
+        assert_eq!(score.hop_count, 1);
+    }
+
+    #[test]
+    fn test_orderbook_within_best_level_has_no_fee_or_impact() {
+        let book = Venue::Sdex(OrderbookSide {
+            selling: usdc(),
+            buying: xlm(),
+            levels: vec![PriceLevel {
+                price_n: 10,
+                price_d: 1,
+                amount: 100_000_000,
+            }],
+        });
+        let graph = graph(vec![book]);
+        let route = Pathfinder::default()
+            .find_best_route(&graph, &xlm(), &usdc(), 50_000_000)
+            .unwrap();
+        let score = score_route(&graph, &route, &ResourcePricing::free()).unwrap();
+        assert_eq!(score.gross_output, 5_000_000);
+        assert_eq!(score.fees, 0);
+        assert_eq!(score.price_impact_bps, 0);
+    }
+
+    #[test]
+    fn test_rank_explains_shortfall() {
+        let graph = graph(vec![
+            pool("CDEEP", 10_000_000_000, 1_000_000_000, 30),
+            pool("CTHIN", 100_000_000, 10_000_000, 30),
+            pool("CFEE", 10_000_000_000, 1_000_000_000, 100),
+        ]);
+        let routes = Pathfinder::default()
+            .find_top_routes(
+                &graph,
+                &xlm(),
+                &usdc(),
+                10_000_000,
+                3,
+                &RouteConstraints::default(),
+            )
+            .unwrap();
+        let ranked = rank_routes(&graph, routes, &ResourcePricing::free()).unwrap();
+
+        let venues: Vec<String> = ranked
+            .iter()
+            .map(|r| r.route.hops[0].venue.to_string())
+            .collect();
+        assert_eq!(venues, vec!["amm:CDEEP", "amm:CFEE", "amm:CTHIN"]);
+        assert_eq!(ranked[0].rank, 1);
+        assert_eq!(ranked[0].shortfall, 0);
+
+        // The high-fee pool loses on fees, the thin pool on price impact
+        let (deep, fee, thin) = (&ranked[0].score, &ranked[1].score, &ranked[2].score);
+        assert!(fee.fees > deep.fees);
+        assert_eq!(fee.price_impact_bps, deep.price_impact_bps);
+        assert!(thin.price_impact_bps > deep.price_impact_bps);
+        assert!(ranked[1].shortfall > 0 && ranked[2].shortfall > ranked[1].shortfall);
+    }
+}
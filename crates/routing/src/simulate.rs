@@ -0,0 +1,587 @@
+//! Router contract swap simulation
+//!
+//! Replays the checks the router contract's `execute_swap` makes, in the
+//! order it makes them, against the liquidity graph and a known router
+//! state, to tell which [`ContractError`] a swap would fail with before it is
+//! submitted. Pool swaps are priced on the graph, so a pool whose state moved
+//! since the graph was last updated can still behave differently on-chain.
+//!
+//! Two checks depend on more than the parameters and are not replayed: the
+//! per-sender rate limit (`RateLimitExceeded`), and the sandwich detection
+//! that compares pool reserves around the swap
+//! (`ReserveManipulationDetected`).
+
+use serde::{Deserialize, Serialize};
+
+use crate::amount::{mul_div_floor, BPS};
+use crate::export::{ContractAsset, ContractRoute, ContractRouteHop, ExportConfig};
+use crate::graph::{AssetId, EdgeRef, LiquidityGraph, VenueId};
+use crate::pathfinder::DEFAULT_MAX_HOPS;
+
+/// Price impact the router books for every hop, whatever the pool
+const PRICE_IMPACT_BPS_PER_HOP: u32 = 5;
+
+/// The contract's `ContractError`, for the errors a swap can fail with
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[repr(u32)]
+pub enum ContractError {
+    Paused = 4,
+    InvalidRoute = 10,
+    SlippageExceeded = 22,
+    DeadlineExceeded = 23,
+    ExecutionTooEarly = 24,
+    PriceImpactTooHigh = 25,
+    SpreadTooHigh = 26,
+    PoolNotSupported = 30,
+    PoolCallFailed = 31,
+    Overflow = 41,
+    TokenNotAllowed = 80,
+    CommitmentRequired = 90,
+}
+
+impl ContractError {
+    /// Code the contract reports the error with
+    pub fn code(self) -> u32 {
+        self as u32
+    }
+
+    /// Variant name, as in the contract
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Paused => "Paused",
+            Self::InvalidRoute => "InvalidRoute",
+            Self::SlippageExceeded => "SlippageExceeded",
+            Self::DeadlineExceeded => "DeadlineExceeded",
+            Self::ExecutionTooEarly => "ExecutionTooEarly",
+            Self::PriceImpactTooHigh => "PriceImpactTooHigh",
+            Self::SpreadTooHigh => "SpreadTooHigh",
+            Self::PoolNotSupported => "PoolNotSupported",
+            Self::PoolCallFailed => "PoolCallFailed",
+            Self::Overflow => "Overflow",
+            Self::TokenNotAllowed => "TokenNotAllowed",
+            Self::CommitmentRequired => "CommitmentRequired",
+        }
+    }
+
+    /// Why the contract returns the error
+    pub fn description(self) -> &'static str {
+        match self {
+            Self::Paused => "the router is paused",
+            Self::InvalidRoute => "the route must have between 1 and 4 hops",
+            Self::SlippageExceeded => "the output is below min_amount_out",
+            Self::DeadlineExceeded => "the deadline ledger has passed",
+            Self::ExecutionTooEarly => "the not_before ledger has not been reached",
+            Self::PriceImpactTooHigh => "the route's price impact exceeds max_price_impact_bps",
+            Self::SpreadTooHigh => {
+                "the output falls short of the route's estimate by more than max_execution_spread_bps"
+            }
+            Self::PoolNotSupported => "a hop's pool is not registered with the router",
+            Self::PoolCallFailed => "a hop's pool cannot swap the amount between its assets",
+            Self::Overflow => "an amount overflows the router's arithmetic",
+            Self::TokenNotAllowed => "an asset in the route is not on the token allowlist",
+            Self::CommitmentRequired => {
+                "amount_in reaches the commit threshold; use commit_swap and reveal_and_execute"
+            }
+        }
+    }
+}
+
+/// The contract's `SwapParams`, less the recipient, which no check reads
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ContractSwapParams {
+    pub route: ContractRoute,
+    pub amount_in: i128,
+    pub min_amount_out: i128,
+    /// Last ledger the swap may execute in
+    pub deadline: u64,
+    /// First ledger the swap may execute in
+    #[serde(default)]
+    pub not_before: u64,
+    /// Highest price impact accepted, in basis points; 0 disables the check
+    #[serde(default)]
+    pub max_price_impact_bps: u32,
+    /// Largest shortfall of the output against `route.estimated_output`, in
+    /// basis points; 0 disables the check
+    #[serde(default)]
+    pub max_execution_spread_bps: u32,
+}
+
+/// Router contract state a swap's outcome depends on
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RouterState {
+    pub paused: bool,
+    /// Router fee taken from the final output, in basis points
+    pub protocol_fee_bps: u32,
+    /// Adapter contract the router calls for SDEX hops
+    pub sdex_adapter: Option<String>,
+    /// Assets on the token allowlist; the contract skips the check while the
+    /// allowlist is empty
+    pub allowed_tokens: Vec<ContractAsset>,
+    /// Smallest `amount_in` that must go through commit-reveal, when MEV
+    /// protection is configured
+    pub commit_threshold: Option<i128>,
+}
+
+impl RouterState {
+    /// Settings to export routes for this router with
+    pub fn export_config(&self) -> ExportConfig {
+        ExportConfig {
+            sdex_adapter: self.sdex_adapter.clone(),
+            protocol_fee_bps: self.protocol_fee_bps,
+        }
+    }
+}
+
+/// Amounts through one hop of a simulated swap
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SimulatedHop {
+    pub pool: String,
+    pub amount_in: i128,
+    pub amount_out: i128,
+}
+
+/// Outcome of a simulated swap
+///
+/// Amounts are filled in as far as the swap got before failing.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SwapSimulation {
+    /// Error the router would return, or `None` if the swap would succeed
+    pub error: Option<ContractError>,
+    /// Hops run, in order
+    pub hops: Vec<SimulatedHop>,
+    /// Output paid to the recipient, after the router fee
+    pub amount_out: Option<i128>,
+    /// Router fee, in the output asset
+    pub fee_amount: Option<i128>,
+    /// Price impact as the router books it
+    pub price_impact_bps: Option<u32>,
+    /// Shortfall of the output against the route's estimate, in basis points
+    pub spread_bps: Option<i128>,
+}
+
+/// Simulate `execute_swap` with `params` in ledger `ledger`
+///
+/// Without a ledger, the swap's `deadline` and `not_before` are not checked.
+pub fn simulate_swap(
+    graph: &LiquidityGraph,
+    state: &RouterState,
+    params: &ContractSwapParams,
+    ledger: Option<u64>,
+) -> SwapSimulation {
+    let mut simulation = SwapSimulation::default();
+    simulation.error = execute(graph, state, params, ledger, &mut simulation).err();
+    simulation
+}
+
+fn execute(
+    graph: &LiquidityGraph,
+    state: &RouterState,
+    params: &ContractSwapParams,
+    ledger: Option<u64>,
+    simulation: &mut SwapSimulation,
+) -> Result<(), ContractError> {
+    let hops = &params.route.hops;
+
+    if state.paused {
+        return Err(ContractError::Paused);
+    }
+    if !state.allowed_tokens.is_empty()
+        && hops.iter().any(|hop| {
+            !state.allowed_tokens.contains(&hop.source)
+                || !state.allowed_tokens.contains(&hop.destination)
+        })
+    {
+        return Err(ContractError::TokenNotAllowed);
+    }
+    if state
+        .commit_threshold
+        .is_some_and(|threshold| params.amount_in >= threshold)
+    {
+        return Err(ContractError::CommitmentRequired);
+    }
+    if ledger.is_some_and(|ledger| ledger > params.deadline) {
+        return Err(ContractError::DeadlineExceeded);
+    }
+    if ledger.is_some_and(|ledger| ledger < params.not_before) {
+        return Err(ContractError::ExecutionTooEarly);
+    }
+    if hops.is_empty() || hops.len() > DEFAULT_MAX_HOPS {
+        return Err(ContractError::InvalidRoute);
+    }
+
+    let mut amount = params.amount_in;
+    for hop in hops {
+        let edge = hop_edge(graph, state, hop)?;
+        let amount_out = edge
+            .amount_out(amount)
+            .map_err(|_| ContractError::PoolCallFailed)?;
+        simulation.hops.push(SimulatedHop {
+            pool: hop.pool.clone(),
+            amount_in: amount,
+            amount_out,
+        });
+        amount = amount_out;
+    }
+
+    let fee_amount = mul_div_floor(amount, state.protocol_fee_bps as i128, BPS)
+        .map_err(|_| ContractError::Overflow)?;
+    let amount_out = amount - fee_amount;
+    let price_impact_bps = PRICE_IMPACT_BPS_PER_HOP * hops.len() as u32;
+    simulation.amount_out = Some(amount_out);
+    simulation.fee_amount = Some(fee_amount);
+    simulation.price_impact_bps = Some(price_impact_bps);
+
+    if params.max_price_impact_bps > 0 && price_impact_bps > params.max_price_impact_bps {
+        return Err(ContractError::PriceImpactTooHigh);
+    }
+
+    let estimated = params.route.estimated_output;
+    if params.max_execution_spread_bps > 0 && estimated > 0 {
+        let spread = if amount_out < estimated {
+            mul_div_floor(estimated - amount_out, BPS, estimated)
+                .map_err(|_| ContractError::Overflow)?
+        } else {
+            0
+        };
+        simulation.spread_bps = Some(spread);
+        if spread > params.max_execution_spread_bps as i128 {
+            return Err(ContractError::SpreadTooHigh);
+        }
+    }
+
+    if amount_out < params.min_amount_out {
+        return Err(ContractError::SlippageExceeded);
+    }
+    Ok(())
+}
+
+/// The graph edge a hop's pool swaps over
+///
+/// SDEX hops go through the router's adapter, and AMM hops to the pool
+/// itself; any other address is not registered with the router. A
+/// registered pool without an edge between the hop's assets cannot swap.
+fn hop_edge<'a>(
+    graph: &'a LiquidityGraph,
+    state: &RouterState,
+    hop: &ContractRouteHop,
+) -> Result<EdgeRef<'a>, ContractError> {
+    let from = asset_id(&hop.source);
+    let to = asset_id(&hop.destination);
+    let venue = if state.sdex_adapter.as_ref() == Some(&hop.pool) {
+        VenueId::Sdex {
+            selling: to.clone(),
+            buying: from.clone(),
+        }
+    } else {
+        let venue = VenueId::Amm {
+            address: hop.pool.clone(),
+        };
+        if graph.venue(&venue).is_none() {
+            return Err(ContractError::PoolNotSupported);
+        }
+        venue
+    };
+
+    graph
+        .edges_from(&from)
+        .find(|edge| edge.to == &to && edge.venue.id() == venue)
+        .ok_or(ContractError::PoolCallFailed)
+}
+
+/// Graph asset for a contract asset
+fn asset_id(asset: &ContractAsset) -> AssetId {
+    match asset {
+        ContractAsset::Native => AssetId::native(),
+        ContractAsset::Issued { issuer, code } => AssetId::credit(code, issuer),
+        ContractAsset::Soroban { address } => AssetId::new(address.clone()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::delta::SdexOffer;
+    use crate::graph::{AmmPool, PoolType, Venue};
+
+    const ADAPTER: &str = "CADAPTER";
+    const POOL: &str = "CPOOL";
+
+    fn usdc() -> ContractAsset {
+        ContractAsset::Issued {
+            issuer: "GISSUER".to_string(),
+            code: "USDC".to_string(),
+        }
+    }
+
+    fn eurc() -> ContractAsset {
+        ContractAsset::Issued {
+            issuer: "GISSUER".to_string(),
+            code: "EURC".to_string(),
+        }
+    }
+
+    /// An XLM/USDC pool, and a book selling EURC for USDC at 0.5 USDC each
+    fn graph() -> LiquidityGraph {
+        let mut graph = LiquidityGraph::new();
+        graph
+            .upsert_venue(Venue::Amm(AmmPool {
+                address: POOL.to_string(),
+                pool_type: PoolType::AmmConstProd,
+                asset_a: AssetId::native(),
+                asset_b: AssetId::credit("USDC", "GISSUER"),
+                reserve_a: 1_000_000_000_000,
+                reserve_b: 1_000_000_000_000,
+                fee_bps: 30,
+                amplification: None,
+            }))
+            .unwrap();
+        graph
+            .upsert_offer(SdexOffer {
+                id: 1,
+                selling: AssetId::credit("EURC", "GISSUER"),
+                buying: AssetId::credit("USDC", "GISSUER"),
+                price_n: 1,
+                price_d: 2,
+                amount: 1_000_000_000,
+            })
+            .unwrap();
+        graph
+    }
+
+    fn state() -> RouterState {
+        RouterState {
+            protocol_fee_bps: 30,
+            sdex_adapter: Some(ADAPTER.to_string()),
+            ..RouterState::default()
+        }
+    }
+
+    fn hop(source: ContractAsset, destination: ContractAsset, pool: &str) -> ContractRouteHop {
+        ContractRouteHop {
+            source,
+            destination,
+            pool: pool.to_string(),
+            pool_type: if pool == ADAPTER {
+                PoolType::Sdex
+            } else {
+                PoolType::AmmConstProd
+            },
+        }
+    }
+
+    /// XLM to USDC through the pool, then USDC to EURC on the SDEX
+    fn swap_params() -> ContractSwapParams {
+        ContractSwapParams {
+            route: ContractRoute {
+                hops: vec![
+                    hop(ContractAsset::Native, usdc(), POOL),
+                    hop(usdc(), eurc(), ADAPTER),
+                ],
+                estimated_output: 0,
+                min_output: 0,
+                expires_at: 200,
+            },
+            amount_in: 100_000_000,
+            min_amount_out: 0,
+            deadline: 200,
+            not_before: 0,
+            max_price_impact_bps: 0,
+            max_execution_spread_bps: 0,
+        }
+    }
+
+    fn simulate(state: &RouterState, params: &ContractSwapParams) -> SwapSimulation {
+        simulate_swap(&graph(), state, params, Some(100))
+    }
+
+    #[test]
+    fn test_swap_within_limits_succeeds() {
+        let simulation = simulate(&state(), &swap_params());
+
+        assert_eq!(simulation.error, None);
+        assert_eq!(simulation.hops.len(), 2);
+        assert_eq!(simulation.hops[0].pool, POOL);
+        assert_eq!(simulation.hops[1].amount_in, simulation.hops[0].amount_out);
+        let gross = simulation.hops[1].amount_out;
+        // 0.997 USDC per XLM less price movement, then 2 EURC per USDC
+        assert!(gross > 190_000_000 && gross < 200_000_000);
+        let fee = gross * 30 / 10_000;
+        assert_eq!(simulation.fee_amount, Some(fee));
+        assert_eq!(simulation.amount_out, Some(gross - fee));
+        assert_eq!(simulation.price_impact_bps, Some(10));
+        assert_eq!(simulation.spread_bps, None);
+    }
+
+    #[test]
+    fn test_output_below_minimum_is_slippage() {
+        let amount_out = simulate(&state(), &swap_params()).amount_out.unwrap();
+        let mut params = swap_params();
+
+        params.min_amount_out = amount_out;
+        assert_eq!(simulate(&state(), &params).error, None);
+        params.min_amount_out = amount_out + 1;
+        let simulation = simulate(&state(), &params);
+        assert_eq!(simulation.error, Some(ContractError::SlippageExceeded));
+        assert_eq!(simulation.amount_out, Some(amount_out));
+    }
+
+    #[test]
+    fn test_router_booked_impact_is_checked() {
+        let mut params = swap_params();
+        params.max_price_impact_bps = 10;
+        assert_eq!(simulate(&state(), &params).error, None);
+        params.max_price_impact_bps = 9;
+        assert_eq!(
+            simulate(&state(), &params).error,
+            Some(ContractError::PriceImpactTooHigh)
+        );
+    }
+
+    #[test]
+    fn test_shortfall_against_estimate_is_spread() {
+        let amount_out = simulate(&state(), &swap_params()).amount_out.unwrap();
+        let mut params = swap_params();
+        params.max_execution_spread_bps = 100;
+
+        // 1% short of the estimate, rounded down, is within 100 bps
+        params.route.estimated_output = amount_out * 100 / 99;
+        let simulation = simulate(&state(), &params);
+        assert_eq!(simulation.error, None);
+        assert_eq!(simulation.spread_bps, Some(99));
+
+        params.route.estimated_output = amount_out * 100 / 98;
+        let simulation = simulate(&state(), &params);
+        assert_eq!(simulation.error, Some(ContractError::SpreadTooHigh));
+        assert!(simulation.spread_bps.unwrap() > 100);
+
+        // Beating the estimate is no spread
+        params.route.estimated_output = amount_out / 2;
+        assert_eq!(simulate(&state(), &params).spread_bps, Some(0));
+    }
+
+    #[test]
+    fn test_ledger_window_is_checked() {
+        let mut params = swap_params();
+        params.deadline = 99;
+        assert_eq!(
+            simulate(&state(), &params).error,
+            Some(ContractError::DeadlineExceeded)
+        );
+
+        params.deadline = 100;
+        params.not_before = 101;
+        assert_eq!(
+            simulate(&state(), &params).error,
+            Some(ContractError::ExecutionTooEarly)
+        );
+
+        // Without a ledger the window cannot be checked
+        let unchecked = simulate_swap(&graph(), &state(), &params, None);
+        assert_eq!(unchecked.error, None);
+    }
+
+    #[test]
+    fn test_hop_count_is_checked() {
+        let mut params = swap_params();
+        params.route.hops.clear();
+        assert_eq!(
+            simulate(&state(), &params).error,
+            Some(ContractError::InvalidRoute)
+        );
+
+        params.route.hops = vec![hop(ContractAsset::Native, usdc(), POOL); 5];
+        assert_eq!(
+            simulate(&state(), &params).error,
+            Some(ContractError::InvalidRoute)
+        );
+    }
+
+    #[test]
+    fn test_unknown_pools_and_impossible_hops() {
+        let mut params = swap_params();
+        params.route.hops[1].pool = "CUNKNOWN".to_string();
+        let simulation = simulate(&state(), &params);
+        assert_eq!(simulation.error, Some(ContractError::PoolNotSupported));
+        assert_eq!(simulation.hops.len(), 1);
+
+        // Without an adapter the router has no SDEX pool either
+        let no_adapter = RouterState {
+            sdex_adapter: None,
+            ..state()
+        };
+        assert_eq!(
+            simulate(&no_adapter, &swap_params()).error,
+            Some(ContractError::PoolNotSupported)
+        );
+
+        // The pool does not hold EURC
+        let mut params = swap_params();
+        params.route.hops[0].destination = eurc();
+        assert_eq!(
+            simulate(&state(), &params).error,
+            Some(ContractError::PoolCallFailed)
+        );
+
+        // More USDC than the book can fill
+        let mut params = swap_params();
+        params.amount_in = 100_000_000_000;
+        assert_eq!(
+            simulate(&state(), &params).error,
+            Some(ContractError::PoolCallFailed)
+        );
+    }
+
+    #[test]
+    fn test_router_state_checks_come_first() {
+        let mut params = swap_params();
+        params.deadline = 0;
+
+        let allowlisted = RouterState {
+            allowed_tokens: vec![ContractAsset::Native, usdc()],
+            ..state()
+        };
+        assert_eq!(
+            simulate(&allowlisted, &params).error,
+            Some(ContractError::TokenNotAllowed)
+        );
+
+        let mev = RouterState {
+            commit_threshold: Some(params.amount_in),
+            ..state()
+        };
+        let simulation = simulate(&mev, &params);
+        assert_eq!(simulation.error, Some(ContractError::CommitmentRequired));
+        assert!(simulation.hops.is_empty());
+
+        let paused = RouterState {
+            paused: true,
+            ..mev
+        };
+        assert_eq!(
+            simulate(&paused, &params).error,
+            Some(ContractError::Paused)
+        );
+
+        let allowed = RouterState {
+            allowed_tokens: vec![ContractAsset::Native, usdc(), eurc()],
+            commit_threshold: Some(params.amount_in + 1),
+            ..state()
+        };
+        assert_eq!(
+            simulate(&allowed, &params).error,
+            Some(ContractError::DeadlineExceeded)
+        );
+    }
+
+    #[test]
+    fn test_errors_carry_contract_codes() {
+        assert_eq!(ContractError::SlippageExceeded.code(), 22);
+        assert_eq!(ContractError::TokenNotAllowed.code(), 80);
+        assert_eq!(ContractError::CommitmentRequired.code(), 90);
+        assert_eq!(ContractError::SpreadTooHigh.name(), "SpreadTooHigh");
+        assert_eq!(
+            serde_json::to_value(ContractError::PoolCallFailed).unwrap(),
+            "PoolCallFailed"
+        );
+    }
+}
@@ -0,0 +1,425 @@
+//! Split routing across parallel venues
+//!
+//! A large order often does better spread over several venues than pushed
+//! through the single best one. Candidate routes are found venue-disjoint, so
+//! the legs never move each other's prices, and the input is then handed out
+//! in small steps to whichever leg returns the most for the next step. With
+//! concave venue curves this greedy allocation converges on equal marginal
+//! prices across the legs used, which maximises total output.
+
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+
+use crate::constraints::RouteConstraints;
+use crate::error::{Result, RoutingError};
+use crate::graph::{AssetId, LiquidityGraph};
+use crate::pathfinder::{simulate_route, Pathfinder, Route};
+
+/// Default maximum number of parallel legs
+pub const DEFAULT_MAX_LEGS: usize = 4;
+
+/// Default number of allocation steps (1% of the input each)
+pub const DEFAULT_SPLIT_STEPS: u32 = 100;
+
+/// Split optimizer configuration
+#[derive(Debug, Clone)]
+pub struct SplitConfig {
+    /// Maximum number of parallel legs
+    pub max_legs: usize,
+    /// Number of increments the input is divided into while allocating
+    pub steps: u32,
+}
+
+impl Default for SplitConfig {
+    fn default() -> Self {
+        Self {
+            max_legs: DEFAULT_MAX_LEGS,
+            steps: DEFAULT_SPLIT_STEPS,
+        }
+    }
+}
+
+/// One leg of a split route
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SplitLeg {
+    /// Share of the total input sent down this leg, in basis points
+    pub weight_bps: u32,
+    pub route: Route,
+}
+
+/// A set of weighted routes executed in parallel
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SplitRoute {
+    /// Legs ordered by input, largest first
+    pub legs: Vec<SplitLeg>,
+    pub amount_in: i128,
+    pub amount_out: i128,
+}
+
+impl SplitRoute {
+    /// Whether the input is spread over more than one leg
+    pub fn is_split(&self) -> bool {
+        self.legs.len() > 1
+    }
+}
+
+/// Leg being allocated, with its quote for the next step cached
+struct Candidate {
+    template: Route,
+    current: Option<Route>,
+    next: Option<(i128, Option<Route>)>,
+}
+
+impl Candidate {
+    fn allocated(&self) -> i128 {
+        self.current.as_ref().map_or(0, |r| r.amount_in)
+    }
+
+    fn output(&self) -> i128 {
+        self.current.as_ref().map_or(0, |r| r.amount_out)
+    }
+
+    /// Quote for adding `step` to this leg, `None` if it cannot absorb it
+    fn quote_step(&mut self, graph: &LiquidityGraph, step: i128) -> Option<&Route> {
+        if !matches!(self.next, Some((cached, _)) if cached == step) {
+            let quote = simulate_route(graph, &self.template, self.allocated() + step).ok();
+            self.next = Some((step, quote));
+        }
+        self.next.as_ref().and_then(|(_, quote)| quote.as_ref())
+    }
+}
+
+/// Splits an order over venue-disjoint routes found by a [`Pathfinder`]
+#[derive(Debug, Clone, Default)]
+pub struct SplitRouter {
+    pathfinder: Pathfinder,
+    config: SplitConfig,
+}
+
+impl SplitRouter {
+    pub fn new(pathfinder: Pathfinder, config: SplitConfig) -> Self {
+        Self { pathfinder, config }
+    }
+
+    pub fn config(&self) -> &SplitConfig {
+        &self.config
+    }
+
+    /// Best allocation of `amount_in` of `from` across parallel routes to `to`
+    ///
+    /// Falls back to a single leg whenever splitting does not beat the best
+    /// single route. Every leg keeps to `constraints`.
+    pub fn find_split_route(
+        &self,
+        graph: &LiquidityGraph,
+        from: &AssetId,
+        to: &AssetId,
+        amount_in: i128,
+        constraints: &RouteConstraints,
+    ) -> Result<SplitRoute> {
+        if amount_in <= 0 {
+            return Err(RoutingError::InvalidAmount(format!(
+                "amount must be positive, got {}",
+                amount_in
+            )));
+        }
+
+        let single = self
+            .pathfinder
+            .find_constrained_route(graph, from, to, amount_in, constraints)
+            .ok();
+
+        let mut candidates = self.candidates(graph, from, to, amount_in, constraints)?;
+        let split = match self.allocate(graph, &mut candidates, amount_in) {
+            Ok(split) => split,
+            Err(err) => return single.map(single_leg).ok_or(err),
+        };
+
+        match single {
+            Some(route) if route.amount_out >= split.amount_out => Ok(single_leg(route)),
+            _ => Ok(split),
+        }
+    }
+
+    /// Venue-disjoint routes, best first, probed with a single allocation step
+    ///
+    /// Probing small keeps thin venues that can still absorb part of the order.
+    fn candidates(
+        &self,
+        graph: &LiquidityGraph,
+        from: &AssetId,
+        to: &AssetId,
+        amount_in: i128,
+        constraints: &RouteConstraints,
+    ) -> Result<Vec<Candidate>> {
+        let max_legs = self.config.max_legs.max(1);
+        let probe = self.chunk(amount_in);
+        // Venues already used by a leg are denied to the following ones
+        let mut remaining = constraints.clone();
+        let mut candidates = Vec::new();
+
+        while candidates.len() < max_legs {
+            let route = match self
+                .pathfinder
+                .find_constrained_route(graph, from, to, probe, &remaining)
+            {
+                Ok(route) => route,
+                Err(err) if candidates.is_empty() => return Err(err),
+                Err(_) => break,
+            };
+            remaining
+                .denied_venues
+                .extend(route.hops.iter().map(|h| h.venue.clone()));
+            candidates.push(Candidate {
+                template: route,
+                current: None,
+                next: None,
+            });
+        }
+        Ok(candidates)
+    }
+
+    fn chunk(&self, amount_in: i128) -> i128 {
+        (amount_in / self.config.steps.max(1) as i128).max(1)
+    }
+
+    /// Hand out the input step by step to the leg with the largest marginal gain
+    fn allocate(
+        &self,
+        graph: &LiquidityGraph,
+        candidates: &mut [Candidate],
+        amount_in: i128,
+    ) -> Result<SplitRoute> {
+        let chunk = self.chunk(amount_in);
+        let mut remaining = amount_in;
+
+        while remaining > 0 {
+            let step = chunk.min(remaining);
+            let mut best: Option<(usize, i128)> = None;
+            for (idx, candidate) in candidates.iter_mut().enumerate() {
+                let output = candidate.output();
+                let Some(quote) = candidate.quote_step(graph, step) else {
+                    continue;
+                };
+                let gain = quote.amount_out - output;
+                if best.is_none_or(|(_, best_gain)| gain > best_gain) {
+                    best = Some((idx, gain));
+                }
+            }
+
+            let (idx, _) = best.ok_or_else(|| {
+                RoutingError::InsufficientLiquidity(format!(
+                    "parallel routes can absorb {} of {} stroops",
+                    amount_in - remaining,
+                    amount_in
+                ))
+            })?;
+            let candidate = &mut candidates[idx];
+            candidate.current = candidate.next.take().and_then(|(_, quote)| quote);
+            remaining -= step;
+        }
+
+        let mut routes: Vec<Route> = candidates
+            .iter_mut()
+            .filter_map(|c| c.current.take())
+            .collect();
+        routes.sort_by_key(|r| Reverse(r.amount_in));
+
+        let amount_out = routes.iter().map(|r| r.amount_out).sum();
+        let mut weight_left = 10_000u32;
+        let last = routes.len().saturating_sub(1);
+        let legs = routes
+            .into_iter()
+            .enumerate()
+            .map(|(idx, route)| {
+                let weight_bps = if idx == last {
+                    weight_left
+                } else {
+                    (route.amount_in * 10_000 / amount_in) as u32
+                };
+                weight_left -= weight_bps;
+                SplitLeg { weight_bps, route }
+            })
+            .collect();
+
+        Ok(SplitRoute {
+            legs,
+            amount_in,
+            amount_out,
+        })
+    }
+}
+
+fn single_leg(route: Route) -> SplitRoute {
+    SplitRoute {
+        amount_in: route.amount_in,
+        amount_out: route.amount_out,
+        legs: vec![SplitLeg {
+            weight_bps: 10_000,
+            route,
+        }],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::{AmmPool, OrderbookSide, PoolType, PriceLevel, Venue};
+    use crate::test_support::{graph, usdc, xlm};
+
+    fn pool(address: &str, reserve_xlm: i128, reserve_usdc: i128) -> Venue {
+        Venue::Amm(AmmPool {
+            address: address.to_string(),
+            pool_type: PoolType::AmmConstProd,
+            asset_a: xlm(),
+            asset_b: usdc(),
+            reserve_a: reserve_xlm,
+            reserve_b: reserve_usdc,
+            fee_bps: 30,
+            amplification: None,
+        })
+    }
+
+    /// Makers selling USDC for XLM at `price_n / price_d` XLM per USDC
+    fn book(price_n: i64, price_d: i64, amount_usdc: i128) -> Venue {
+        Venue::Sdex(OrderbookSide {
+            selling: usdc(),
+            buying: xlm(),
+            levels: vec![PriceLevel {
+                price_n,
+                price_d,
+                amount: amount_usdc,
+            }],
+        })
+    }
+
+    fn assert_consistent(split: &SplitRoute) {
+        let total_in: i128 = split.legs.iter().map(|l| l.route.amount_in).sum();
+        let total_out: i128 = split.legs.iter().map(|l| l.route.amount_out).sum();
+        let total_weight: u32 = split.legs.iter().map(|l| l.weight_bps).sum();
+        assert_eq!(total_in, split.amount_in);
+        assert_eq!(total_out, split.amount_out);
+        assert_eq!(total_weight, 10_000);
+    }
+
+    #[test]
+    fn test_identical_pools_split_evenly() {
+        let graph = graph(vec![
+            pool("C1", 1_000_000_000, 100_000_000),
+            pool("C2", 1_000_000_000, 100_000_000),
+        ]);
+        let router = SplitRouter::default();
+        let amount = 200_000_000;
+
+        let split = router
+            .find_split_route(
+                &graph,
+                &xlm(),
+                &usdc(),
+                amount,
+                &RouteConstraints::default(),
+            )
+            .unwrap();
+        assert_consistent(&split);
+        assert_eq!(split.legs.len(), 2);
+        assert_eq!(split.legs[0].weight_bps, 5_000);
+
+        let single = Pathfinder::default()
+            .find_best_route(&graph, &xlm(), &usdc(), amount)
+            .unwrap();
+        assert!(split.amount_out > single.amount_out);
+    }
+
+    #[test]
+    fn test_sdex_and_amm_marginal_prices_equalise() {
+        // The book quotes a flat 10.5 XLM/USDC while the pool starts near 10
+        // and worsens with size, so the pool fills until it reaches 10.5
+        let graph = graph(vec![
+            book(21, 2, 100_000_000),
+            pool("C1", 1_000_000_000, 100_000_000),
+        ]);
+        let split = SplitRouter::default()
+            .find_split_route(
+                &graph,
+                &xlm(),
+                &usdc(),
+                300_000_000,
+                &RouteConstraints::default(),
+            )
+            .unwrap();
+        assert_consistent(&split);
+        assert_eq!(split.legs.len(), 2);
+        assert_eq!(split.legs[0].route.hops[0].pool_type, PoolType::Sdex);
+        assert!(split.legs[0].weight_bps > split.legs[1].weight_bps);
+
+        // Pool marginal price after the allocation, fee included, sits at the book's
+        let amm = &split.legs[1].route;
+        let pool_xlm = 1_000_000_000 + amm.amount_in;
+        let pool_usdc = 100_000_000 - amm.amount_out;
+        let marginal = pool_xlm as f64 / pool_usdc as f64 / 0.997;
+        assert!((marginal - 10.5).abs() < 0.05, "marginal {marginal}");
+    }
+
+    #[test]
+    fn test_thin_book_overflow_goes_to_pool() {
+        // Book holds 1 USDC (10 XLM of input), the order is 20 XLM
+        let graph = graph(vec![
+            book(10, 1, 10_000_000),
+            pool("C1", 10_000_000_000, 1_000_000_000),
+        ]);
+        let split = SplitRouter::default()
+            .find_split_route(
+                &graph,
+                &xlm(),
+                &usdc(),
+                200_000_000,
+                &RouteConstraints::default(),
+            )
+            .unwrap();
+        assert_consistent(&split);
+        assert!(split.is_split());
+        let sdex = split
+            .legs
+            .iter()
+            .find(|l| l.route.hops[0].pool_type == PoolType::Sdex)
+            .unwrap();
+        assert_eq!(sdex.route.amount_out, 10_000_000);
+    }
+
+    #[test]
+    fn test_single_venue_is_single_leg() {
+        let graph = graph(vec![pool("C1", 1_000_000_000, 100_000_000)]);
+        let split = SplitRouter::default()
+            .find_split_route(
+                &graph,
+                &xlm(),
+                &usdc(),
+                10_000_000,
+                &RouteConstraints::default(),
+            )
+            .unwrap();
+        assert!(!split.is_split());
+        assert_eq!(split.legs[0].weight_bps, 10_000);
+        assert_consistent(&split);
+    }
+
+    #[test]
+    fn test_errors() {
+        let graph = graph(vec![book(10, 1, 10_000_000)]);
+        let router = SplitRouter::default();
+        assert!(matches!(
+            router.find_split_route(&graph, &xlm(), &usdc(), 0, &RouteConstraints::default()),
+            Err(RoutingError::InvalidAmount(_))
+        ));
+        assert!(matches!(
+            router.find_split_route(
+                &graph,
+                &xlm(),
+                &usdc(),
+                1_000_000_000,
+                &RouteConstraints::default()
+            ),
+            Err(RoutingError::InsufficientLiquidity(_))
+        ));
+    }
+}
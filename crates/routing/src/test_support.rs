@@ -0,0 +1,46 @@
+//! Fixtures shared by the routing unit tests
+
+use crate::graph::{AmmPool, AssetId, LiquidityGraph, PoolType, Venue};
+
+/// Issuer of every credit asset, a valid account id so routes through them
+/// can be exported to the router contract
+pub(crate) const ISSUER: &str = "GAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAWHF";
+
+/// `XLM` is the native asset, any other code a credit from [`ISSUER`]
+pub(crate) fn asset(code: &str) -> AssetId {
+    if code == "XLM" {
+        AssetId::native()
+    } else {
+        AssetId::credit(code, ISSUER)
+    }
+}
+
+pub(crate) fn xlm() -> AssetId {
+    AssetId::native()
+}
+
+pub(crate) fn usdc() -> AssetId {
+    asset("USDC")
+}
+
+/// Constant-product pool with the default 30bps fee
+pub(crate) fn pool(address: &str, a: &str, b: &str, reserve_a: i128, reserve_b: i128) -> Venue {
+    Venue::Amm(AmmPool {
+        address: address.to_string(),
+        pool_type: PoolType::AmmConstProd,
+        asset_a: asset(a),
+        asset_b: asset(b),
+        reserve_a,
+        reserve_b,
+        fee_bps: 30,
+        amplification: None,
+    })
+}
+
+pub(crate) fn graph(venues: impl IntoIterator<Item = Venue>) -> LiquidityGraph {
+    let mut graph = LiquidityGraph::new();
+    for venue in venues {
+        graph.upsert_venue(venue).unwrap();
+    }
+    graph
+}
@@ -0,0 +1,329 @@
+//! Property tests checking the pathfinder against exhaustive enumeration
+//!
+//! Markets are generated from a hidden value for every asset. Pools hold
+//! reserves in proportion to those values and every offer is priced above
+//! them, so each venue loses value to the taker and no cycle is profitable.
+//! While no orderbook runs out of depth, the layered search must find the
+//! same best output as trying every simple path.
+//!
+//! Thin books make routing a resource-constrained path problem: the best
+//! route can start with a weaker prefix several venues before the book that
+//! refuses the stronger one. The search only falls back to weaker paths at
+//! the asset right before such a book, so with thin books it is exact for
+//! routes of up to two hops and never claims more than the exhaustive best.
+
+use std::ops::Range;
+
+use proptest::prelude::*;
+use stellarroute_routing::amount::BPS;
+use stellarroute_routing::graph::{
+    AmmPool, AssetId, EdgeRef, LiquidityGraph, OrderbookSide, PoolType, PriceLevel, Venue,
+};
+use stellarroute_routing::pathfinder::{simulate_route, Pathfinder, Route, RouteHop};
+use stellarroute_routing::score::{score_route, ResourcePricing};
+
+const MAX_ASSETS: usize = 5;
+
+/// Per-level offer amounts that trades in these tests can exhaust
+const THIN: Range<i128> = 1..1_000_000_000;
+
+/// Per-level offer amounts above any amount a test trade can carry: inputs
+/// stay below 2e9 stroops and asset values within a factor of 50
+const DEEP: Range<i128> = 1_000_000_000_000..10_000_000_000_000;
+
+/// `(spread_bps, amount)` of one price level
+type Level = (i64, i128);
+
+#[derive(Debug, Clone)]
+struct Market {
+    /// Value of one unit of each asset, in arbitrary units
+    values: Vec<i64>,
+    /// `(asset_a, asset_b, depth, fee_bps)`
+    pools: Vec<(usize, usize, i64, u32)>,
+    /// `(selling, buying, levels)`
+    books: Vec<(usize, usize, Vec<Level>)>,
+}
+
+impl Market {
+    fn asset(&self, idx: usize) -> AssetId {
+        match idx {
+            0 => AssetId::native(),
+            _ => AssetId::credit(&format!("A{}", idx), "GISSUER"),
+        }
+    }
+
+    /// Two distinct assets picked by arbitrary indices
+    fn pair(&self, from: usize, offset: usize) -> (AssetId, AssetId) {
+        let n = self.values.len();
+        let from = from % n;
+        (
+            self.asset(from),
+            self.asset((from + 1 + offset % (n - 1)) % n),
+        )
+    }
+
+    fn graph(&self) -> LiquidityGraph {
+        let mut graph = LiquidityGraph::new();
+        for (idx, &(a, b, depth, fee_bps)) in self.pools.iter().enumerate() {
+            if a == b {
+                continue;
+            }
+            graph
+                .upsert_venue(Venue::Amm(AmmPool {
+                    address: format!("CPOOL{}", idx),
+                    pool_type: PoolType::AmmConstProd,
+                    asset_a: self.asset(a),
+                    asset_b: self.asset(b),
+                    // Spot rate a -> b is exactly value_a / value_b
+                    reserve_a: (depth * self.values[b]) as i128,
+                    reserve_b: (depth * self.values[a]) as i128,
+                    fee_bps,
+                    amplification: None,
+                }))
+                .unwrap();
+        }
+        for (selling, buying, offers) in &self.books {
+            let (selling, buying) = (*selling, *buying);
+            if selling == buying {
+                continue;
+            }
+            let mut offers = offers.clone();
+            offers.sort();
+            offers.dedup_by_key(|(spread, _)| *spread);
+            let levels = offers
+                .into_iter()
+                .map(|(spread_bps, amount)| PriceLevel {
+                    // Buying per selling, `spread_bps` above value
+                    price_n: self.values[selling] * (10_000 + spread_bps),
+                    price_d: self.values[buying] * 10_000,
+                    amount,
+                })
+                .collect();
+            graph
+                .upsert_venue(Venue::Sdex(OrderbookSide {
+                    selling: self.asset(selling),
+                    buying: self.asset(buying),
+                    levels,
+                }))
+                .unwrap();
+        }
+        graph
+    }
+}
+
+fn market(level_amounts: Range<i128>) -> impl Strategy<Value = Market> {
+    (3..=MAX_ASSETS)
+        .prop_flat_map(move |n| {
+            (
+                prop::collection::vec(1i64..=50, n),
+                prop::collection::vec((0..n, 0..n, 1_000i64..100_000_000, 1u32..=100), 0..6),
+                prop::collection::vec(
+                    (
+                        0..n,
+                        0..n,
+                        prop::collection::vec((1i64..=300, level_amounts.clone()), 1..4),
+                    ),
+                    0..6,
+                ),
+            )
+        })
+        .prop_map(|(values, pools, books)| Market {
+            values,
+            pools,
+            books,
+        })
+}
+
+/// Best output over every simple path of at most `max_hops` hops, with ties
+/// going to fewer hops
+fn oracle(
+    graph: &LiquidityGraph,
+    from: &AssetId,
+    to: &AssetId,
+    amount_in: i128,
+    max_hops: usize,
+) -> Option<(i128, usize)> {
+    fn walk(
+        graph: &LiquidityGraph,
+        path: &mut Vec<AssetId>,
+        to: &AssetId,
+        amount: i128,
+        hops_left: usize,
+        best: &mut Option<(i128, usize)>,
+    ) {
+        if hops_left == 0 {
+            return;
+        }
+        let at = path.last().unwrap().clone();
+        for edge in graph.edges_from(&at) {
+            if path.contains(edge.to) {
+                continue;
+            }
+            let out = match edge.amount_out(amount) {
+                Ok(out) if out > 0 => out,
+                _ => continue,
+            };
+            if edge.to == to {
+                let candidate = (out, path.len());
+                if best.is_none_or(|(b, h)| out > b || (out == b && candidate.1 < h)) {
+                    *best = Some(candidate);
+                }
+                continue;
+            }
+            path.push(edge.to.clone());
+            walk(graph, path, to, out, hops_left - 1, best);
+            path.pop();
+        }
+    }
+
+    let mut best = None;
+    walk(
+        graph,
+        &mut vec![from.clone()],
+        to,
+        amount_in,
+        max_hops,
+        &mut best,
+    );
+    best
+}
+
+fn hop_edge<'a>(graph: &'a LiquidityGraph, hop: &RouteHop) -> EdgeRef<'a> {
+    graph
+        .edges_from(&hop.from)
+        .find(|e| e.to == &hop.to && e.venue.id() == hop.venue)
+        .expect("route hop is an edge of the graph")
+}
+
+fn check_amounts(route: &Route, amount_in: i128) -> Result<(), TestCaseError> {
+    prop_assert_eq!(route.amount_in, amount_in);
+    prop_assert!(route.amount_out > 0);
+    let mut carried = amount_in;
+    for hop in &route.hops {
+        prop_assert_eq!(hop.amount_in, carried);
+        prop_assert!(hop.amount_in > 0 && hop.amount_out > 0);
+        carried = hop.amount_out;
+    }
+    prop_assert_eq!(carried, route.amount_out);
+    Ok(())
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(512))]
+
+    #[test]
+    fn best_route_matches_exhaustive_search(
+        market in market(DEEP),
+        from in 0..MAX_ASSETS,
+        to in 0..MAX_ASSETS,
+        amount_in in 1i128..1_000_000_000,
+    ) {
+        let (from, to) = market.pair(from, to);
+        let graph = market.graph();
+        let pathfinder = Pathfinder::default();
+
+        let found = pathfinder.find_best_route(&graph, &from, &to, amount_in).ok();
+        let expected = oracle(&graph, &from, &to, amount_in, pathfinder.config().max_hops);
+        prop_assert_eq!(
+            found.as_ref().map(|r| (r.amount_out, r.hop_count())),
+            expected
+        );
+        if let Some(route) = found {
+            prop_assert_eq!(&simulate_route(&graph, &route, amount_in).unwrap(), &route);
+        }
+    }
+
+    #[test]
+    fn thin_books_never_beat_exhaustive_search(
+        market in market(THIN),
+        from in 0..MAX_ASSETS,
+        to in 0..MAX_ASSETS,
+        amount_in in 1i128..1_000_000_000,
+    ) {
+        let (from, to) = market.pair(from, to);
+        let graph = market.graph();
+        let pathfinder = Pathfinder::default();
+
+        let found = pathfinder.find_best_route(&graph, &from, &to, amount_in).ok();
+        let expected = oracle(&graph, &from, &to, amount_in, pathfinder.config().max_hops);
+        match (&found, expected) {
+            (_, Some((_, hops))) if hops <= 2 => prop_assert_eq!(
+                found.as_ref().map(|r| (r.amount_out, r.hop_count())),
+                expected
+            ),
+            (Some(route), Some((best, _))) => prop_assert!(route.amount_out <= best),
+            (Some(_), None) => prop_assert!(false, "route found where none exists"),
+            (None, _) => {}
+        }
+        if let Some(route) = found {
+            prop_assert_eq!(&simulate_route(&graph, &route, amount_in).unwrap(), &route);
+        }
+    }
+
+    #[test]
+    fn output_is_monotonic_in_input(
+        market in market(DEEP),
+        from in 0..MAX_ASSETS,
+        to in 0..MAX_ASSETS,
+        small in 1i128..1_000_000_000,
+        extra in 0i128..1_000_000_000,
+    ) {
+        let (from, to) = market.pair(from, to);
+        let graph = market.graph();
+        let pathfinder = Pathfinder::default();
+        let large = small + extra;
+
+        let Ok(route) = pathfinder.find_best_route(&graph, &from, &to, small) else {
+            return Ok(());
+        };
+        let resized = simulate_route(&graph, &route, large).unwrap();
+        prop_assert!(resized.amount_out >= route.amount_out);
+        let best = pathfinder.find_best_route(&graph, &from, &to, large).unwrap();
+        prop_assert!(best.amount_out >= resized.amount_out);
+    }
+
+    #[test]
+    fn amounts_are_positive_and_chain(
+        market in market(THIN),
+        from in 0..MAX_ASSETS,
+        to in 0..MAX_ASSETS,
+        amount_in in 1i128..1_000_000_000,
+    ) {
+        let (from, to) = market.pair(from, to);
+        let graph = market.graph();
+
+        let routes = Pathfinder::default()
+            .find_top_routes(&graph, &from, &to, amount_in, 3, &Default::default())
+            .unwrap_or_default();
+        for route in &routes {
+            check_amounts(route, amount_in)?;
+        }
+    }
+
+    #[test]
+    fn fees_are_never_undercounted(
+        market in market(THIN),
+        from in 0..MAX_ASSETS,
+        to in 0..MAX_ASSETS,
+        amount_in in 1i128..1_000_000_000,
+    ) {
+        let (from, to) = market.pair(from, to);
+        let graph = market.graph();
+
+        let Ok(route) = Pathfinder::default().find_best_route(&graph, &from, &to, amount_in) else {
+            return Ok(());
+        };
+        for hop in &route.hops {
+            let edge = hop_edge(&graph, hop);
+            // The taker never receives more than if the fee, rounded down,
+            // had been taken from the input up front
+            let fee = hop.amount_in * edge.fee_bps() as i128 / BPS;
+            let fee_free = edge.amount_out_before_fees(hop.amount_in - fee).unwrap();
+            prop_assert!(hop.amount_out <= fee_free);
+        }
+
+        let score = score_route(&graph, &route, &ResourcePricing::default()).unwrap();
+        prop_assert!(score.gross_output >= route.amount_out);
+        prop_assert_eq!(score.fees, score.gross_output - route.amount_out);
+    }
+}
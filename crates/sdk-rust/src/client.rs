@@ -1,14 +1,67 @@
 //! StellarRoute API client
-//!
-//! Placeholder for SDK client
-//! TODO: Implement client for API interactions
+
+use reqwest::Client;
+use serde::de::DeserializeOwned;
+
+use crate::error::{Result, SdkError};
+use crate::types::{Quote, QuoteSigningKey};
+use crate::verify::QuoteVerifier;
 
 pub struct StellarRouteClient {
-    // TODO: Implement client
+    http: Client,
+    api_url: String,
 }
 
 impl StellarRouteClient {
-    pub fn new(_api_url: &str) -> Self {
-        Self {}
+    pub fn new(api_url: &str) -> Self {
+        Self {
+            http: Client::new(),
+            api_url: api_url.trim_end_matches('/').to_string(),
+        }
+    }
+
+    /// Quote for selling `amount` of `base` for `quote`
+    ///
+    /// Assets are given as "native" or "CODE:ISSUER".
+    pub async fn get_quote(&self, base: &str, quote: &str, amount: &str) -> Result<Quote> {
+        let url = format!("{}/api/v1/quote/{}/{}", self.api_url, base, quote);
+        let request = self.http.get(url).query(&[("amount", amount)]);
+        self.send(request).await
+    }
+
+    /// A previously issued quote by id, with its route
+    pub async fn get_quote_by_id(&self, quote_id: &str) -> Result<Quote> {
+        let url = format!("{}/api/v1/quote/by-id/{}", self.api_url, quote_id);
+        self.send(self.http.get(url)).await
+    }
+
+    /// Key the API signs quotes with
+    pub async fn quote_signing_key(&self) -> Result<QuoteSigningKey> {
+        let url = format!("{}/.well-known/stellarroute-quote-key", self.api_url);
+        self.send(self.http.get(url)).await
+    }
+
+    /// Like [`get_quote`](Self::get_quote), failing unless the quote is
+    /// signed by `verifier`'s key
+    pub async fn get_verified_quote(
+        &self,
+        verifier: &QuoteVerifier,
+        base: &str,
+        quote: &str,
+        amount: &str,
+    ) -> Result<Quote> {
+        let quote = self.get_quote(base, quote, amount).await?;
+        verifier.verify(&quote)?;
+        Ok(quote)
+    }
+
+    async fn send<T: DeserializeOwned>(&self, request: reqwest::RequestBuilder) -> Result<T> {
+        let response = request.send().await?;
+        let status = response.status();
+        let body = response.text().await?;
+        if !status.is_success() {
+            return Err(SdkError::Api(format!("{}: {}", status, body)));
+        }
+        Ok(serde_json::from_str(&body)?)
     }
 }
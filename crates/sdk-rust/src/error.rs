@@ -10,6 +10,9 @@ pub enum SdkError {
     #[error("API error: {0}")]
     Api(String),
 
+    #[error("Signature error: {0}")]
+    Signature(String),
+
     #[error("Serialization error: {0}")]
     Serialization(#[from] serde_json::Error),
 }
@@ -5,6 +5,8 @@
 pub mod client;
 pub mod error;
 pub mod types;
+pub mod verify;
 
 /// StellarRoute client
 pub use client::StellarRouteClient;
+pub use verify::QuoteVerifier;
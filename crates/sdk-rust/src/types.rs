@@ -2,10 +2,120 @@
 
 use serde::{Deserialize, Serialize};
 
-/// Placeholder for SDK types
-/// TODO: Define quote, orderbook, route types
+/// Asset as described by the API
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Asset {
+    pub asset_type: String,
+    pub asset_code: Option<String>,
+    pub asset_issuer: Option<String>,
+}
+
+impl Asset {
+    /// Canonical Stellar asset identifier: "native" or "CODE:ISSUER"
+    pub fn to_canonical(&self) -> String {
+        match (&self.asset_code, &self.asset_issuer) {
+            (None, _) => "native".to_string(),
+            (Some(code), Some(issuer)) => format!("{}:{}", code, issuer),
+            (Some(code), None) => code.clone(),
+        }
+    }
+}
+
+/// Step in a quote's trading path
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PathStep {
+    pub from_asset: Asset,
+    pub to_asset: Asset,
+    pub price: String,
+    /// "sdex" or "amm:{pool_address}"
+    pub source: String,
+}
+
+/// Asset as the router contract takes it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ContractAsset {
+    Native,
+    Issued {
+        issuer: String,
+        code: String,
+    },
+    /// Soroban token contract
+    Soroban {
+        address: String,
+    },
+}
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+impl ContractAsset {
+    /// "native", "CODE:ISSUER", or the token contract address
+    pub fn to_canonical(&self) -> String {
+        match self {
+            Self::Native => "native".to_string(),
+            Self::Issued { issuer, code } => format!("{}:{}", code, issuer),
+            Self::Soroban { address } => address.clone(),
+        }
+    }
+}
+
+/// Hop of a route in the router contract's format
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ContractRouteHop {
+    pub source: ContractAsset,
+    pub destination: ContractAsset,
+    /// Contract the router calls to swap
+    pub pool: String,
+    /// "sdex", "amm_const_prod" or "amm_stable"
+    pub pool_type: String,
+}
+
+/// Route in the router contract's format, to pass to `execute_swap` as is
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ContractRoute {
+    pub hops: Vec<ContractRouteHop>,
+    /// Output after the router fee, in stroops
+    pub estimated_output: i128,
+    /// Least output accepted, in stroops
+    pub min_output: i128,
+    /// Ledger sequence after which the route should not be executed
+    pub expires_at: u64,
+}
+
+/// Firm quote issued by the API
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Quote {
-    // TODO: Define quote structure
+    pub quote_id: String,
+    pub base_asset: Asset,
+    pub quote_asset: Asset,
+    pub amount: String,
+    pub price: String,
+    pub total: String,
+    pub quote_type: String,
+    pub path: Vec<PathStep>,
+    /// Percentage, e.g. "0.12"
+    pub price_impact: String,
+    pub fee: String,
+    pub min_received: String,
+    /// Most of the quote asset a buy sends; absent for a sell
+    #[serde(default)]
+    pub max_sent: Option<String>,
+    pub timestamp: i64,
+    /// Unix timestamp after which the quote can no longer be executed
+    pub valid_until: i64,
+    /// Base64 ed25519 signature over the quote and its route, when the API
+    /// signs quotes
+    #[serde(default)]
+    pub signature: Option<String>,
+    /// Route the quote was priced on; absent when the router contract cannot
+    /// execute it
+    #[serde(default)]
+    pub route: Option<ContractRoute>,
+}
+
+/// Key the API signs quotes with
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QuoteSigningKey {
+    pub algorithm: String,
+    /// Stellar account id (`G...`)
+    pub public_key: String,
+    pub message_version: String,
 }
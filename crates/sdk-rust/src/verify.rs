@@ -0,0 +1,242 @@
+//! Quote signature verification
+//!
+//! Rebuilds the message the API signs for a quote and checks the quote's
+//! signature against the API's published key. The signature covers the
+//! quote's contract route too, so a verified quote's route can be executed
+//! as is. The message format is documented with the API's signing module and
+//! named by its first line.
+
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use ed25519_dalek::{Signature, Verifier, VerifyingKey};
+use stellar_strkey::ed25519::PublicKey;
+
+use crate::error::{Result, SdkError};
+use crate::types::Quote;
+
+/// Message format this SDK can verify
+pub const MESSAGE_VERSION: &str = "stellarroute-quote-v2";
+
+/// Checks quote signatures against one public key
+#[derive(Debug, Clone)]
+pub struct QuoteVerifier {
+    key: VerifyingKey,
+}
+
+impl QuoteVerifier {
+    /// Verifier for a Stellar account id (`G...`)
+    pub fn from_public_key(public_key: &str) -> Result<Self> {
+        let public = PublicKey::from_string(public_key.trim())
+            .map_err(|e| SdkError::Signature(format!("invalid public key: {}", e)))?;
+        let key = VerifyingKey::from_bytes(&public.0)
+            .map_err(|e| SdkError::Signature(format!("invalid public key: {}", e)))?;
+        Ok(Self { key })
+    }
+
+    /// Fails unless `quote` carries a valid signature by this key
+    pub fn verify(&self, quote: &Quote) -> Result<()> {
+        let encoded = quote
+            .signature
+            .as_deref()
+            .ok_or_else(|| SdkError::Signature("quote is not signed".to_string()))?;
+        let bytes = BASE64
+            .decode(encoded)
+            .map_err(|e| SdkError::Signature(format!("malformed signature: {}", e)))?;
+        let signature = Signature::from_slice(&bytes)
+            .map_err(|e| SdkError::Signature(format!("malformed signature: {}", e)))?;
+        self.key
+            .verify(signing_message(quote).as_bytes(), &signature)
+            .map_err(|_| SdkError::Signature("signature does not match quote".to_string()))
+    }
+}
+
+/// The message a quote's signature covers
+pub fn signing_message(quote: &Quote) -> String {
+    let mut lines = vec![
+        MESSAGE_VERSION.to_string(),
+        format!("quote_id:{}", quote.quote_id),
+        format!("base:{}", quote.base_asset.to_canonical()),
+        format!("quote:{}", quote.quote_asset.to_canonical()),
+        format!("type:{}", quote.quote_type),
+        format!("amount:{}", quote.amount),
+        format!("price:{}", quote.price),
+        format!("total:{}", quote.total),
+        format!("fee:{}", quote.fee),
+        format!("min_received:{}", quote.min_received),
+    ];
+    if let Some(max_sent) = &quote.max_sent {
+        lines.push(format!("max_sent:{}", max_sent));
+    }
+    lines.extend([
+        format!("price_impact:{}", quote.price_impact),
+        format!("timestamp:{}", quote.timestamp),
+        format!("valid_until:{}", quote.valid_until),
+    ]);
+    lines.extend(quote.path.iter().map(|step| {
+        format!(
+            "hop:{}>{} {} {}",
+            step.from_asset.to_canonical(),
+            step.to_asset.to_canonical(),
+            step.source,
+            step.price
+        )
+    }));
+    match &quote.route {
+        Some(route) => {
+            lines.push(format!(
+                "route:{} {} {}",
+                route.estimated_output, route.min_output, route.expires_at
+            ));
+            lines.extend(route.hops.iter().map(|hop| {
+                format!(
+                    "route_hop:{}>{} {} {}",
+                    hop.source.to_canonical(),
+                    hop.destination.to_canonical(),
+                    hop.pool,
+                    hop.pool_type
+                )
+            }));
+        }
+        None => lines.push("route:none".to_string()),
+    }
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ed25519_dalek::{Signer, SigningKey};
+    use serde_json::json;
+
+    /// A signed quote as the API serves it
+    fn quote() -> Quote {
+        serde_json::from_value(json!({
+            "quote_id": "2b1f0c1e-8f4e-4c52-9d0a-4e0f5b8a7c11",
+            "base_asset": { "asset_type": "native" },
+            "quote_asset": {
+                "asset_type": "credit_alphanum4",
+                "asset_code": "USDC",
+                "asset_issuer": "GISSUER"
+            },
+            "amount": "100.0000000",
+            "price": "0.1000000",
+            "total": "10.0000000",
+            "quote_type": "sell",
+            "path": [{
+                "from_asset": { "asset_type": "native" },
+                "to_asset": {
+                    "asset_type": "credit_alphanum4",
+                    "asset_code": "USDC",
+                    "asset_issuer": "GISSUER"
+                },
+                "price": "0.1000000",
+                "source": "sdex"
+            }],
+            "price_impact": "0.12",
+            "fee": "0.0300000",
+            "min_received": "9.9500000",
+            "timestamp": 1700000000,
+            "valid_until": 1700000030,
+            "route": {
+                "hops": [{
+                    "source": { "kind": "native" },
+                    "destination": { "kind": "issued", "issuer": "GISSUER", "code": "USDC" },
+                    "pool": "CADAPTER",
+                    "pool_type": "sdex"
+                }],
+                "estimated_output": 99700000,
+                "min_output": 99200000,
+                "expires_at": 51000007
+            }
+        }))
+        .unwrap()
+    }
+
+    fn signed(key: &SigningKey) -> Quote {
+        let mut quote = quote();
+        let signature = key.sign(signing_message(&quote).as_bytes());
+        quote.signature = Some(BASE64.encode(signature.to_bytes()));
+        quote
+    }
+
+    fn verifier(key: &SigningKey) -> QuoteVerifier {
+        let public_key = PublicKey(key.verifying_key().to_bytes()).to_string();
+        QuoteVerifier::from_public_key(&public_key).unwrap()
+    }
+
+    #[test]
+    fn test_signing_message_layout() {
+        assert_eq!(
+            signing_message(&quote()),
+            "stellarroute-quote-v2\n\
+             quote_id:2b1f0c1e-8f4e-4c52-9d0a-4e0f5b8a7c11\n\
+             base:native\n\
+             quote:USDC:GISSUER\n\
+             type:sell\n\
+             amount:100.0000000\n\
+             price:0.1000000\n\
+             total:10.0000000\n\
+             fee:0.0300000\n\
+             min_received:9.9500000\n\
+             price_impact:0.12\n\
+             timestamp:1700000000\n\
+             valid_until:1700000030\n\
+             hop:native>USDC:GISSUER sdex 0.1000000\n\
+             route:99700000 99200000 51000007\n\
+             route_hop:native>USDC:GISSUER CADAPTER sdex"
+        );
+
+        let mut buy = quote();
+        buy.quote_type = "buy".to_string();
+        buy.max_sent = Some("10.0500000".to_string());
+        buy.route = None;
+        let message = signing_message(&buy);
+        assert!(message.contains("\nmin_received:9.9500000\nmax_sent:10.0500000\nprice_impact:"));
+        assert!(message.ends_with("\nroute:none"));
+    }
+
+    #[test]
+    fn test_verifies_signed_quotes() {
+        let key = SigningKey::from_bytes(&[7; 32]);
+        assert!(verifier(&key).verify(&signed(&key)).is_ok());
+    }
+
+    #[test]
+    fn test_rejects_altered_unsigned_or_foreign_quotes() {
+        let key = SigningKey::from_bytes(&[7; 32]);
+        let verifier = verifier(&key);
+
+        let mut altered = signed(&key);
+        altered.path[0].source = "amm:CPOOL".to_string();
+        assert!(verifier.verify(&altered).is_err());
+
+        let mut expired_later = signed(&key);
+        expired_later.valid_until += 60;
+        assert!(verifier.verify(&expired_later).is_err());
+
+        let mut other_pool = signed(&key);
+        other_pool.route.as_mut().unwrap().hops[0].pool = "CELSEWHERE".to_string();
+        assert!(verifier.verify(&other_pool).is_err());
+
+        let mut looser = signed(&key);
+        looser.route.as_mut().unwrap().min_output -= 1;
+        assert!(verifier.verify(&looser).is_err());
+
+        let mut route_later = signed(&key);
+        route_later.route.as_mut().unwrap().expires_at += 100;
+        assert!(verifier.verify(&route_later).is_err());
+
+        let mut unrouted = signed(&key);
+        unrouted.route = None;
+        assert!(verifier.verify(&unrouted).is_err());
+
+        assert!(verifier.verify(&quote()).is_err());
+
+        let other = SigningKey::from_bytes(&[8; 32]);
+        assert!(verifier.verify(&signed(&other)).is_err());
+    }
+
+    #[test]
+    fn test_rejects_malformed_public_keys() {
+        assert!(QuoteVerifier::from_public_key("GNOTAKEY").is_err());
+    }
+}
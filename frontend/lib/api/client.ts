@@ -9,11 +9,14 @@
  */
 
 import type {
+  CandleInterval,
+  CandlesResponse,
   HealthStatus,
   Orderbook,
   PairsResponse,
   PriceQuote,
   QuoteType,
+  Ticker,
 } from '@/types';
 
 // ---------------------------------------------------------------------------
@@ -165,6 +168,40 @@ export class StellarRouteClient {
     return this.request<Orderbook>(path, opts);
   }
 
+  /**
+   * GET /api/v1/candles/{base}/{quote}?interval={1m|5m|1h|1d}
+   *
+   * @param base     Asset identifier
+   * @param quote    Asset identifier
+   * @param interval Candle width (default "1h")
+   * @param range    Optional `from`/`to` Unix timestamps and `limit`
+   */
+  getCandles(
+    base: string,
+    quote: string,
+    interval: CandleInterval = '1h',
+    range: { from?: number; to?: number; limit?: number } = {},
+    opts?: FetchOptions,
+  ): Promise<CandlesResponse> {
+    const params = new URLSearchParams({ interval });
+    for (const [key, value] of Object.entries(range)) {
+      if (value !== undefined) params.set(key, String(value));
+    }
+    const path = `/api/v1/candles/${encodeURIComponent(base)}/${encodeURIComponent(quote)}?${params}`;
+    return this.request<CandlesResponse>(path, opts);
+  }
+
+  /**
+   * GET /api/v1/ticker/{base}/{quote} — 24h open, high, low, close, volume
+   *
+   * @param base  Asset identifier
+   * @param quote Asset identifier
+   */
+  getTicker(base: string, quote: string, opts?: FetchOptions): Promise<Ticker> {
+    const path = `/api/v1/ticker/${encodeURIComponent(base)}/${encodeURIComponent(quote)}`;
+    return this.request<Ticker>(path, opts);
+  }
+
   /**
    * GET /api/v1/quote/{base}/{quote}?amount={amount}&quote_type={sell|buy}
    *
@@ -15,6 +15,10 @@ export interface TradingPair {
   counter_asset: string;
   offer_count: number;
   last_updated?: string;
+  /** Price of the most recent trade, in counter asset */
+  last_price?: string;
+  /** Base asset traded over the last 24 hours */
+  volume_24h: string;
 }
 
 export interface PairsResponse {
@@ -37,6 +41,55 @@ export interface Orderbook {
   timestamp: number;
 }
 
+export type CandleInterval = '1m' | '5m' | '1h' | '1d';
+
+/** Trades of one interval, priced in quote asset per base asset */
+export interface Candle {
+  /** Start of the interval, Unix timestamp (seconds) */
+  time: number;
+  open: string;
+  high: string;
+  low: string;
+  close: string;
+  /** Base asset traded */
+  volume: string;
+  /** Quote asset traded */
+  quote_volume: string;
+  trades: number;
+}
+
+export interface CandlesResponse {
+  base_asset: Asset;
+  quote_asset: Asset;
+  interval: CandleInterval;
+  /** Ascending by time; intervals without trades are left out */
+  candles: Candle[];
+}
+
+/** 24h statistics; prices are absent when nothing traded in the window */
+export interface Ticker {
+  base_asset: Asset;
+  quote_asset: Asset;
+  open?: string;
+  high?: string;
+  low?: string;
+  close?: string;
+  volume: string;
+  quote_volume: string;
+  trades: number;
+  change?: string;
+  /** Change relative to open, in percent */
+  change_percent?: string;
+  /** Mid price of the latest orderbook snapshot */
+  mid_price?: string;
+  /** Unix timestamp (seconds) */
+  last_trade_at?: number;
+  /** Window start, Unix timestamp (seconds) */
+  from: number;
+  /** Window end, Unix timestamp (seconds) */
+  to: number;
+}
+
 export type QuoteType = 'sell' | 'buy';
 
 export interface PathStep {
@@ -47,7 +100,34 @@ export interface PathStep {
   source: string;
 }
 
+/** Asset as the router contract takes it */
+export type ContractAsset =
+  | { kind: 'native' }
+  | { kind: 'issued'; issuer: string; code: string }
+  | { kind: 'soroban'; address: string };
+
+export interface ContractRouteHop {
+  source: ContractAsset;
+  destination: ContractAsset;
+  /** Contract the router calls to swap */
+  pool: string;
+  pool_type: 'sdex' | 'amm_const_prod' | 'amm_stable';
+}
+
+/** Route in the router contract's format, to pass to `execute_swap` as is */
+export interface ContractRoute {
+  hops: ContractRouteHop[];
+  /** Output after the router fee, in stroops */
+  estimated_output: number;
+  /** Least output accepted, in stroops */
+  min_output: number;
+  /** Ledger sequence after which the route should not be executed */
+  expires_at: number;
+}
+
 export interface PriceQuote {
+  /** Id to fetch the quote back by, or to execute it */
+  quote_id: string;
   base_asset: Asset;
   quote_asset: Asset;
   amount: string;
@@ -55,8 +135,22 @@ export interface PriceQuote {
   total: string;
   quote_type: QuoteType;
   path: PathStep[];
+  /** Execution price against spot along the path, in percent */
+  price_impact: string;
+  /** Swap fees paid along the path and the router's fee, in the asset received */
+  fee: string;
+  /** Least amount received within the requested slippage, after the router's fee */
+  min_received: string;
+  /** Most of the quote asset a buy sends within the requested slippage */
+  max_sent?: string;
   /** Unix timestamp (seconds) */
   timestamp: number;
+  /** Unix timestamp (seconds) after which the quote cannot be executed */
+  valid_until: number;
+  /** Base64 ed25519 signature over the quote and its route */
+  signature?: string;
+  /** Absent when the router contract cannot execute the route */
+  route?: ContractRoute;
 }
 
 export interface HealthStatus {